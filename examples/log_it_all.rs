@@ -25,7 +25,7 @@ pub fn main() -> io::Result<()> {
         false,
         "winecfg",
         DebugRules::new().enable(DebugChannel::All),
-    );
+    )?;
     eprintln!("{command:?}");
     command.spawn()?.wait()?;
     Ok(())
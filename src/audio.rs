@@ -0,0 +1,101 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::env;
+
+use crate::{
+    DllOverride, DllOverrideMode, ResolvedCommand, WineCommand,
+    settings::AudioDriver,
+};
+
+/// The audio drivers of Wine, each of which WINEDLLOVERRIDES can keep from
+/// loading.
+const DRIVER_DLLS: &[(AudioDriver, &str)] = &[
+    (AudioDriver::Pulse, "winepulse.drv"),
+    (AudioDriver::Alsa, "winealsa.drv"),
+    (AudioDriver::Oss, "wineoss.drv"),
+    (AudioDriver::CoreAudio, "winecoreaudio.drv"),
+];
+
+/// The sound server a launch plays through, e.g., the one of the desktop
+/// session when the program runs in a container or as another user.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AudioServer {
+    /// PulseAudio, or PipeWire through `pipewire-pulse`, at `server`, e.g.,
+    /// `unix:/run/user/1000/pulse/native`. `None` forwards `PULSE_SERVER`
+    /// and `PULSE_COOKIE` from the caller even if the environment is cleared.
+    Pulse { server: Option<String> },
+    /// PipeWire at `remote`, e.g., `pipewire-0`, with `None` forwarding
+    /// `PIPEWIRE_REMOTE` like [`AudioServer::Pulse`]. Wine has no PipeWire
+    /// driver, so it still plays through its PulseAudio one.
+    PipeWire { remote: Option<String> },
+}
+
+impl AudioServer {
+    fn apply(&self, command: &mut ResolvedCommand) {
+        let (variable, value, forwarded): (_, _, &[&str]) = match self {
+            Self::Pulse { server } => {
+                ("PULSE_SERVER", server, &["PULSE_SERVER", "PULSE_COOKIE"])
+            }
+            Self::PipeWire { remote } => (
+                "PIPEWIRE_REMOTE",
+                remote,
+                &["PIPEWIRE_REMOTE", "PULSE_SERVER", "PULSE_COOKIE"],
+            ),
+        };
+        if command.env_clear {
+            for name in forwarded {
+                if let Some(value) = env::var_os(name) {
+                    command.envs.push((name.into(), value));
+                }
+            }
+        }
+        if let Some(value) = value {
+            command.envs.push((variable.into(), value.into()));
+        }
+    }
+}
+
+/// Keeps every audio driver but `driver` from loading, which leaves Wine no
+/// other choice regardless of the `Drivers` registry key.
+pub(crate) fn driver_overrides(driver: AudioDriver) -> Vec<DllOverride> {
+    DRIVER_DLLS
+        .iter()
+        .filter(|(candidate, _)| *candidate != driver)
+        .map(|(_, dll)| DllOverride::new(*dll, DllOverrideMode::Disabled))
+        .collect()
+}
+
+impl WineCommand<'_> {
+    /// Makes the launch use `driver`, without changing the one configured
+    /// for the prefix through [`crate::Prefix::set_audio_driver`].
+    pub fn audio_driver(&mut self, driver: AudioDriver) -> &mut Self {
+        self.audio_driver = Some(driver);
+        self
+    }
+
+    /// Routes the audio of the launch to `server` through the PulseAudio
+    /// driver, unless [`WineCommand::audio_driver`] picks another.
+    pub fn audio_server(&mut self, server: AudioServer) -> &mut Self {
+        self.audio_server = Some(server);
+        self
+    }
+
+    pub(crate) fn apply_audio(&self, command: &mut ResolvedCommand) {
+        if let Some(server) = &self.audio_server {
+            server.apply(command);
+        }
+    }
+
+    pub(crate) fn audio_dll_overrides(&self) -> Vec<DllOverride> {
+        let driver = match (&self.audio_driver, &self.audio_server) {
+            (Some(driver), _) => *driver,
+            (None, Some(_)) => AudioDriver::Pulse,
+            (None, None) => return Vec::new(),
+        };
+        driver_overrides(driver)
+    }
+}
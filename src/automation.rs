@@ -0,0 +1,124 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    ffi::OsStr,
+    io, thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    Error, Prefix, WineWindow,
+    window::{find_titled, host_tool},
+};
+
+/// How often [`Prefix::wait_for_window`] looks for the window.
+const WINDOW_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    #[default]
+    Left,
+    Middle,
+    Right,
+}
+
+impl MouseButton {
+    /// The X11 button number.
+    fn number(self) -> &'static str {
+        match self {
+            Self::Left => "1",
+            Self::Middle => "2",
+            Self::Right => "3",
+        }
+    }
+}
+
+fn xdotool(
+    args: impl IntoIterator<Item = impl AsRef<OsStr>>,
+) -> io::Result<()> {
+    if cfg!(target_os = "macos") {
+        return Err(Error::Unsupported(
+            "windows can only be automated under X11".into(),
+        )
+        .into());
+    }
+    host_tool("xdotool", "xdotool", args).map(drop)
+}
+
+// Input is synthesized through `xdotool` as if a user gave it, rather than
+// sent to the window, which Windows programs may ignore. Keystrokes go to the
+// window with the focus, so each of these focuses the window first.
+impl WineWindow {
+    fn id_arg(&self) -> String {
+        self.id.to_string()
+    }
+
+    /// Raises the window and gives it the focus, or only the focus under
+    /// window managers that cannot activate windows.
+    pub fn activate(&self) -> io::Result<()> {
+        xdotool(["windowactivate", "--sync", &self.id_arg()])
+            .or_else(|_| xdotool(["windowfocus", "--sync", &self.id_arg()]))
+    }
+
+    /// Types `text` into the window as if on the keyboard, e.g., to fill in
+    /// a field of an installer wizard that has no silent mode.
+    pub fn type_text(&self, text: &str) -> io::Result<()> {
+        self.activate()?;
+        xdotool(["type", "--clearmodifiers", "--delay", "20", "--", text])
+    }
+
+    /// Presses and releases each key combination of `keys`, which are
+    /// separated by spaces and written as X11 key names joined with `+`,
+    /// e.g., `alt+n` for the Next button of a wizard or `Tab Tab Return`.
+    pub fn press_keys(&self, keys: &str) -> io::Result<()> {
+        self.activate()?;
+        let mut args = vec!["key", "--clearmodifiers", "--"];
+        args.extend(keys.split_whitespace());
+        xdotool(args)
+    }
+
+    /// Clicks `button` at `x` and `y` pixels from the top left corner of the
+    /// window.
+    pub fn click(&self, x: u32, y: u32, button: MouseButton) -> io::Result<()> {
+        self.activate()?;
+        xdotool([
+            "mousemove",
+            "--sync",
+            "--window",
+            &self.id_arg(),
+            &x.to_string(),
+            &y.to_string(),
+            "click",
+            button.number(),
+        ])
+    }
+}
+
+impl Prefix {
+    /// Blocks until the prefix shows a window found by
+    /// [`Prefix::find_window`], e.g., the next page of an installer, failing
+    /// with [`Error::Timeout`] after `timeout`.
+    pub fn wait_for_window(
+        &self,
+        title: &str,
+        timeout: Duration,
+    ) -> io::Result<WineWindow> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(window) = find_titled(&self.windows()?, title) {
+                return Ok(window.clone());
+            }
+            if Instant::now() >= deadline {
+                return Err(Error::Timeout(format!(
+                    "waiting for a window titled `{title}`"
+                ))
+                .into());
+            }
+            thread::sleep(WINDOW_POLL_INTERVAL);
+        }
+    }
+}
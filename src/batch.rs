@@ -0,0 +1,140 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    io,
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+
+use crate::{Prefix, PrefixBuilder, PrefixStore, Profile, VerifyReport};
+
+/// What an operation of a [`PrefixBatch`] did to one prefix of the store.
+#[derive(Debug)]
+pub struct BatchResult<T> {
+    /// The name of the prefix in the store.
+    pub name: String,
+    pub result: io::Result<T>,
+}
+
+type Configure<'a> = Box<dyn Fn(&str, &mut PrefixBuilder) + Sync + 'a>;
+
+/// Runs an operation on every prefix of a [`PrefixStore`], several at a
+/// time, started with [`PrefixStore::batch`]. A prefix whose operation fails
+/// does not stop the others, which is reported in its [`BatchResult`].
+pub struct PrefixBatch<'a> {
+    store: &'a PrefixStore,
+    parallelism: usize,
+    configure: Option<Configure<'a>>,
+}
+
+impl<'a> PrefixBatch<'a> {
+    /// How many prefixes are worked on at once, which defaults to the
+    /// available parallelism of the host.
+    pub fn parallelism(&mut self, parallelism: usize) -> &mut Self {
+        self.parallelism = parallelism.max(1);
+        self
+    }
+
+    /// Calls `configure` with the name and builder of each prefix before it
+    /// is built, e.g., to give the Wine binaries of prefixes that have no
+    /// `bin/` of their own.
+    pub fn configure(
+        &mut self,
+        configure: impl Fn(&str, &mut PrefixBuilder) + Sync + 'a,
+    ) -> &mut Self {
+        self.configure = Some(Box::new(configure));
+        self
+    }
+
+    /// Runs `operation` on every prefix, returning the results in the order
+    /// of [`PrefixStore::names`]. Only failing to list the store fails the
+    /// batch as a whole.
+    pub fn run<T: Send>(
+        &self,
+        operation: impl Fn(&Prefix) -> io::Result<T> + Sync,
+    ) -> io::Result<Vec<BatchResult<T>>> {
+        let names = self.store.names()?;
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(Vec::with_capacity(names.len()));
+        thread::scope(|scope| {
+            for _ in 0..self.parallelism.min(names.len()) {
+                scope.spawn(|| {
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(name) = names.get(index) else {
+                            break;
+                        };
+                        let result =
+                            self.store.builder(name).and_then(|mut builder| {
+                                if let Some(configure) = &self.configure {
+                                    configure(name, &mut builder);
+                                }
+                                operation(&builder.build())
+                            });
+                        results.lock().unwrap().push((index, result));
+                    }
+                });
+            }
+        });
+        let mut results = results.into_inner().unwrap();
+        results.sort_by_key(|(index, _)| *index);
+        Ok(results
+            .into_iter()
+            .zip(names)
+            .map(|((_, result), name)| BatchResult { name, result })
+            .collect())
+    }
+
+    /// Runs [`Prefix::update`] on every prefix.
+    pub fn update(&self) -> io::Result<Vec<BatchResult<()>>> {
+        self.run(Prefix::update)
+    }
+
+    /// Runs [`Prefix::verify`] on every prefix.
+    pub fn verify(&self) -> io::Result<Vec<BatchResult<VerifyReport>>> {
+        self.run(Prefix::verify)
+    }
+
+    /// Runs [`Prefix::apply_profile`] with `profile` on every prefix.
+    pub fn apply_profile(
+        &self,
+        profile: &Profile,
+    ) -> io::Result<Vec<BatchResult<()>>> {
+        self.run(|prefix| prefix.apply_profile(profile))
+    }
+
+    /// Runs [`Prefix::clean_temp`] on every prefix, each of which reports
+    /// the bytes it freed.
+    pub fn clean_temp(&self) -> io::Result<Vec<BatchResult<u64>>> {
+        self.run(Prefix::clean_temp)
+    }
+}
+
+impl PrefixStore {
+    /// Begins an operation on every prefix in the store, e.g., to update
+    /// dozens of them after upgrading Wine.
+    pub fn batch(&self) -> PrefixBatch<'_> {
+        PrefixBatch {
+            store: self,
+            parallelism: thread::available_parallelism()
+                .map_or(1, |parallelism| parallelism.get()),
+            configure: None,
+        }
+    }
+
+    /// Runs `operation` on every prefix in the store with the defaults of
+    /// [`PrefixStore::batch`].
+    pub fn for_each<T: Send>(
+        &self,
+        operation: impl Fn(&Prefix) -> io::Result<T> + Sync,
+    ) -> io::Result<Vec<BatchResult<T>>> {
+        self.batch().run(operation)
+    }
+}
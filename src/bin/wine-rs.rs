@@ -0,0 +1,268 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use wine::{
+    LaunchRecording, MANIFEST_AREAS, Prefix, PrefixConfig, Profile, StartMode,
+    ToJson, WineCommand,
+    registry::{RegistryKey, RegistryValue},
+};
+
+const USAGE: &str = "\
+usage: wine-rs [options] <command> [arguments]
+
+commands:
+  create <prefix>                     initialize a new prefix
+  provision <prefix> <profile>        initialize a prefix if needed and apply
+                                      the settings of a profile file
+  list <directory>                    list the prefixes in a directory
+  run <prefix> <program> [args...]    run a program in a prefix
+  record <prefix> <bundle> <program> [args...]
+                                      run a program, saving a bug report
+  replay <bundle> [<prefix>]          rerun a recorded launch, optionally
+                                      in another prefix
+  ps <prefix>                         list the Windows processes in a prefix
+  kill <prefix>                       kill every process in a prefix
+  reg query <prefix> <key> [<value>]  print a registry value
+
+options:
+  -L <directory>  add a dynamic library search path (repeatable)
+  --esync         enable esync
+  --msync         enable msync
+  --start         run the program through start.exe
+  --env-clear     do not inherit the environment of wine-rs
+  --json          print the results of list, ps, and reg query as JSON
+  -h, --help      print this message";
+
+#[derive(Default)]
+struct Options {
+    library_paths: Vec<PathBuf>,
+    config: PrefixConfig,
+    start: bool,
+    env_clear: bool,
+    json: bool,
+}
+
+impl Options {
+    fn prefix(&self, path: impl AsRef<Path>) -> io::Result<Prefix> {
+        Prefix::try_at(path, &self.library_paths, self.config.clone())
+    }
+
+    fn wine_command<'a>(
+        &self,
+        prefix: &'a Prefix,
+        program: &str,
+        args: &[String],
+    ) -> WineCommand<'a> {
+        let mut command = prefix.wine_command(program);
+        command.args(args);
+        if self.start {
+            command.start_mode(StartMode::StartExe);
+        }
+        if self.env_clear {
+            command.env_clear();
+        }
+        command
+    }
+}
+
+/// `None` meaning the program was killed by a signal. Codes that do not fit
+/// in a byte are reported as failures rather than truncated, possibly to 0.
+fn exit_code(code: Option<i32>) -> ExitCode {
+    match code.map(u8::try_from) {
+        Some(Ok(code)) => ExitCode::from(code),
+        Some(Err(_)) | None => ExitCode::FAILURE,
+    }
+}
+
+fn usage_error(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn list(options: &Options, directory: &Path) -> io::Result<()> {
+    let mut prefixes = Vec::new();
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.join("system.reg").is_file() {
+            prefixes.push(path);
+        }
+    }
+    prefixes.sort();
+    if options.json {
+        println!("{}", prefixes.to_json()?);
+        return Ok(());
+    }
+    for prefix in prefixes {
+        println!("{}", prefix.display());
+    }
+    Ok(())
+}
+
+fn run(options: &Options, arguments: &[String]) -> io::Result<ExitCode> {
+    let [command, rest @ ..] = arguments else {
+        return Err(usage_error("missing command"));
+    };
+    match (command.as_str(), rest) {
+        ("create", [prefix]) => options.prefix(prefix)?.initialize()?,
+        ("provision", [prefix, profile]) => {
+            let profile = Profile::load(profile)?;
+            let prefix = options.prefix(prefix)?;
+            if !prefix.is_initialized() {
+                prefix.initialize()?;
+            }
+            prefix.apply_profile(&profile)?;
+        }
+        ("list", [directory]) => list(options, Path::new(directory))?,
+        ("run", [prefix, program, args @ ..]) => {
+            let prefix = options.prefix(prefix)?;
+            let status =
+                options.wine_command(&prefix, program, args).status()?;
+            return Ok(exit_code(status.code()));
+        }
+        ("record", [prefix, bundle, program, args @ ..]) => {
+            let prefix = options.prefix(prefix)?;
+            let recording =
+                options.wine_command(&prefix, program, args).record()?;
+            recording.save(bundle)?;
+            io::stdout().write_all(&recording.stdout)?;
+            io::stderr().write_all(&recording.stderr)?;
+            eprintln!("wine-rs: saved the launch to {bundle}");
+            return Ok(exit_code(recording.exit_code));
+        }
+        ("replay", [bundle, prefix @ ..]) if prefix.len() <= 1 => {
+            let recording = LaunchRecording::load(bundle)?;
+            eprintln!(
+                "wine-rs: recorded with {}, exiting with {}",
+                recording.wine_version.as_deref().unwrap_or("unknown Wine"),
+                recording
+                    .exit_code
+                    .map_or("a signal".into(), |code| code.to_string()),
+            );
+            let (prefix, command) = match prefix.first() {
+                Some(prefix) => {
+                    (options.prefix(prefix)?, recording.command_for(prefix))
+                }
+                None => (
+                    options.prefix(&recording.prefix)?,
+                    recording.command.clone(),
+                ),
+            };
+            let report = recording.manifest.verify(&prefix, MANIFEST_AREAS)?;
+            for path in report.modified {
+                eprintln!("wine-rs: {} differs", path.display());
+            }
+            for path in report.missing {
+                eprintln!("wine-rs: {} is missing", path.display());
+            }
+            let status = command.to_command().status()?;
+            return Ok(exit_code(status.code()));
+        }
+        ("ps", [prefix]) => {
+            let processes = options.prefix(prefix)?.processes()?;
+            if options.json {
+                println!("{}", processes.to_json()?);
+            } else {
+                for process in processes {
+                    println!(
+                        "{:08x} {:>4} {}",
+                        process.pid, process.threads, process.image_name
+                    );
+                }
+            }
+        }
+        ("kill", [prefix]) => {
+            options.prefix(prefix)?.kill_all()?;
+        }
+        ("reg", [subcommand, prefix, key, value @ ..])
+            if subcommand == "query" && value.len() <= 1 =>
+        {
+            let key: RegistryKey = key.parse()?;
+            let name = value.first().map(String::as_str);
+            let value = options.prefix(prefix)?.registry_value(&key, name)?;
+            if options.json
+                && let Some(value) = &value
+            {
+                println!("{}", value.to_json()?);
+                return Ok(ExitCode::SUCCESS);
+            }
+            match value {
+                Some(
+                    RegistryValue::String(string)
+                    | RegistryValue::ExpandString(string),
+                ) => println!("{string}"),
+                Some(RegistryValue::MultiString(strings)) => {
+                    for string in strings {
+                        println!("{string}");
+                    }
+                }
+                Some(RegistryValue::Binary(bytes)) => {
+                    let hex: Vec<_> = bytes
+                        .iter()
+                        .map(|byte| format!("{byte:02x}"))
+                        .collect();
+                    println!("{}", hex.join(" "));
+                }
+                Some(RegistryValue::DWord(dword)) => println!("{dword:#x}"),
+                Some(RegistryValue::QWord(qword)) => println!("{qword:#x}"),
+                None => {
+                    eprintln!("wine-rs: no such registry value");
+                    return Ok(ExitCode::FAILURE);
+                }
+            }
+        }
+        _ => return Err(usage_error("unknown command or wrong arguments")),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn main() -> ExitCode {
+    let mut options = Options::default();
+    let mut arguments = env::args().skip(1);
+    let mut rest = Vec::new();
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            "-L" => match arguments.next() {
+                Some(path) => options.library_paths.push(path.into()),
+                None => {
+                    eprintln!("wine-rs: -L requires a directory\n\n{USAGE}");
+                    return ExitCode::FAILURE;
+                }
+            },
+            "--esync" => options.config.esync = true,
+            "--msync" => options.config.msync = true,
+            "--start" => options.start = true,
+            "--env-clear" => options.env_clear = true,
+            "--json" => options.json = true,
+            _ => {
+                rest.push(argument);
+                rest.extend(arguments);
+                break;
+            }
+        }
+    }
+
+    match run(&options, &rest) {
+        Ok(code) => code,
+        Err(error) if error.kind() == io::ErrorKind::InvalidInput => {
+            eprintln!("wine-rs: {error}\n\n{USAGE}");
+            ExitCode::FAILURE
+        }
+        Err(error) => {
+            eprintln!("wine-rs: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
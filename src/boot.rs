@@ -0,0 +1,150 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{ffi::OsStr, fs, io, time::UNIX_EPOCH};
+
+use crate::{
+    DllOverride, DllOverrideMode, Prefix, PrefixEvent, REGISTRY_FILES, trace,
+    tree,
+};
+
+/// What `wineboot --init` creates besides [`REGISTRY_FILES`].
+const BOOT_FILES: &[&str] = &["drive_c", "dosdevices", ".update-timestamp"];
+
+impl Prefix {
+    /// Whether `wineboot` has populated the prefix.
+    pub fn is_initialized(&self) -> bool {
+        self.path.join("system.reg").is_file()
+    }
+
+    /// Creates the Windows directory tree and registry of the prefix, as a
+    /// `win32` one if `WINEARCH` says so, which builds using
+    /// [`crate::Wow64Mode::New`] refuse. Those builds create a `win64` one
+    /// otherwise, whose 32-bit programs they run too.
+    pub fn initialize(&self) -> io::Result<()> {
+        trace::operation("initialize", &self.path, None, || {
+            let _lock = self.lock()?;
+            let mut command = self.builtin_command("wineboot");
+            command.arg("--init");
+            if self.config.skip_mono_gecko_install {
+                // The installers are only offered when these libraries load.
+                command
+                    .dll_override(DllOverride::new(
+                        "mscoree",
+                        DllOverrideMode::Disabled,
+                    ))
+                    .dll_override(DllOverride::new(
+                        "mshtml",
+                        DllOverrideMode::Disabled,
+                    ));
+            }
+            self.prepare_creation_arch(&mut command)?;
+            self.check_cancelled()?;
+            let was_initialized = self.is_initialized();
+            self.report_progress("running wineboot", 0, 1);
+            if let Err(error) = command.output_checked() {
+                if !was_initialized && self.check_cancelled().is_err() {
+                    self.remove_boot_files()?;
+                }
+                return Err(error);
+            }
+            self.report_progress("created the prefix", 1, 1);
+            self.emit(|| PrefixEvent::PrefixCreated {
+                prefix: self.path.clone(),
+            });
+            Ok(())
+        })
+    }
+
+    /// Removes what an interrupted `wineboot --init` left behind, keeping the
+    /// rest of the prefix directory, which may hold the Wine build too.
+    fn remove_boot_files(&self) -> io::Result<()> {
+        for item in BOOT_FILES.iter().chain(REGISTRY_FILES) {
+            tree::remove_tree(&self.path.join(item))?;
+        }
+        Ok(())
+    }
+
+    /// Applies [`crate::PrefixConfig::auto_initialize`] before launching
+    /// `program`.
+    pub(crate) fn initialize_for_launch(
+        &self,
+        program: &OsStr,
+    ) -> io::Result<()> {
+        // Initializing launches `wineboot` itself.
+        if !self.config.auto_initialize
+            || program == "wineboot"
+            || self.is_initialized()
+        {
+            return Ok(());
+        }
+        self.emit(|| PrefixEvent::InitializingPrefix {
+            prefix: self.path.clone(),
+        });
+        self.initialize()
+    }
+
+    /// Whether the prefix was last updated by a different Wine build than the
+    /// one it is configured with, comparing `.update-timestamp` against the
+    /// modification time of the build's `wine.inf` like the loader does.
+    pub fn needs_update(&self) -> io::Result<bool> {
+        let timestamp =
+            match fs::read_to_string(self.path.join(".update-timestamp")) {
+                Ok(timestamp) => timestamp,
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                    return Ok(true);
+                }
+                Err(error) => return Err(error),
+            };
+        let timestamp = timestamp.trim();
+        if timestamp == "disable" {
+            return Ok(false);
+        }
+
+        let wine_inf = self.install_root().join("share/wine/wine.inf");
+        let modified = fs::metadata(&wine_inf)?
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map_err(io::Error::other)?
+            .as_secs();
+        Ok(timestamp.parse() != Ok(modified))
+    }
+
+    /// Brings the prefix in line with the configured Wine build.
+    pub fn update(&self) -> io::Result<()> {
+        let _lock = self.lock()?;
+        self.update_locked()
+    }
+
+    pub(crate) fn update_locked(&self) -> io::Result<()> {
+        self.check_ownership()?;
+        self.run_builtin("wineboot", ["--update"])?;
+        Ok(())
+    }
+
+    /// Simulates a Windows reboot, which runs the `RunOnce` entries and the
+    /// file renames that installers schedule for the next boot.
+    pub fn restart(&self) -> io::Result<()> {
+        trace::operation("restart", &self.path, None, || {
+            let _lock = self.lock()?;
+            self.check_ownership()?;
+            self.run_builtin("wineboot", ["--restart"])?;
+            Ok(())
+        })
+    }
+
+    /// Whether wine-mono was installed into the prefix, as opposed to being
+    /// absent or shared from the Wine installation.
+    pub fn is_mono_installed(&self) -> bool {
+        self.drive_c().join("windows/mono").is_dir()
+    }
+
+    /// Whether wine-gecko was installed into the prefix, as opposed to being
+    /// absent or shared from the Wine installation.
+    pub fn is_gecko_installed(&self) -> bool {
+        self.drive_c().join("windows/system32/gecko").is_dir()
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+use crate::{Prefix, PrefixConfig, WineFlavor, hooks, runner};
+
+/// Constructs a [`Prefix`] whose Wine binaries live somewhere other than
+/// `<prefix>/bin`.
+///
+/// Nothing is checked up front: a binary that does not exist is reported,
+/// with its path, when something first tries to run it.
+#[derive(Debug, Clone)]
+pub struct PrefixBuilder {
+    path: PathBuf,
+    dynamic_library_paths: Vec<PathBuf>,
+    config: PrefixConfig,
+    wine: Option<PathBuf>,
+    wine64: Option<PathBuf>,
+    wineserver: Option<PathBuf>,
+    regedit: Option<PathBuf>,
+}
+
+impl PrefixBuilder {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            dynamic_library_paths: Vec::new(),
+            config: PrefixConfig::default(),
+            wine: None,
+            wine64: None,
+            wineserver: None,
+            regedit: None,
+        }
+    }
+
+    pub fn dynamic_library_paths(
+        &mut self,
+        paths: impl IntoIterator<Item = impl AsRef<Path>>,
+    ) -> &mut Self {
+        self.dynamic_library_paths = paths
+            .into_iter()
+            .map(|path| path.as_ref().to_path_buf())
+            .collect();
+        self
+    }
+
+    pub fn config(&mut self, config: PrefixConfig) -> &mut Self {
+        self.config = config;
+        self
+    }
+
+    /// Also decides the installation whose layout the flavor is detected from
+    /// and whose `wine.inf` [`Prefix::needs_update`] compares against.
+    pub fn wine(&mut self, path: impl AsRef<Path>) -> &mut Self {
+        self.wine = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Defaults to `wine64` next to [`PrefixBuilder::wine`].
+    pub fn wine64(&mut self, path: impl AsRef<Path>) -> &mut Self {
+        self.wine64 = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Defaults to `wineserver` next to [`PrefixBuilder::wine`].
+    pub fn wineserver(&mut self, path: impl AsRef<Path>) -> &mut Self {
+        self.wineserver = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Defaults to `regedit` next to [`PrefixBuilder::wine`].
+    pub fn regedit(&mut self, path: impl AsRef<Path>) -> &mut Self {
+        self.regedit = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn build(&self) -> Prefix {
+        let wine = self
+            .wine
+            .clone()
+            .unwrap_or_else(|| self.path.join("bin/wine"));
+        let bin_dir = wine.parent().unwrap_or(Path::new("")).to_path_buf();
+        let sibling = |binary: &Option<PathBuf>, name: &str| -> OsString {
+            binary.clone().unwrap_or_else(|| bin_dir.join(name)).into()
+        };
+        let install_root = bin_dir.parent().unwrap_or(&bin_dir);
+
+        let mut dynamic_library_paths = OsString::new();
+        for (i, path) in self.dynamic_library_paths.iter().enumerate() {
+            if i > 0 {
+                dynamic_library_paths.push(":");
+            }
+            dynamic_library_paths.push(path);
+        }
+
+        Prefix {
+            path: self.path.clone(),
+            dynamic_library_paths,
+            wine64: sibling(&self.wine64, "wine64"),
+            wineserver: sibling(&self.wineserver, "wineserver"),
+            regedit: sibling(&self.regedit, "regedit"),
+            flavor: WineFlavor::detect_from_layout(install_root),
+            wine: wine.into(),
+            config: self.config.clone(),
+            runner: runner::Runner::default(),
+            hooks: hooks::Hooks::default(),
+        }
+    }
+}
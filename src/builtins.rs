@@ -0,0 +1,34 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Shorthands for launching the programs that ship with Wine.
+
+use crate::{Prefix, WineCommand};
+
+impl Prefix {
+    pub fn winecfg(&self) -> WineCommand<'_> {
+        self.wine_command("winecfg")
+    }
+
+    /// The registry editor window, as opposed to the command-line
+    /// [`Prefix::set_registry_value`] family.
+    pub fn regedit_gui(&self) -> WineCommand<'_> {
+        self.wine_command("regedit")
+    }
+
+    pub fn taskmgr(&self) -> WineCommand<'_> {
+        self.wine_command("taskmgr")
+    }
+
+    pub fn control_panel(&self) -> WineCommand<'_> {
+        self.wine_command("control")
+    }
+
+    /// The command prompt, attached to the terminal of the current process.
+    pub fn cmd(&self) -> WineCommand<'_> {
+        self.wine_command("cmd")
+    }
+}
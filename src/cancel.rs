@@ -0,0 +1,100 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    io,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use crate::{Error, Prefix, ResolvedCommand, runner::Runner};
+
+#[derive(Debug)]
+struct CancelState {
+    cancelled: AtomicBool,
+    kill_all: ResolvedCommand,
+    runner: Runner,
+}
+
+/// Cancels launches in a prefix from another thread, e.g., behind the Cancel
+/// button of a UI.
+///
+/// Windows processes cannot be told apart from the Wine processes serving
+/// them, so cancelling kills every process in the prefix. Launches made with
+/// [`crate::WineCommand::cancel_handle`] then fail with
+/// [`io::ErrorKind::Interrupted`], while other operations that were running,
+/// such as [`Prefix::initialize`], fail the way they would if the processes
+/// had crashed.
+#[derive(Debug, Clone)]
+pub struct CancelHandle {
+    state: Arc<CancelState>,
+}
+
+impl CancelHandle {
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Does nothing if already cancelled.
+    pub fn cancel(&self) -> io::Result<()> {
+        if self.state.cancelled.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        self.state.runner.0.output(&self.state.kill_all)?;
+        Ok(())
+    }
+
+    /// Identifies the handle, which clones share.
+    pub(crate) fn as_ptr(&self) -> *const () {
+        Arc::as_ptr(&self.state).cast()
+    }
+
+    /// Fails if the handle was cancelled.
+    pub(crate) fn check(&self) -> io::Result<()> {
+        if self.is_cancelled() {
+            Err(Error::Cancelled.into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Prefix {
+    pub fn cancel_handle(&self) -> CancelHandle {
+        CancelHandle {
+            state: Arc::new(CancelState {
+                cancelled: AtomicBool::new(false),
+                kill_all: self.kill_all_command(),
+                runner: self.runner.clone(),
+            }),
+        }
+    }
+
+    /// Makes [`Prefix::initialize`], [`Prefix::snapshot`],
+    /// [`crate::PrefixPool::new`], and [`crate::runtimes::install`] stop once
+    /// `handle` is cancelled, removing the half-created prefix, snapshot,
+    /// copies, or download. Launches in the prefix fail as if given the
+    /// handle through [`crate::WineCommand::cancel_handle`].
+    ///
+    /// [`Prefix::rollback`] is never interrupted, since that would leave the
+    /// prefix neither as it was nor as it was saved.
+    pub fn with_cancel_handle(mut self, handle: &CancelHandle) -> Self {
+        self.hooks.cancel = Some(handle.clone());
+        self
+    }
+
+    pub(crate) fn cancel(&self) -> Option<&CancelHandle> {
+        self.hooks.cancel.as_ref()
+    }
+
+    /// Fails if the handle given to [`Prefix::with_cancel_handle`] was
+    /// cancelled.
+    pub(crate) fn check_cancelled(&self) -> io::Result<()> {
+        self.cancel().map_or(Ok(()), CancelHandle::check)
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::io;
+
+use crate::{Prefix, registry::RegistryKey, sha1};
+
+const PEM_BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+const PEM_END: &str = "-----END CERTIFICATE-----";
+
+/// The properties of a certificate that crypt32 serializes into `Blob`.
+const CERT_SHA1_HASH_PROP_ID: u32 = 3;
+const CERT_CERT_PROP_ID: u32 = 32;
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message.to_owned())
+}
+
+/// Decodes standard base64, skipping whitespace.
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut decoded = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for byte in text.bytes().filter(|byte| !byte.is_ascii_whitespace()) {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits) as u8);
+        }
+    }
+    Some(decoded)
+}
+
+/// The length of the DER value that starts `der`, including its header, if
+/// it is a `SEQUENCE`, which certificates are.
+fn der_sequence_length(der: &[u8]) -> Option<usize> {
+    let (&tag, rest) = der.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+    if tag != 0x30 {
+        return None;
+    }
+    if first < 0x80 {
+        return Some(2 + usize::from(first));
+    }
+    let count = usize::from(first & 0x7f);
+    if count == 0 || count > 4 {
+        return None;
+    }
+    let length = rest
+        .get(..count)?
+        .iter()
+        .fold(0, |length, &byte| (length << 8) | usize::from(byte));
+    Some(2 + count + length)
+}
+
+/// The DER encoding of `certificate`, which is either DER itself or the
+/// first certificate of PEM text.
+fn to_der(certificate: &[u8]) -> io::Result<Vec<u8>> {
+    let der = match std::str::from_utf8(certificate)
+        .ok()
+        .and_then(|text| text.split_once(PEM_BEGIN))
+    {
+        Some((_, rest)) => {
+            let (body, _) = rest
+                .split_once(PEM_END)
+                .ok_or_else(|| invalid("PEM certificate is not terminated"))?;
+            decode_base64(body)
+                .ok_or_else(|| invalid("PEM certificate is not base64"))?
+        }
+        None => certificate.to_vec(),
+    };
+    if der_sequence_length(&der) != Some(der.len()) {
+        return Err(invalid("not a DER or PEM certificate"));
+    }
+    Ok(der)
+}
+
+fn root_certificates_key() -> RegistryKey {
+    RegistryKey::local_machine(
+        "Software\\Microsoft\\SystemCertificates\\Root\\Certificates",
+    )
+}
+
+impl Prefix {
+    /// Trusts the CA `certificate`, given as PEM or DER, for every program in
+    /// the prefix, e.g., the one of a TLS-intercepting proxy or of a test
+    /// service. Returns its SHA-1 thumbprint, by which Windows names it.
+    ///
+    /// Only the first certificate of a PEM bundle is installed.
+    pub fn install_root_certificate(
+        &self,
+        certificate: impl AsRef<[u8]>,
+    ) -> io::Result<String> {
+        let der = to_der(certificate.as_ref())?;
+        let thumbprint = sha1::digest(&der);
+        let mut blob = Vec::new();
+        for (property, value) in [
+            (CERT_SHA1_HASH_PROP_ID, thumbprint.as_slice()),
+            (CERT_CERT_PROP_ID, der.as_slice()),
+        ] {
+            for field in [property, 1, value.len() as u32] {
+                blob.extend(field.to_le_bytes());
+            }
+            blob.extend(value);
+        }
+
+        let thumbprint: String = thumbprint
+            .iter()
+            .map(|byte| format!("{byte:02X}"))
+            .collect();
+        self.set_registry_value(
+            &root_certificates_key().join(&thumbprint),
+            Some("Blob"),
+            blob,
+        )?;
+        Ok(thumbprint)
+    }
+
+    /// Removes a certificate added with [`Prefix::install_root_certificate`]
+    /// by its thumbprint.
+    pub fn remove_root_certificate(&self, thumbprint: &str) -> io::Result<()> {
+        self.delete_registry_key(
+            &root_certificates_key().join(&thumbprint.to_ascii_uppercase()),
+        )
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::io;
+
+use crate::{Codepage, Error, Prefix, Script};
+
+/// Prints the clipboard as UTF-8 without a trailing newline.
+const GET_CLIPBOARD: &str = "\
+[Console]::OutputEncoding = [Text.Encoding]::UTF8
+[Console]::Out.Write((Get-Clipboard -Raw))";
+
+impl Prefix {
+    /// Puts `text` on the clipboard of the prefix with Wine's `clip`, e.g., to
+    /// paste it into a dialog during automated setup. Wine shares the
+    /// clipboard with the host display while a program of the prefix, such
+    /// as `explorer.exe`, keeps running.
+    pub fn set_clipboard_text(&self, text: &str) -> io::Result<()> {
+        let (path, _temp) = self.stage_script(
+            &Script::Inline(text.to_owned()),
+            "txt",
+            |text| text.as_bytes().to_vec(),
+        )?;
+        self.builtin_command("cmd")
+            .console_codepage(Codepage::UTF8)
+            .args(["/c", "clip", "<"])
+            .arg(path)
+            .output_checked()?;
+        Ok(())
+    }
+
+    /// The text on the clipboard of the prefix, which is empty if there is
+    /// none. Wine has no program to print it, so this needs
+    /// [`Prefix::powershell`] and otherwise fails with
+    /// [`io::ErrorKind::NotFound`].
+    pub fn get_clipboard_text(&self) -> io::Result<String> {
+        let output =
+            self.run_powershell(&Script::Inline(GET_CLIPBOARD.to_owned()))?;
+        if !output.status.success() {
+            return Err(Error::ProcessCrashed {
+                program: "Get-Clipboard".into(),
+                status: output.status,
+                stderr: String::from_utf8_lossy(&output.stderr)
+                    .trim()
+                    .to_owned(),
+            }
+            .into());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
@@ -0,0 +1,228 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    env,
+    ffi::OsString,
+    io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    Error, Prefix, ResolvedCommand, WineCommand,
+    registry::{RegistryBatch, RegistryKey, RegistryValue},
+};
+
+/// Where distributions and Homebrew install libfaketime.
+const LIBFAKETIME_PATHS: &[&str] = &[
+    "/usr/lib/x86_64-linux-gnu/faketime/libfaketime.so.1",
+    "/usr/lib/aarch64-linux-gnu/faketime/libfaketime.so.1",
+    "/usr/lib/faketime/libfaketime.so.1",
+    "/usr/lib64/faketime/libfaketime.so.1",
+    "/usr/local/lib/faketime/libfaketime.so.1",
+    "/opt/homebrew/lib/faketime/libfaketime.1.dylib",
+    "/usr/local/lib/faketime/libfaketime.1.dylib",
+];
+
+#[cfg(target_os = "macos")]
+const PRELOAD_VAR: &str = "DYLD_INSERT_LIBRARIES";
+#[cfg(not(target_os = "macos"))]
+const PRELOAD_VAR: &str = "LD_PRELOAD";
+
+/// The size of the `TZI` value, i.e., three biases and two `SYSTEMTIME`s.
+const TZI_LENGTH: usize = 3 * 4 + 2 * 16;
+
+/// A clock that launches see in place of the real one, e.g., to test how a
+/// program behaves on a given date, applied with
+/// [`WineCommand::fake_clock`].
+///
+/// It is faked by preloading libfaketime into Wine, which only affects
+/// processes started by the launch, so a wineserver already running for the
+/// prefix keeps the real time. Monotonic clocks are left alone so that
+/// timers keep working.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FakeClock {
+    pub time: SystemTime,
+    /// Whether the clock stays at `time` instead of running from it.
+    pub is_frozen: bool,
+    /// The libfaketime library to preload.
+    pub library: PathBuf,
+}
+
+impl FakeClock {
+    /// A clock that always reads `time`. Fails with [`Error::NotInstalled`]
+    /// if libfaketime is not installed.
+    pub fn frozen(time: SystemTime) -> io::Result<Self> {
+        Self::new(time, true)
+    }
+
+    /// A clock that reads `time` when the launch starts and runs from there.
+    pub fn starting_at(time: SystemTime) -> io::Result<Self> {
+        Self::new(time, false)
+    }
+
+    fn new(time: SystemTime, is_frozen: bool) -> io::Result<Self> {
+        let library = LIBFAKETIME_PATHS
+            .iter()
+            .map(Path::new)
+            .find(|path| path.is_file())
+            .ok_or_else(|| Error::NotInstalled("libfaketime".to_owned()))?;
+        Ok(Self {
+            time,
+            is_frozen,
+            library: library.to_path_buf(),
+        })
+    }
+
+    fn apply(&self, command: &mut ResolvedCommand) {
+        let seconds = match self.time.duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs() as i64,
+            Err(error) => -(error.duration().as_secs() as i64),
+        };
+        let mut preload = self.library.clone().into_os_string();
+        let inherited =
+            command.env(PRELOAD_VAR).map(ToOwned::to_owned).or_else(|| {
+                env::var_os(PRELOAD_VAR).filter(|_| !command.env_clear)
+            });
+        if let Some(inherited) = inherited.filter(|value| !value.is_empty()) {
+            preload.push(":");
+            preload.push(inherited);
+        }
+        command.envs.push((PRELOAD_VAR.into(), preload));
+        if cfg!(target_os = "macos") {
+            command
+                .envs
+                .push(("DYLD_FORCE_FLAT_NAMESPACE".into(), "1".into()));
+        }
+        // An `@` makes the clock run from the time instead of stopping.
+        let faketime = if self.is_frozen {
+            seconds.to_string()
+        } else {
+            format!("@{seconds}")
+        };
+        command.envs.extend([
+            (OsString::from("FAKETIME"), OsString::from(faketime)),
+            ("FAKETIME_FMT".into(), "%s".into()),
+            ("FAKETIME_DONT_FAKE_MONOTONIC".into(), "1".into()),
+        ]);
+    }
+}
+
+fn time_zone_information_key() -> RegistryKey {
+    RegistryKey::local_machine(
+        "System\\CurrentControlSet\\Control\\TimeZoneInformation",
+    )
+}
+
+fn time_zones_key() -> RegistryKey {
+    RegistryKey::local_machine(
+        "Software\\Microsoft\\Windows NT\\CurrentVersion\\Time Zones",
+    )
+}
+
+impl Prefix {
+    /// Records the time zone named `windows_name`, e.g., `Pacific Standard
+    /// Time`, as the one of the machine, for programs that read it from the
+    /// registry. Fails with [`Error::RegistryKeyMissing`] if the prefix does
+    /// not know the zone.
+    ///
+    /// Wine computes local time from the time zone of the host instead, so
+    /// launches should also set a matching `TZ`, see
+    /// [`crate::PrefixConfig::time_zone`].
+    pub fn set_time_zone(&self, windows_name: &str) -> io::Result<()> {
+        let zone = time_zones_key().join(windows_name);
+        let mut tzi = None;
+        let (mut standard_name, mut daylight_name) = (None, None);
+        for (name, value) in zone.values(self)? {
+            match (name.as_deref(), value) {
+                (Some("TZI"), RegistryValue::Binary(data))
+                    if data.len() == TZI_LENGTH =>
+                {
+                    tzi = Some(data);
+                }
+                (Some("Std"), RegistryValue::String(name)) => {
+                    standard_name = Some(name);
+                }
+                (Some("Dlt"), RegistryValue::String(name)) => {
+                    daylight_name = Some(name);
+                }
+                _ => {}
+            }
+        }
+        let Some(tzi) = tzi else {
+            return Err(Error::RegistryKeyMissing(zone.to_string()).into());
+        };
+        let bias = |index: usize| {
+            u32::from_le_bytes(tzi[4 * index..][..4].try_into().unwrap())
+        };
+
+        let key = time_zone_information_key();
+        let mut batch = RegistryBatch::new();
+        batch
+            .set_value(&key, Some("Bias"), bias(0))
+            .set_value(&key, Some("StandardBias"), bias(1))
+            .set_value(&key, Some("DaylightBias"), bias(2))
+            .set_value(&key, Some("StandardStart"), &tzi[12..28])
+            .set_value(&key, Some("DaylightStart"), &tzi[28..44])
+            .set_value(
+                &key,
+                Some("StandardName"),
+                standard_name.unwrap_or_else(|| windows_name.to_owned()),
+            )
+            .set_value(
+                &key,
+                Some("DaylightName"),
+                daylight_name.unwrap_or_else(|| windows_name.to_owned()),
+            )
+            .set_value(&key, Some("TimeZoneKeyName"), windows_name)
+            .set_value(&key, Some("DynamicDaylightTimeDisabled"), 0u32);
+        self.apply_registry_batch(&batch)
+    }
+
+    /// The Windows name of the time zone recorded for the machine, e.g., by
+    /// [`Prefix::set_time_zone`], or `None` if there is none.
+    pub fn time_zone(&self) -> io::Result<Option<String>> {
+        match self.registry_value(
+            &time_zone_information_key(),
+            Some("TimeZoneKeyName"),
+        )? {
+            Some(RegistryValue::String(name)) if !name.is_empty() => {
+                Ok(Some(name))
+            }
+            Some(RegistryValue::String(_)) | None => Ok(None),
+            Some(_) => Err(Error::RegistryParse(
+                "TimeZoneKeyName is not a REG_SZ".to_owned(),
+            )
+            .into()),
+        }
+    }
+}
+
+impl WineCommand<'_> {
+    /// Overrides [`crate::PrefixConfig::time_zone`] for this launch.
+    pub fn time_zone(&mut self, name: impl Into<String>) -> &mut Self {
+        self.time_zone = Some(name.into());
+        self
+    }
+
+    pub fn fake_clock(&mut self, clock: FakeClock) -> &mut Self {
+        self.fake_clock = Some(clock);
+        self
+    }
+
+    pub(crate) fn apply_clock(&self, command: &mut ResolvedCommand) {
+        let config = &self.prefix.config;
+        if let Some(time_zone) =
+            self.time_zone.as_ref().or(config.time_zone.as_ref())
+        {
+            command.envs.push(("TZ".into(), time_zone.into()));
+        }
+        if let Some(clock) = &self.fake_clock {
+            clock.apply(command);
+        }
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::ffi::OsString;
+
+use crate::WineCommand;
+
+/// A Windows codepage, as passed to `chcp`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Codepage(pub u16);
+
+/// The upper half of codepage 437, the OEM codepage of US English.
+const IBM437_HIGH: &str = "\
+ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜ¢£¥₧ƒáíóúñÑªº¿⌐¬½¼¡«»\
+░▒▓│┤╡╢╖╕╣║╗╝╜╛┐└┴┬├─┼╞╟╚╔╩╦╠═╬╧╨╤╥╙╘╒╓╫╪┘┌█▄▌▐▀\
+αßΓπΣσµτΦΘΩδ∞φε∩≡±≥≤⌠⌡÷≈°∙·√ⁿ²■\u{a0}";
+
+/// Bytes `0x80` to `0x9f` of codepage 1252, where it differs from Latin-1.
+/// Windows passes the five unassigned bytes through as C1 controls.
+const WINDOWS_1252_C1: &str = "\
+€\u{81}‚ƒ„…†‡ˆ‰Š‹Œ\u{8d}Ž\u{8f}\u{90}‘’“”•–—˜™š›œ\u{9d}žŸ";
+
+impl Codepage {
+    pub const UTF8: Self = Self(65001);
+    /// The ANSI codepage of Western European locales.
+    pub const WINDOWS_1252: Self = Self(1252);
+    /// The OEM codepage of US English, which consoles use by default.
+    pub const IBM437: Self = Self(437);
+    pub const LATIN1: Self = Self(28591);
+
+    /// Decodes text the guest wrote in this codepage. Codepages other than
+    /// the ones above are decoded as UTF-8, replacing invalid sequences.
+    pub fn decode(self, bytes: &[u8]) -> String {
+        let high_half = |table: &str, offset: u8| {
+            let table = table.chars().collect::<Vec<_>>();
+            bytes
+                .iter()
+                .map(|&byte| match byte.checked_sub(offset) {
+                    Some(index) => table
+                        .get(usize::from(index))
+                        .copied()
+                        .unwrap_or(char::from(byte)),
+                    None => char::from(byte),
+                })
+                .collect()
+        };
+        match self {
+            Self::IBM437 => high_half(IBM437_HIGH, 0x80),
+            Self::WINDOWS_1252 => high_half(WINDOWS_1252_C1, 0x80),
+            Self::LATIN1 => {
+                bytes.iter().map(|&byte| char::from(byte)).collect()
+            }
+            _ => String::from_utf8_lossy(bytes).into_owned(),
+        }
+    }
+}
+
+impl WineCommand<'_> {
+    /// Switches the guest console to `codepage` with `chcp` before running
+    /// the program, and decodes its streamed output in that codepage.
+    pub fn console_codepage(&mut self, codepage: Codepage) -> &mut Self {
+        self.codepage = Some(codepage);
+        self
+    }
+
+    /// The codepage set with [`WineCommand::console_codepage`].
+    pub fn codepage(&self) -> Option<Codepage> {
+        self.codepage
+    }
+}
+
+/// The arguments that run `cmd` to switch to `codepage` and then run the
+/// rest of the command line.
+pub(crate) fn chcp_prelude(codepage: Codepage) -> [OsString; 6] {
+    [
+        "cmd".into(),
+        "/c".into(),
+        "chcp".into(),
+        codepage.0.to_string().into(),
+        ">nul".into(),
+        "&&".into(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Codepage, IBM437_HIGH, WINDOWS_1252_C1};
+
+    #[test]
+    fn tables_cover_their_ranges() {
+        assert_eq!(IBM437_HIGH.chars().count(), 0x80);
+        assert_eq!(WINDOWS_1252_C1.chars().count(), 0x20);
+    }
+
+    #[test]
+    fn decodes_each_codepage() {
+        let high = [0x41, 0x80, 0x8a, 0x9b, 0x9f, 0xb0, 0xdb, 0xe1, 0xe9, 0xff];
+        assert_eq!(Codepage::IBM437.decode(&high), "AÇè¢ƒ░█ßΘ\u{a0}");
+        assert_eq!(Codepage::WINDOWS_1252.decode(&high), "A€Š›Ÿ°Ûáéÿ");
+        assert_eq!(
+            Codepage::WINDOWS_1252.decode(&[0x81, 0x8d, 0x8f, 0x90, 0x9d]),
+            "\u{81}\u{8d}\u{8f}\u{90}\u{9d}"
+        );
+        assert_eq!(
+            Codepage::LATIN1.decode(&high),
+            "A\u{80}\u{8a}\u{9b}\u{9f}°Ûáéÿ"
+        );
+        assert_eq!(Codepage::UTF8.decode("é€".as_bytes()), "é€");
+        assert_eq!(Codepage::UTF8.decode(&[b'a', 0xff]), "a\u{fffd}");
+        assert_eq!(Codepage(936).decode(b"plain"), "plain");
+    }
+}
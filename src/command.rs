@@ -0,0 +1,1056 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    env,
+    ffi::{OsStr, OsString},
+    fs, io,
+    os::unix::{fs::PermissionsExt, process::CommandExt},
+    path::{Path, PathBuf},
+    process::{self, Command, Stdio},
+};
+
+use crate::{
+    AudioServer, CancelHandle, Codepage, CpuTopology, DYNAMIC_LIBRARY_PATH_VAR,
+    DebugRules, Defaults, DisplayServer, DllOverride, Error, FakeClock,
+    LaunchEnvironment, Locale, Prefix, ResourceLimits, VirtualDisplay,
+    codepage, dll_overrides, hooks::Hooks, pe, retry, settings::AudioDriver,
+    trace,
+};
+
+/// Host variables forwarded even when the environment is cleared, since Wine
+/// cannot locate the user or reach the display server without them. Only
+/// [`EnvironmentPolicy::Deny`] can withhold them.
+pub const REQUIRED_HOST_VARS: &[&str] = &[
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "TMPDIR",
+    "DISPLAY",
+    "WAYLAND_DISPLAY",
+    "XDG_RUNTIME_DIR",
+];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StartMode {
+    /// Run the program directly with the loader.
+    #[default]
+    Direct,
+    /// Run the program through `start.exe`, passing `/unix` for an absolute
+    /// host path such as `/home/me/Downloads/setup.exe`.
+    StartExe,
+    /// Run the program under `wineconsole`, which some legacy console
+    /// programs need to render correctly.
+    Console(ConsoleBackend),
+}
+
+/// How much processor time a launched program gets next to the others on the
+/// host.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Priority {
+    /// Only what is left over, e.g., for installs in the background.
+    Idle,
+    BelowNormal,
+    #[default]
+    Normal,
+    /// Like [`Priority::High`], needs the privilege to lower niceness, without
+    /// which `nice` warns and runs the program at normal priority.
+    AboveNormal,
+    High,
+}
+
+impl Priority {
+    pub fn niceness(&self) -> i32 {
+        match self {
+            Self::Idle => 19,
+            Self::BelowNormal => 10,
+            Self::Normal => 0,
+            Self::AboveNormal => -5,
+            Self::High => -10,
+        }
+    }
+
+    /// The switch giving `start` the matching Windows priority class.
+    fn start_switch(&self) -> &'static str {
+        match self {
+            Self::Idle => "/low",
+            Self::BelowNormal => "/belownormal",
+            Self::Normal => "/normal",
+            Self::AboveNormal => "/abovenormal",
+            Self::High => "/high",
+        }
+    }
+}
+
+/// How `wineconsole` draws the console.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConsoleBackend {
+    /// A window of its own.
+    #[default]
+    User,
+    /// The terminal the caller runs in, through curses.
+    Curses,
+}
+
+impl ConsoleBackend {
+    fn as_arg(self) -> &'static str {
+        match self {
+            Self::User => "--backend=user",
+            Self::Curses => "--backend=curses",
+        }
+    }
+}
+
+/// Which loader runs the program on Wine builds that ship separate `wine` and
+/// `wine64` loaders instead of the new WoW64 mode.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Loader {
+    /// `wine64` for 64-bit executables when the installation has it, and
+    /// `wine` otherwise, including for programs that are not host files.
+    #[default]
+    Auto,
+    Wine,
+    Wine64,
+}
+
+/// Where the launched program starts, if not the root of the prefix.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum WorkingDir {
+    /// A Windows path, e.g., `C:\Games\Foo`.
+    Guest(String),
+    Host(PathBuf),
+}
+
+/// What happens to a running [`crate::WineChild`] when it is dropped.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KillOnDrop {
+    /// Leave it running.
+    #[default]
+    Never,
+    Process,
+    /// Also kill the processes it started, which share its process group.
+    ProcessTree,
+}
+
+/// Which variables of the host environment a Wine process sees, before the
+/// ones this crate sets for the launch.
+///
+/// A launch not given one through [`WineCommand::environment`] uses
+/// [`crate::PrefixConfig::environment`], falling back to
+/// [`Defaults::environment`]. The same policy applies to the wineserver and
+/// the other programs a prefix runs for itself.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub enum EnvironmentPolicy {
+    /// Inherit the entire environment of the current process.
+    #[default]
+    Inherit,
+    /// Start from an empty environment, keeping only [`REQUIRED_HOST_VARS`].
+    Clear,
+    /// Like [`EnvironmentPolicy::Clear`], but also forward the listed
+    /// variables.
+    Allow(Vec<OsString>),
+    /// Like [`EnvironmentPolicy::Inherit`], but withhold the listed
+    /// variables, e.g., a `WINEDEBUG` left set in the shell.
+    Deny(Vec<OsString>),
+}
+
+impl EnvironmentPolicy {
+    /// Applies the policy to a command that has no variables yet.
+    pub(crate) fn apply(&self, resolved: &mut ResolvedCommand) {
+        let forwarded = match self {
+            Self::Inherit => return,
+            Self::Deny(vars) => {
+                for var in vars {
+                    resolved.unset_env(var);
+                }
+                return;
+            }
+            Self::Clear => [].as_slice(),
+            Self::Allow(vars) => vars.as_slice(),
+        };
+        resolved.env_clear = true;
+        let names = REQUIRED_HOST_VARS
+            .iter()
+            .map(OsStr::new)
+            .chain(forwarded.iter().map(OsString::as_os_str));
+        for name in names {
+            if let Some(value) = env::var_os(name) {
+                resolved.set_env(name, value);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WineCommand<'a> {
+    pub(crate) prefix: &'a Prefix,
+    pub(crate) program: OsString,
+    pub(crate) args: Vec<OsString>,
+    start_mode: Option<StartMode>,
+    debug_rules: DebugRules<'a>,
+    environment: Option<EnvironmentPolicy>,
+    envs: Vec<(OsString, OsString)>,
+    locale: Option<Locale>,
+    dll_overrides: Vec<DllOverride>,
+    cpu_topology: Option<CpuTopology>,
+    cpu_affinity: Vec<u32>,
+    priority: Priority,
+    pub(crate) resource_limits: ResourceLimits,
+    pub(crate) virtual_display: Option<VirtualDisplay>,
+    pub(crate) display_server: DisplayServer,
+    pub(crate) audio_driver: Option<AudioDriver>,
+    pub(crate) audio_server: Option<AudioServer>,
+    pub(crate) restored_environment: Option<LaunchEnvironment>,
+    pub(crate) time_zone: Option<String>,
+    pub(crate) fake_clock: Option<FakeClock>,
+    loader: Loader,
+    working_dir: Option<WorkingDir>,
+    pub(crate) codepage: Option<Codepage>,
+    pub(crate) kill_on_drop: KillOnDrop,
+    cancel_handle: Option<CancelHandle>,
+    pub(crate) hooks: Hooks,
+}
+
+impl<'a> WineCommand<'a> {
+    pub fn new(prefix: &'a Prefix, program: impl AsRef<OsStr>) -> Self {
+        Self {
+            prefix,
+            program: program.as_ref().to_os_string(),
+            args: Vec::new(),
+            start_mode: None,
+            debug_rules: DebugRules::new(),
+            environment: None,
+            envs: Vec::new(),
+            locale: None,
+            dll_overrides: Vec::new(),
+            cpu_topology: None,
+            cpu_affinity: Vec::new(),
+            priority: Priority::default(),
+            resource_limits: ResourceLimits::default(),
+            virtual_display: None,
+            display_server: DisplayServer::default(),
+            audio_driver: None,
+            audio_server: None,
+            restored_environment: None,
+            time_zone: None,
+            fake_clock: None,
+            loader: Loader::default(),
+            working_dir: None,
+            codepage: None,
+            kill_on_drop: KillOnDrop::default(),
+            cancel_handle: None,
+            hooks: Hooks::default(),
+        }
+    }
+
+    pub fn arg(&mut self, arg: impl AsRef<OsStr>) -> &mut Self {
+        self.args.push(arg.as_ref().to_os_string());
+        self
+    }
+
+    pub fn args(
+        &mut self,
+        args: impl IntoIterator<Item = impl AsRef<OsStr>>,
+    ) -> &mut Self {
+        self.args
+            .extend(args.into_iter().map(|arg| arg.as_ref().to_os_string()));
+        self
+    }
+
+    /// Passes `path` the way Windows programs see it, per
+    /// [`Prefix::windows_path`], e.g., for a file the program should open.
+    pub fn host_path_arg(&mut self, path: impl AsRef<Path>) -> &mut Self {
+        self.args.push(self.prefix.windows_path(path));
+        self
+    }
+
+    /// Calls [`WineCommand::host_path_arg`] for each path.
+    pub fn host_path_args(
+        &mut self,
+        paths: impl IntoIterator<Item = impl AsRef<Path>>,
+    ) -> &mut Self {
+        for path in paths {
+            self.host_path_arg(path);
+        }
+        self
+    }
+
+    pub fn env(
+        &mut self,
+        key: impl AsRef<OsStr>,
+        value: impl AsRef<OsStr>,
+    ) -> &mut Self {
+        self.envs
+            .push((key.as_ref().to_os_string(), value.as_ref().to_os_string()));
+        self
+    }
+
+    pub fn envs(
+        &mut self,
+        vars: impl IntoIterator<Item = (impl AsRef<OsStr>, impl AsRef<OsStr>)>,
+    ) -> &mut Self {
+        for (key, value) in vars {
+            self.env(key, value);
+        }
+        self
+    }
+
+    /// Overrides [`crate::PrefixConfig::locale`] for this launch.
+    pub fn locale(&mut self, locale: Locale) -> &mut Self {
+        self.locale = Some(locale);
+        self
+    }
+
+    /// Adds to [`crate::PrefixConfig::dll_overrides`] for this launch.
+    pub fn dll_override(&mut self, dll_override: DllOverride) -> &mut Self {
+        self.dll_overrides.push(dll_override);
+        self
+    }
+
+    /// Overrides [`crate::PrefixConfig::cpu_topology`] for this launch.
+    pub fn cpu_topology(&mut self, cpu_topology: CpuTopology) -> &mut Self {
+        self.cpu_topology = Some(cpu_topology);
+        self
+    }
+
+    /// Pins the launched process to the given host processors with `taskset`.
+    /// macOS offers no way to pin processes, so this is ignored there.
+    pub fn cpu_affinity(
+        &mut self,
+        cpus: impl IntoIterator<Item = u32>,
+    ) -> &mut Self {
+        self.cpu_affinity = cpus.into_iter().collect();
+        self
+    }
+
+    /// Runs the launched process at `priority` on the host through `nice`,
+    /// and in Windows through `start` under [`StartMode::StartExe`].
+    ///
+    /// A wineserver that the launch starts inherits the niceness, slowing
+    /// every later program in the prefix as well, so lowering the priority
+    /// of a background install is best done with the server already running.
+    pub fn priority(&mut self, priority: Priority) -> &mut Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn loader(&mut self, loader: Loader) -> &mut Self {
+        self.loader = loader;
+        self
+    }
+
+    /// Applies to the [`crate::WineChild`] handles returned by
+    /// [`WineCommand::spawn_child`] and the streaming spawns, e.g., so that a
+    /// panic does not leave the program running.
+    pub fn kill_on_drop(&mut self, kill_on_drop: KillOnDrop) -> &mut Self {
+        self.kill_on_drop = kill_on_drop;
+        self
+    }
+
+    /// Makes the launch fail with [`io::ErrorKind::Interrupted`] once `handle`
+    /// is cancelled, whether before it starts or while it runs, in place of
+    /// any given to [`Prefix::with_cancel_handle`].
+    pub fn cancel_handle(&mut self, handle: &CancelHandle) -> &mut Self {
+        self.cancel_handle = Some(handle.clone());
+        self
+    }
+
+    /// Fails if the launch was cancelled, through its own handle or that of
+    /// the prefix.
+    fn check_cancelled(&self) -> io::Result<()> {
+        match self.cancel_handle.as_ref().or(self.prefix.cancel()) {
+            Some(handle) => handle.check(),
+            None => Ok(()),
+        }
+    }
+
+    /// Starts the program in the Windows directory `dir`, e.g.,
+    /// `C:\Games\Foo`, through `start /d` under [`StartMode::StartExe`].
+    /// Otherwise the directory is mapped to the host with
+    /// [`Prefix::resolve_guest_path`], and the program runs from the root of
+    /// the prefix if that fails.
+    pub fn working_dir(&mut self, dir: impl AsRef<str>) -> &mut Self {
+        self.working_dir = Some(WorkingDir::Guest(dir.as_ref().to_owned()));
+        self
+    }
+
+    /// Like [`WineCommand::working_dir`] for a directory on the host.
+    pub fn host_working_dir(&mut self, dir: impl AsRef<Path>) -> &mut Self {
+        self.working_dir = Some(WorkingDir::Host(dir.as_ref().to_path_buf()));
+        self
+    }
+
+    /// Overrides [`crate::Defaults::start_mode`] for this launch.
+    pub fn start_mode(&mut self, start_mode: StartMode) -> &mut Self {
+        self.start_mode = Some(start_mode);
+        self
+    }
+
+    /// Overrides [`crate::Defaults::debug_rules`] for this launch unless
+    /// `debug_rules` is empty.
+    pub fn debug_rules(
+        &mut self,
+        debug_rules: impl AsRef<DebugRules<'a>>,
+    ) -> &mut Self {
+        self.debug_rules = debug_rules.as_ref().clone();
+        self
+    }
+
+    /// Defaults to the policy of the prefix, see [`EnvironmentPolicy`].
+    pub fn environment(&mut self, environment: EnvironmentPolicy) -> &mut Self {
+        self.environment = Some(environment);
+        self
+    }
+
+    /// Shorthand for [`EnvironmentPolicy::Clear`].
+    pub fn env_clear(&mut self) -> &mut Self {
+        self.environment(EnvironmentPolicy::Clear)
+    }
+
+    /// Shorthand for [`EnvironmentPolicy::Allow`].
+    pub fn env_allow(
+        &mut self,
+        vars: impl IntoIterator<Item = impl AsRef<OsStr>>,
+    ) -> &mut Self {
+        self.environment(EnvironmentPolicy::Allow(
+            vars.into_iter()
+                .map(|var| var.as_ref().to_os_string())
+                .collect(),
+        ))
+    }
+
+    /// Shorthand for [`EnvironmentPolicy::Deny`].
+    pub fn env_deny(
+        &mut self,
+        vars: impl IntoIterator<Item = impl AsRef<OsStr>>,
+    ) -> &mut Self {
+        self.environment(EnvironmentPolicy::Deny(
+            vars.into_iter()
+                .map(|var| var.as_ref().to_os_string())
+                .collect(),
+        ))
+    }
+
+    /// Whether [`Loader::Auto`] should pick `wine64`.
+    fn is_64_bit_program(&self) -> bool {
+        let program = Path::new(&self.program);
+        Path::new(&self.prefix.wine64).is_file()
+            && program.is_file()
+            && pe::inspect(program)
+                .is_ok_and(|info| info.machine.bits() == Some(64))
+    }
+
+    /// Computes the invocation without executing anything, e.g., to inspect
+    /// why a launch behaves differently from one typed into a shell. Launches
+    /// pass the result through the pre-launch hooks first.
+    pub fn resolve(&self) -> ResolvedCommand {
+        let prefix = self.prefix;
+        let config = &prefix.config;
+        let loader = match self.loader {
+            Loader::Auto if self.is_64_bit_program() => &prefix.wine64,
+            Loader::Auto | Loader::Wine => &prefix.wine,
+            Loader::Wine64 => &prefix.wine64,
+        };
+
+        let mut wrapper = self.resource_limits.wrapper();
+        if let Some(display) = &self.virtual_display {
+            wrapper.extend(display.wrapper());
+        }
+        if self.priority != Priority::Normal {
+            wrapper.extend([
+                "nice".into(),
+                "-n".into(),
+                self.priority.niceness().to_string().into(),
+            ]);
+        }
+        if !self.cpu_affinity.is_empty() && cfg!(target_os = "linux") {
+            let cpus = self
+                .cpu_affinity
+                .iter()
+                .map(u32::to_string)
+                .collect::<Vec<_>>()
+                .join(",");
+            wrapper.extend(["taskset".into(), "-c".into(), cpus.into()]);
+        }
+        let mut resolved = match wrapper.split_first() {
+            Some((program, args)) => {
+                let mut resolved = ResolvedCommand::new(program, &prefix.path);
+                resolved.args.extend(args.iter().cloned());
+                resolved.args.push(loader.clone());
+                resolved
+            }
+            None => ResolvedCommand::new(loader, &prefix.path),
+        };
+
+        match &self.environment {
+            Some(environment) => environment.apply(&mut resolved),
+            None => prefix.environment_policy().apply(&mut resolved),
+        }
+
+        resolved.set_env("WINEPREFIX", &prefix.path);
+        resolved
+            .set_env(DYNAMIC_LIBRARY_PATH_VAR, prefix.dynamic_library_paths());
+        let flavor = prefix.flavor();
+        if config.esync {
+            resolved.set_env(flavor.esync_var(), "1");
+        }
+        let toggles = [
+            (config.msync, Some(flavor.msync_var())),
+            (config.fsync, flavor.fsync_var()),
+            (config.large_address_aware, flavor.large_address_aware_var()),
+            (config.simulate_writecopy, flavor.simulate_writecopy_var()),
+            (
+                config.staging_shared_memory,
+                flavor.staging_shared_memory_var(),
+            ),
+        ];
+        for (is_enabled, var) in toggles {
+            if is_enabled && let Some(var) = var {
+                resolved.set_env(var, "1");
+            }
+        }
+        let mut dll_overrides = Vec::new();
+        if let Some(gptk) = &config.gptk {
+            for (key, value) in gptk.env() {
+                resolved.set_env(key, value);
+            }
+            dll_overrides.extend(gptk.dll_overrides());
+        }
+        dll_overrides.extend(config.dll_overrides.iter().cloned());
+        dll_overrides.extend(self.audio_dll_overrides());
+        dll_overrides.extend(self.dll_overrides.iter().cloned());
+        if !dll_overrides.is_empty() {
+            resolved.set_env(
+                "WINEDLLOVERRIDES",
+                dll_overrides::to_env_value(&dll_overrides),
+            );
+        }
+        if !config.wine_path.is_empty() {
+            let mut wine_path = OsString::new();
+            for (i, entry) in config.wine_path.iter().enumerate() {
+                if i > 0 {
+                    wine_path.push(";");
+                }
+                wine_path.push(entry.to_windows_path());
+            }
+            resolved.set_env("WINEPATH", wine_path);
+        }
+        if let Some(cpu_topology) =
+            self.cpu_topology.as_ref().or(config.cpu_topology.as_ref())
+        {
+            resolved.set_env("WINE_CPU_TOPOLOGY", cpu_topology.to_env_value());
+        }
+        if let Some(locale) = self.locale.as_ref().or(config.locale.as_ref()) {
+            resolved.set_env("LANG", locale.as_os_str());
+            resolved.set_env("LC_ALL", locale.as_os_str());
+        }
+        if let Some(proxy) = &config.proxy {
+            for (key, value) in proxy.env() {
+                resolved.set_env(key, value);
+            }
+        }
+        let (debug_rules, start_mode) = Defaults::with(|defaults| {
+            let debug_rules = if self.debug_rules.rules.is_empty() {
+                &defaults.debug_rules
+            } else {
+                &self.debug_rules
+            };
+            (
+                debug_rules.to_env_value(),
+                self.start_mode.unwrap_or(defaults.start_mode),
+            )
+        });
+        if !debug_rules.is_empty() {
+            resolved.set_env("WINEDEBUG", debug_rules);
+        }
+        self.display_server.apply(&mut resolved);
+        self.apply_audio(&mut resolved);
+        self.apply_clock(&mut resolved);
+        resolved.envs.extend(config.extra_env.iter().cloned());
+        resolved.envs.extend(self.envs.iter().cloned());
+        if let Some(environment) = &self.restored_environment {
+            environment.apply(&mut resolved);
+        }
+
+        if let StartMode::Console(backend) = start_mode {
+            resolved.args.push("wineconsole".into());
+            resolved.args.push(backend.as_arg().into());
+        }
+        if let Some(codepage) = self.codepage {
+            resolved.args.extend(codepage::chcp_prelude(codepage));
+        }
+        if start_mode == StartMode::StartExe {
+            resolved.args.push("start".into());
+            if self.priority != Priority::Normal {
+                resolved.args.push(self.priority.start_switch().into());
+            }
+            match &self.working_dir {
+                Some(WorkingDir::Guest(dir)) => {
+                    resolved.args.extend(["/d".into(), dir.into()]);
+                }
+                Some(WorkingDir::Host(dir)) => {
+                    resolved
+                        .args
+                        .extend(["/d".into(), prefix.windows_path(dir)]);
+                }
+                None => {}
+            }
+            // `start` would otherwise read a host path as its own switches.
+            if Path::new(&self.program).is_absolute() {
+                resolved.args.push("/unix".into());
+            }
+        } else {
+            match &self.working_dir {
+                Some(WorkingDir::Guest(dir)) => {
+                    if let Ok(dir) = prefix.resolve_guest_path(dir) {
+                        resolved.current_dir = dir;
+                    }
+                }
+                Some(WorkingDir::Host(dir)) => {
+                    resolved.current_dir = dir.clone();
+                }
+                None => {}
+            }
+        }
+        resolved.args.push(self.program.clone());
+        resolved.args.extend(self.args.iter().cloned());
+        resolved.process_group = self.kill_on_drop == KillOnDrop::ProcessTree;
+
+        resolved
+    }
+
+    pub fn to_command(&self) -> Command {
+        self.resolve().to_command()
+    }
+
+    /// See [`ResolvedCommand::to_shell_string`].
+    pub fn to_shell_string(&self) -> String {
+        self.resolve().to_shell_string()
+    }
+
+    /// See [`ResolvedCommand::write_script`].
+    pub fn write_script(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.resolve().write_script(path)
+    }
+
+    pub fn spawn(&self) -> io::Result<process::Child> {
+        self.spawn_resolved("spawn", &mut self.resolve())
+    }
+
+    /// Spawns the command with its stdout and stderr piped, and its stdin
+    /// too if `pipe_stdin`.
+    pub(crate) fn spawn_captured(
+        &self,
+        pipe_stdin: bool,
+    ) -> io::Result<process::Child> {
+        let mut command = self.resolve();
+        command.capture_output = true;
+        command.pipe_stdin = pipe_stdin;
+        self.spawn_resolved("spawn_captured", &mut command)
+    }
+
+    /// Spawns `command` after running the pre-launch hooks on it.
+    pub(crate) fn spawn_resolved(
+        &self,
+        operation: &'static str,
+        command: &mut ResolvedCommand,
+    ) -> io::Result<process::Child> {
+        trace::operation(
+            operation,
+            &self.prefix.path,
+            Some(&self.program),
+            || {
+                self.check_cancelled()?;
+                self.prefix.initialize_for_launch(&self.program)?;
+                let hooks = self.all_hooks();
+                hooks.pre_launch(command);
+                let mut child = self
+                    .prefix
+                    .with_retry(
+                        || self.prefix.runner.0.spawn(command),
+                        |_| false,
+                    )
+                    .inspect_err(|error| {
+                        hooks.post_exit(command, Err(error));
+                        self.emit_spawn(Err(error));
+                    })?;
+                self.emit_spawn(Ok(child.id()));
+                // Cancelling may have raced the launch.
+                if let Err(error) = self.check_cancelled() {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(error);
+                }
+                Ok(child)
+            },
+        )
+    }
+
+    /// Runs the command to completion, inheriting stdio.
+    pub fn status(&self) -> io::Result<process::ExitStatus> {
+        trace::operation(
+            "status",
+            &self.prefix.path,
+            Some(&self.program),
+            || {
+                self.check_cancelled()?;
+                self.prefix.initialize_for_launch(&self.program)?;
+                let hooks = self.all_hooks();
+                let mut command = self.resolve();
+                hooks.pre_launch(&mut command);
+                let result = self.prefix.with_retry(
+                    || self.prefix.runner.0.status(&command),
+                    |_| false,
+                );
+                self.finish(&hooks, &command, result)
+            },
+        )
+    }
+
+    /// Reports the exit of `command` to `hooks` and the prefix events, and
+    /// fails if the launch was cancelled while it ran.
+    pub(crate) fn finish(
+        &self,
+        hooks: &Hooks,
+        command: &ResolvedCommand,
+        result: io::Result<process::ExitStatus>,
+    ) -> io::Result<process::ExitStatus> {
+        hooks.post_exit(command, result.as_ref().copied());
+        self.emit_exit(result.as_ref().copied());
+        let status = result?;
+        self.check_cancelled()?;
+        Ok(status)
+    }
+
+    /// Runs the command to completion, capturing stdout and stderr.
+    pub fn output(&self) -> io::Result<process::Output> {
+        trace::operation(
+            "output",
+            &self.prefix.path,
+            Some(&self.program),
+            || {
+                self.check_cancelled()?;
+                self.prefix.initialize_for_launch(&self.program)?;
+                let hooks = self.all_hooks();
+                let mut command = self.resolve();
+                hooks.pre_launch(&mut command);
+                let result = self.prefix.with_retry(
+                    || self.prefix.runner.0.output(&command),
+                    retry::is_transient_output,
+                );
+                let status = result.as_ref().map(|output| output.status);
+                hooks.post_exit(&command, status);
+                self.emit_exit(status);
+                let output = result?;
+                self.check_cancelled()?;
+                Ok(output)
+            },
+        )
+    }
+
+    /// Runs the command to completion, turning a failed exit status into an
+    /// error carrying its stderr.
+    pub(crate) fn output_checked(&self) -> io::Result<process::Output> {
+        let output = self.output()?;
+        if !output.status.success() {
+            return Err(Error::ProcessCrashed {
+                program: self.program.clone(),
+                status: output.status,
+                stderr: String::from_utf8_lossy(&output.stderr)
+                    .trim()
+                    .to_owned(),
+            }
+            .into());
+        }
+        Ok(output)
+    }
+}
+
+/// A launch with every decision made, exactly as it would be handed to the
+/// operating system.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ResolvedCommand {
+    pub program: OsString,
+    pub args: Vec<OsString>,
+    pub current_dir: PathBuf,
+    /// Whether the environment of the current process is discarded before
+    /// [`ResolvedCommand::envs`] are applied.
+    pub env_clear: bool,
+    /// Variables in the order they are set, where later entries win.
+    pub envs: Vec<(OsString, OsString)>,
+    /// Variables removed from the inherited environment before
+    /// [`ResolvedCommand::envs`] are applied.
+    pub unset_envs: Vec<OsString>,
+    /// Whether stdout and stderr are piped back to the caller when spawned,
+    /// rather than inherited.
+    pub capture_output: bool,
+    /// Whether only stderr is piped back to the caller when spawned.
+    pub capture_stderr: bool,
+    /// Whether stdin is piped from the caller when spawned, rather than
+    /// inherited.
+    pub pipe_stdin: bool,
+    /// Whether the process leads a process group of its own, which the
+    /// processes it starts join, so that they can be signalled together.
+    pub process_group: bool,
+}
+
+impl ResolvedCommand {
+    pub fn new(
+        program: impl AsRef<OsStr>,
+        current_dir: impl AsRef<Path>,
+    ) -> Self {
+        Self {
+            program: program.as_ref().to_os_string(),
+            args: Vec::new(),
+            current_dir: current_dir.as_ref().to_path_buf(),
+            env_clear: false,
+            envs: Vec::new(),
+            unset_envs: Vec::new(),
+            capture_output: false,
+            capture_stderr: false,
+            pipe_stdin: false,
+            process_group: false,
+        }
+    }
+
+    fn set_env(&mut self, key: impl AsRef<OsStr>, value: impl AsRef<OsStr>) {
+        self.envs
+            .push((key.as_ref().to_os_string(), value.as_ref().to_os_string()));
+    }
+
+    /// Removes `key` from the environment, including any value set so far.
+    pub(crate) fn unset_env(&mut self, key: impl AsRef<OsStr>) {
+        let key = key.as_ref();
+        self.envs.retain(|(name, _)| name != key);
+        self.unset_envs.push(key.to_os_string());
+    }
+
+    /// The value the launched process will see for `key`, if the command sets
+    /// it.
+    pub fn env(&self, key: impl AsRef<OsStr>) -> Option<&OsStr> {
+        self.envs
+            .iter()
+            .rev()
+            .find(|(name, _)| name == key.as_ref())
+            .map(|(_, value)| value.as_os_str())
+    }
+
+    /// Renders the command as a single POSIX shell line that reproduces the
+    /// launch, e.g. `cd /prefix && env WINEPREFIX=/prefix /prefix/bin/wine
+    /// winecfg`.
+    pub fn to_shell_string(&self) -> String {
+        format!(
+            "cd {} && {}",
+            shell_quote(self.current_dir.as_os_str()),
+            self.env_invocation()
+        )
+    }
+
+    /// Writes an executable shell script that performs the launch.
+    pub fn write_script(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        fs::write(
+            path,
+            format!(
+                "#!/bin/sh\ncd {} || exit 1\nexec {} \"$@\"\n",
+                shell_quote(self.current_dir.as_os_str()),
+                self.env_invocation()
+            ),
+        )?;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+    }
+
+    fn env_invocation(&self) -> String {
+        let mut words = vec!["env".to_owned()];
+        if self.env_clear {
+            words.push("-i".to_owned());
+        }
+        for key in &self.unset_envs {
+            words.extend(["-u".to_owned(), shell_quote(key)]);
+        }
+        for (key, value) in &self.envs {
+            let mut assignment = key.clone();
+            assignment.push("=");
+            assignment.push(value);
+            words.push(shell_quote(&assignment));
+        }
+        words.push(shell_quote(&self.program));
+        words.extend(self.args.iter().map(|arg| shell_quote(arg)));
+        words.join(" ")
+    }
+
+    pub fn to_command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command.args(&self.args).current_dir(&self.current_dir);
+        if self.env_clear {
+            command.env_clear();
+        }
+        for key in &self.unset_envs {
+            command.env_remove(key);
+        }
+        command.envs(self.envs.iter().map(|(key, value)| (key, value)));
+        if self.capture_output {
+            command.stdout(Stdio::piped()).stderr(Stdio::piped());
+        } else if self.capture_stderr {
+            command.stderr(Stdio::piped());
+        }
+        if self.pipe_stdin {
+            command.stdin(Stdio::piped());
+        }
+        if self.process_group {
+            command.process_group(0);
+        }
+        command
+    }
+}
+
+/// Quotes `word` for a POSIX shell, leaving it bare when that is unambiguous.
+fn shell_quote(word: &OsStr) -> String {
+    let word = word.to_string_lossy();
+    let is_bare = !word.is_empty()
+        && word.bytes().all(|byte| {
+            byte.is_ascii_alphanumeric() || b"_-+=/.,:@%".contains(&byte)
+        });
+    if is_bare {
+        word.into_owned()
+    } else {
+        format!("'{}'", word.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{OsStr, OsString};
+
+    use crate::{
+        DebugChannel, DebugClass, DebugRule, DebugRules, DllOverride,
+        DllOverrideMode, EnvironmentPolicy, PrefixBuilder,
+        runner::RecordingRunner,
+    };
+
+    fn strings(words: &[OsString]) -> Vec<&str> {
+        words.iter().map(|word| word.to_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn resolve_without_options_runs_the_loader() {
+        let prefix = PrefixBuilder::new("/prefix").build();
+        let resolved = prefix.wine_command("notepad").arg("a.txt").resolve();
+        assert_eq!(resolved.program, "/prefix/bin/wine");
+        assert_eq!(strings(&resolved.args), ["notepad", "a.txt"]);
+        assert_eq!(resolved.current_dir.to_str(), Some("/prefix"));
+        assert_eq!(resolved.env("WINEPREFIX"), Some(OsStr::new("/prefix")));
+        assert_eq!(resolved.env("WINEDLLOVERRIDES"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn resolve_chains_wrappers_before_the_loader() {
+        use crate::{Priority, ResourceLimits, VirtualDisplay};
+
+        let prefix = PrefixBuilder::new("/prefix").build();
+        let resolved = prefix
+            .wine_command("game.exe")
+            .resource_limits(ResourceLimits {
+                open_files: Some(4096),
+                ..Default::default()
+            })
+            .virtual_display(VirtualDisplay::Xvfb {
+                width: 800,
+                height: 600,
+            })
+            .priority(Priority::Idle)
+            .cpu_affinity([0, 2])
+            .resolve();
+        assert_eq!(resolved.program, "sh");
+        assert_eq!(
+            strings(&resolved.args),
+            [
+                "-c",
+                "ulimit -n 4096 && exec \"$@\"",
+                "sh",
+                "xvfb-run",
+                "--auto-servernum",
+                "--server-args=-screen 0 800x600x24",
+                "nice",
+                "-n",
+                "19",
+                "taskset",
+                "-c",
+                "0,2",
+                "/prefix/bin/wine",
+                "game.exe",
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve_formats_debug_rules_and_dll_overrides() {
+        let prefix = PrefixBuilder::new("/prefix").build();
+        let mut debug_rules = DebugRules::new();
+        debug_rules
+            .add(DebugRule {
+                process: Some(OsStr::new("game.exe")),
+                class: Some(DebugClass::Warn),
+                channel: DebugChannel::Relay,
+                is_enabled: true,
+            })
+            .disable(DebugChannel::Heap);
+        let resolved = prefix
+            .wine_command("game.exe")
+            .debug_rules(&debug_rules)
+            .dll_override(DllOverride::new(
+                "d3d11",
+                DllOverrideMode::NativeThenBuiltin,
+            ))
+            .dll_override(DllOverride::new(
+                "mscoree",
+                DllOverrideMode::Disabled,
+            ))
+            .resolve();
+        assert_eq!(
+            resolved.env("WINEDEBUG"),
+            Some(OsStr::new("game.exe:warn+relay,-heap"))
+        );
+        assert_eq!(
+            resolved.env("WINEDLLOVERRIDES"),
+            Some(OsStr::new("d3d11=n,b;mscoree="))
+        );
+    }
+
+    #[test]
+    fn resolve_applies_the_environment_policy() {
+        let prefix = PrefixBuilder::new("/prefix").build();
+        let resolved = prefix
+            .wine_command("game.exe")
+            .environment(EnvironmentPolicy::Deny(vec!["WINEDEBUG".into()]))
+            .resolve();
+        assert!(!resolved.env_clear);
+        assert_eq!(resolved.unset_envs, [OsString::from("WINEDEBUG")]);
+
+        let resolved = prefix.wine_command("game.exe").env_clear().resolve();
+        assert!(resolved.env_clear);
+        assert_eq!(resolved.env("WINEPREFIX"), Some(OsStr::new("/prefix")));
+    }
+
+    #[test]
+    fn launches_go_through_the_runner() {
+        let (prefix, runner) =
+            RecordingRunner::attach(PrefixBuilder::new("/prefix").build());
+        let mut command = prefix.wine_command("setup.exe");
+        command.arg("/S");
+        assert!(command.status().unwrap().success());
+        command.output().unwrap();
+        command.spawn().unwrap().wait().unwrap();
+
+        let recorded = runner.take();
+        assert_eq!(recorded.len(), 3);
+        for resolved in &recorded {
+            assert_eq!(*resolved, command.resolve());
+        }
+        assert_eq!(strings(&recorded[0].args), ["setup.exe", "/S"]);
+    }
+}
@@ -0,0 +1,481 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Reading the backtraces winedbg prints when a program crashes, and
+//! resolving their frames to functions and lines with the PDB or map files
+//! of the program, which Wine cannot find on its own.
+
+use std::{fmt, fs, io, path::Path};
+
+use crate::{Prefix, pdb, registry::RegistryKey};
+
+/// Where a frame is in the source code.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: u32,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+/// A line of a backtrace such as
+/// `=>0 0x0000000140001016 in app (+0x1016) (0x000000000011fe40)`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Frame {
+    pub index: u32,
+    pub address: u64,
+    pub function: Option<String>,
+    /// How far into the function the address is.
+    pub function_offset: Option<u64>,
+    pub source: Option<SourceLocation>,
+    /// The module without its extension, e.g., `app` for `app.exe`.
+    pub module: Option<String>,
+    /// How far into the module the address is, i.e., its RVA, which winedbg
+    /// prints for frames it has no symbols for.
+    pub module_offset: Option<u64>,
+}
+
+fn parse_hex(text: &str) -> Option<u64> {
+    u64::from_str_radix(text.strip_prefix("0x")?, 16).ok()
+}
+
+impl Frame {
+    /// Returns `None` if `line` is not a frame of a winedbg backtrace.
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        let line = line.strip_prefix("=>").unwrap_or(line).trim_start();
+        let (index, rest) = line.split_once(' ')?;
+        let index = index.parse().ok()?;
+        let (address, mut rest) = rest.split_once(' ').unwrap_or((rest, ""));
+        let address = parse_hex(address)?;
+
+        let mut frame = Self {
+            index,
+            address,
+            function: None,
+            function_offset: None,
+            source: None,
+            module: None,
+            module_offset: None,
+        };
+        let module = match rest.strip_prefix("in ") {
+            Some(module) => Some(("", module)),
+            None => rest.rsplit_once(" in "),
+        };
+        if let Some((before, module)) = module {
+            let (name, after) = module.split_once(' ').unwrap_or((module, ""));
+            frame.module = Some(name.to_owned());
+            frame.module_offset = after
+                .strip_prefix("(+")
+                .and_then(|after| after.split_once(')'))
+                .and_then(|(offset, _)| parse_hex(offset));
+            rest = before;
+        }
+        if let Some((before, source)) = rest.rsplit_once(" [")
+            && let Some((file, line)) = source
+                .strip_suffix(']')
+                .and_then(|source| source.rsplit_once(':'))
+            && let Ok(line) = line.parse()
+        {
+            frame.source = Some(SourceLocation {
+                file: file.to_owned(),
+                line,
+            });
+            rest = before;
+        }
+        // The arguments follow the function in parentheses.
+        let function = rest.split('(').next().unwrap_or_default().trim();
+        if !function.is_empty() {
+            let (name, offset) = match function.rsplit_once("+0x") {
+                Some((name, offset)) => {
+                    (name, u64::from_str_radix(offset, 16).ok())
+                }
+                None => (function, None),
+            };
+            frame.function = Some(name.to_owned());
+            frame.function_offset = offset;
+        }
+        Some(frame)
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:>3} 0x{:016x}", self.index, self.address)?;
+        if let Some(function) = &self.function {
+            write!(f, " {function}")?;
+            if let Some(offset) = self.function_offset {
+                write!(f, "+0x{offset:x}")?;
+            }
+        }
+        if let Some(source) = &self.source {
+            write!(f, " [{source}]")?;
+        }
+        if let Some(module) = &self.module {
+            write!(f, " in {module}")?;
+            if let Some(offset) = self.module_offset {
+                write!(f, " (+0x{offset:x})")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The frames of the crashing thread, innermost first.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Backtrace {
+    pub frames: Vec<Frame>,
+}
+
+impl Backtrace {
+    /// Finds the first backtrace in the output of a crashed program, e.g.,
+    /// the stderr of [`crate::Error::ProcessCrashed`].
+    pub fn parse(output: &str) -> Option<Self> {
+        let mut lines = output.lines();
+        lines.find(|line| line.trim() == "Backtrace:")?;
+        let frames: Vec<_> = lines.map_while(Frame::parse).collect();
+        (!frames.is_empty()).then_some(Self { frames })
+    }
+}
+
+impl fmt::Display for Backtrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for frame in &self.frames {
+            writeln!(f, "{frame}")?;
+        }
+        Ok(())
+    }
+}
+
+/// What an address of a module resolves to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Symbol {
+    pub function: String,
+    /// How far into the function the address is.
+    pub offset: u64,
+    pub source: Option<SourceLocation>,
+}
+
+/// The functions of one module, and the lines of their code when the
+/// symbols record them, by RVA.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SymbolTable {
+    /// Sorted by RVA, with the size when it is known.
+    functions: Vec<(u64, Option<u64>, String)>,
+    /// Sorted by RVA, with an index into `files`.
+    lines: Vec<(u64, usize, u32)>,
+    files: Vec<String>,
+}
+
+impl SymbolTable {
+    /// Reads a PDB or a map file written by the MSVC or GNU linker.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let data = fs::read(path)?;
+        if pdb::is_pdb(&data) {
+            let symbols = pdb::read(&data)?;
+            let mut table = Self {
+                functions: symbols
+                    .functions
+                    .into_iter()
+                    .map(|function| {
+                        (
+                            u64::from(function.rva),
+                            function.size.map(u64::from),
+                            function.name,
+                        )
+                    })
+                    .collect(),
+                lines: symbols
+                    .lines
+                    .into_iter()
+                    .map(|line| (u64::from(line.rva), line.file, line.line))
+                    .collect(),
+                files: symbols.files,
+            };
+            table.sort();
+            return Ok(table);
+        }
+        let text = String::from_utf8_lossy(&data);
+        Self::from_map(&text).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} is neither a PDB nor a map file", path.display()),
+            )
+        })
+    }
+
+    /// Publics from `Publics by Value` and `Static symbols` of MSVC, or the
+    /// symbols of `.text` of GNU ld, whose addresses are relative to
+    /// `__image_base__`.
+    fn from_map(text: &str) -> Option<Self> {
+        let mut table = Self::default();
+        if let Some((_, rest)) = text.split_once("Preferred load address is ") {
+            let base = u64::from_str_radix(rest.split_whitespace().next()?, 16)
+                .ok()?;
+            let lines: Vec<Vec<_>> = text
+                .lines()
+                .map(|line| line.split_whitespace().collect())
+                .collect();
+            // `0001:00000000 00001000H .text$mn CODE`
+            let code_sections: Vec<_> = lines
+                .iter()
+                .filter_map(|tokens| match tokens[..] {
+                    [start, _, _, "CODE"] => start.split_once(':'),
+                    _ => None,
+                })
+                .map(|(section, _)| section)
+                .collect();
+            for tokens in &lines {
+                // `0001:00000000 main 0000000140001000 f main.obj`, where
+                // only MSVC marks functions with `f`.
+                if let [start, name, address, _, ..] = tokens[..]
+                    && let Some((section, _)) = start.split_once(':')
+                    && code_sections.contains(&section)
+                    && let Ok(address) = u64::from_str_radix(address, 16)
+                    && let Some(rva) = address.checked_sub(base)
+                {
+                    table.functions.push((rva, None, name.to_owned()));
+                }
+            }
+        } else {
+            let mut base = None;
+            let mut in_text = false;
+            let mut text_symbols = Vec::new();
+            for line in text.lines() {
+                if !line.starts_with(' ') && !line.is_empty() {
+                    in_text = line.split_whitespace().next() == Some(".text");
+                }
+                let tokens: Vec<_> = line.split_whitespace().collect();
+                match tokens[..] {
+                    [address, "__image_base__", ..]
+                    | [address, "___ImageBase", ..] => {
+                        base = parse_hex(address);
+                    }
+                    [address, name] if in_text && line.starts_with("  ") => {
+                        if let Some(address) = parse_hex(address) {
+                            text_symbols.push((address, name));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            let base = base?;
+            for (address, name) in text_symbols {
+                if let Some(rva) = address.checked_sub(base) {
+                    table.functions.push((rva, None, name.to_owned()));
+                }
+            }
+        }
+        if table.functions.is_empty() {
+            return None;
+        }
+        table.sort();
+        Some(table)
+    }
+
+    /// Sorts the entries and keeps one function per address, preferring one
+    /// whose size is known, as procedures of a PDB duplicate its publics.
+    fn sort(&mut self) {
+        self.functions.sort_by(|a, b| {
+            a.0.cmp(&b.0)
+                .then_with(|| b.1.is_some().cmp(&a.1.is_some()))
+        });
+        self.functions.dedup_by_key(|function| function.0);
+        self.lines.sort_by_key(|line| line.0);
+    }
+
+    /// The function containing `rva`, or `None` if it is outside every one
+    /// whose size is known or before the first.
+    pub fn lookup(&self, rva: u64) -> Option<Symbol> {
+        let index =
+            self.functions.partition_point(|function| function.0 <= rva);
+        let (start, size, name) = self.functions.get(index.checked_sub(1)?)?;
+        if size.is_some_and(|size| rva >= start + size) {
+            return None;
+        }
+        let line = self.lines.partition_point(|line| line.0 <= rva);
+        let source = line
+            .checked_sub(1)
+            .map(|line| &self.lines[line])
+            .filter(|line| line.0 >= *start)
+            .map(|(_, file, line)| SourceLocation {
+                file: self.files[*file].clone(),
+                line: *line,
+            });
+        Some(Symbol {
+            function: name.clone(),
+            offset: rva - start,
+            source,
+        })
+    }
+}
+
+/// The symbols of the modules of a program, used to fill in the frames
+/// winedbg could not resolve.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Symbolicator {
+    modules: Vec<(String, SymbolTable)>,
+}
+
+/// `app` for `App.exe`, as winedbg names modules.
+fn module_key(module: &str) -> String {
+    let module = module.to_ascii_lowercase();
+    match module.rsplit_once('.') {
+        Some((stem, "exe" | "dll")) => stem.to_owned(),
+        _ => module,
+    }
+}
+
+impl Symbolicator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolves frames in `module`, e.g., `app.exe`, with the PDB or map file
+    /// at `path`.
+    pub fn load(
+        &mut self,
+        module: &str,
+        path: impl AsRef<Path>,
+    ) -> io::Result<&mut Self> {
+        let table = SymbolTable::load(path)?;
+        Ok(self.add(module, table))
+    }
+
+    pub fn add(&mut self, module: &str, table: SymbolTable) -> &mut Self {
+        let key = module_key(module);
+        self.modules.retain(|(module, _)| *module != key);
+        self.modules.push((key, table));
+        self
+    }
+
+    /// Fills in the function and line of every frame of `backtrace` that has
+    /// an offset into a module with symbols.
+    pub fn symbolicate(&self, backtrace: &Backtrace) -> Backtrace {
+        let mut backtrace = backtrace.clone();
+        for frame in &mut backtrace.frames {
+            let (Some(module), Some(offset)) =
+                (&frame.module, frame.module_offset)
+            else {
+                continue;
+            };
+            let key = module_key(module);
+            let Some(symbol) = self
+                .modules
+                .iter()
+                .find(|(module, _)| *module == key)
+                .and_then(|(_, table)| table.lookup(offset))
+            else {
+                continue;
+            };
+            frame.function = Some(symbol.function);
+            frame.function_offset = Some(symbol.offset);
+            frame.source = symbol.source.or(frame.source.take());
+        }
+        backtrace
+    }
+}
+
+impl Prefix {
+    /// Whether winedbg shows a dialog when a program crashes rather than
+    /// printing the backtrace to the terminal, where [`Backtrace::parse`]
+    /// finds it.
+    pub fn set_crash_dialog(&self, is_enabled: bool) -> io::Result<()> {
+        self.set_registry_value(
+            &RegistryKey::current_user("Software\\Wine\\WineDbg"),
+            Some("ShowCrashDialog"),
+            u32::from(is_enabled),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use super::{Backtrace, Frame, SourceLocation, Symbolicator};
+    use crate::pdb;
+
+    /// What winedbg printed for a null dereference in `main` of `app.exe`.
+    const CRASH: &str = r"Unhandled exception: page fault on read access to 0x0000000000000000 in 64-bit code (0x0000000140001016).
+Register dump:
+ rip:0000000140001016 rsp:000000000011fe40 rbp:000000000011fe70 eflags:00010246 (  R- --  I  Z- -P- )
+Stack dump:
+0x000000000011fe40:  0000000000000000 0000000000000000
+Backtrace:
+=>0 0x0000000140001016 in app (+0x1016) (0x000000000011fe40)
+  1 0x000000014000104a in app (+0x104a) (0x000000000011fe70)
+  2 0x00006fffffc4a5cd BaseThreadInitThunk+0xd(unknown=<couldn't compute location>, entry=<couldn't compute location>, arg=<couldn't compute location>) [Z:\usr\src\wine\dlls\kernel32\thread.c:61] in kernel32 (0x000000000011fea0)
+  3 0x00006fffffd8a4b7 RtlUserThreadStart+0x27(entry=0x140001000, arg=0x0) [Z:\usr\src\wine\dlls\ntdll\thread.c:1173] in ntdll (0x000000000011ff70)
+0x0000000140001016 app+0x1016: movl (%rax),%eax
+Modules:
+Module	Address					Debug info	Name (5 modules)
+";
+
+    #[test]
+    fn parses_winedbg_backtraces() {
+        let backtrace = Backtrace::parse(CRASH).unwrap();
+        assert_eq!(backtrace.frames.len(), 4);
+        assert_eq!(
+            backtrace.frames[0],
+            Frame {
+                index: 0,
+                address: 0x1_4000_1016,
+                function: None,
+                function_offset: None,
+                source: None,
+                module: Some("app".into()),
+                module_offset: Some(0x1016),
+            }
+        );
+        assert_eq!(
+            backtrace.frames[2],
+            Frame {
+                index: 2,
+                address: 0x6fff_ffc4_a5cd,
+                function: Some("BaseThreadInitThunk".into()),
+                function_offset: Some(0xd),
+                source: Some(SourceLocation {
+                    file: r"Z:\usr\src\wine\dlls\kernel32\thread.c".into(),
+                    line: 61,
+                }),
+                module: Some("kernel32".into()),
+                module_offset: None,
+            }
+        );
+        assert!(Frame::parse("Modules:").is_none());
+        assert!(Backtrace::parse("Register dump:\n").is_none());
+    }
+
+    #[test]
+    fn symbolicates_frames_with_a_pdb() {
+        let path = env::temp_dir()
+            .join(format!("wine-rs-crash-test-{}.pdb", process::id()));
+        fs::write(&path, pdb::fixtures::pdb()).unwrap();
+        let mut symbolicator = Symbolicator::new();
+        symbolicator.load("App.exe", &path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        let backtrace =
+            symbolicator.symbolicate(&Backtrace::parse(CRASH).unwrap());
+        assert_eq!(
+            backtrace.frames[0].to_string(),
+            r"  0 0x0000000140001016 main+0x6 [src\main.cpp:3] in app (+0x1016)"
+        );
+        assert_eq!(backtrace.frames[1].function.as_deref(), Some("helper"));
+        assert_eq!(backtrace.frames[1].function_offset, Some(0xa));
+        assert_eq!(backtrace.frames[1].source, None);
+        assert_eq!(
+            backtrace.frames[3].function.as_deref(),
+            Some("RtlUserThreadStart")
+        );
+    }
+}
@@ -0,0 +1,157 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Driving bottles created with CodeWeavers' CrossOver.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{Prefix, PrefixBuilder, PrefixConfig, WineFlavor};
+
+/// Where CrossOver installs its Wine build.
+#[cfg(target_os = "macos")]
+pub const DEFAULT_INSTALL_PATH: &str =
+    "/Applications/CrossOver.app/Contents/SharedSupport/CrossOver";
+#[cfg(not(target_os = "macos"))]
+pub const DEFAULT_INSTALL_PATH: &str = "/opt/cxoffice";
+
+/// The directory CrossOver keeps bottles in for the current user.
+pub fn bottles_dir() -> Option<PathBuf> {
+    let home = PathBuf::from(env::var_os("HOME")?);
+    Some(if cfg!(target_os = "macos") {
+        home.join("Library/Application Support/CrossOver/Bottles")
+    } else {
+        home.join(".cxoffice")
+    })
+}
+
+/// The settings of a bottle, read from its `cxbottle.conf`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Bottle {
+    pub name: String,
+    pub path: PathBuf,
+    /// `win32` or `win64`.
+    pub arch: Option<String>,
+    /// The template the bottle was created from, e.g., `win10_64`.
+    pub template: Option<String>,
+    /// The `[EnvironmentVariables]` section.
+    pub env: Vec<(String, String)>,
+}
+
+/// Strips the quotes around a `cxbottle.conf` key or value.
+fn unquote(text: &str) -> &str {
+    let text = text.trim();
+    text.strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+        .unwrap_or(text)
+}
+
+impl Bottle {
+    /// Reads the bottle at `path`, the directory containing `cxbottle.conf`.
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let conf = fs::read_to_string(path.join("cxbottle.conf"))?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut bottle = Self {
+            name,
+            path: path.to_path_buf(),
+            arch: None,
+            template: None,
+            env: Vec::new(),
+        };
+
+        let mut section = "";
+        for line in conf.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') || line.starts_with('#')
+            {
+                continue;
+            }
+            if let Some(name) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+            {
+                section = name;
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let (key, value) = (unquote(key), unquote(value).to_owned());
+            match (section, key) {
+                ("Bottle", "WineArch") => bottle.arch = Some(value),
+                ("Bottle", "Template") => bottle.template = Some(value),
+                ("EnvironmentVariables", _) => {
+                    bottle.env.push((key.to_owned(), value));
+                }
+                _ => {}
+            }
+        }
+        Ok(bottle)
+    }
+
+    /// Reads every bottle in [`bottles_dir`].
+    pub fn list() -> io::Result<Vec<Self>> {
+        let Some(dir) = bottles_dir() else {
+            return Ok(Vec::new());
+        };
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok(Vec::new());
+            }
+            Err(error) => return Err(error),
+        };
+        let mut bottles = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.join("cxbottle.conf").is_file() {
+                bottles.push(Self::read(path)?);
+            }
+        }
+        bottles.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(bottles)
+    }
+
+    /// The configuration CrossOver would launch this bottle with. The sync
+    /// toggles are lifted into their typed options and the rest of the
+    /// environment is passed through.
+    pub fn config(&self) -> PrefixConfig {
+        let mut config = PrefixConfig {
+            flavor: Some(WineFlavor::CrossOver),
+            ..PrefixConfig::default()
+        };
+        for (key, value) in &self.env {
+            let is_enabled = value == "1";
+            match key.as_str() {
+                "WINEESYNC" | "ESYNC" => config.esync = is_enabled,
+                "WINEMSYNC" | "MSYNC" => config.msync = is_enabled,
+                _ => config.extra_env.push((key.into(), value.into())),
+            }
+        }
+        config
+            .extra_env
+            .push(("CX_BOTTLE".into(), self.name.clone().into()));
+        config
+    }
+
+    /// A prefix for this bottle run by the CrossOver installation at
+    /// `install_path`, such as [`DEFAULT_INSTALL_PATH`].
+    pub fn to_prefix(&self, install_path: impl AsRef<Path>) -> Prefix {
+        let bin_dir = install_path.as_ref().join("bin");
+        let mut builder = PrefixBuilder::new(&self.path);
+        builder
+            .wine(bin_dir.join("wine"))
+            .wineserver(bin_dir.join("wineserver"))
+            .config(self.config());
+        builder.build()
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{path::PathBuf, sync::RwLock};
+
+use crate::{DebugRules, EnvironmentPolicy, StartMode};
+
+static DEFAULTS: RwLock<Defaults> = RwLock::new(Defaults::new());
+
+/// Settings shared by every prefix and launch in the process, which fall
+/// back to them when not given their own, so that an application can set
+/// them once at startup instead of at every call site.
+///
+/// Changes apply to launches resolved afterward, from any thread.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Defaults {
+    /// Used by launches given no rules of their own, e.g., through
+    /// [`crate::WineCommand::debug_rules`].
+    pub debug_rules: DebugRules<'static>,
+    /// Used by prefixes built without dynamic library paths.
+    pub dynamic_library_paths: Vec<PathBuf>,
+    /// Used by launches not given [`crate::WineCommand::start_mode`]. The
+    /// programs this crate runs itself, such as `reg` or installers, always
+    /// run directly so that they can be waited on.
+    pub start_mode: StartMode,
+    /// Used by prefixes configured without a policy of their own.
+    pub environment: EnvironmentPolicy,
+}
+
+impl Default for Defaults {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Defaults {
+    const fn new() -> Self {
+        Self {
+            debug_rules: DebugRules { rules: Vec::new() },
+            dynamic_library_paths: Vec::new(),
+            start_mode: StartMode::Direct,
+            environment: EnvironmentPolicy::Inherit,
+        }
+    }
+
+    /// The current defaults.
+    pub fn get() -> Self {
+        DEFAULTS.read().unwrap().clone()
+    }
+
+    pub fn set(defaults: Defaults) {
+        *DEFAULTS.write().unwrap() = defaults;
+    }
+
+    /// Changes the current defaults in place, e.g., to add a debug rule
+    /// without losing the other settings to a concurrent [`Defaults::set`].
+    pub fn update(change: impl FnOnce(&mut Defaults)) {
+        change(&mut DEFAULTS.write().unwrap());
+    }
+
+    /// Calls `read` on the current defaults without copying them.
+    pub(crate) fn with<T>(read: impl FnOnce(&Defaults) -> T) -> T {
+        read(&DEFAULTS.read().unwrap())
+    }
+}
@@ -0,0 +1,238 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Parsing the messages that Wine writes to stderr as configured by
+//! `WINEDEBUG`, and forwarding them to the `log` or `tracing` ecosystems.
+
+#[cfg(any(feature = "log", feature = "tracing"))]
+use std::io::BufRead;
+use std::{
+    io,
+    process::ExitStatus,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    Codepage, DebugClass, WineCommand, WineExit, serialize::serialize_fields,
+};
+
+/// A line such as `0024:fixme:ntdll:NtQuerySystemInformation info_class 0x94`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// Seconds since the process started, present with `+timestamp`.
+    pub timestamp: Option<f64>,
+    /// Present with `+pid`.
+    pub pid: Option<u32>,
+    pub tid: Option<u32>,
+    pub class: DebugClass,
+    pub channel: String,
+    pub function: String,
+    pub message: String,
+}
+
+serialize_fields!(Diagnostic {
+    timestamp,
+    pid,
+    tid,
+    class,
+    channel,
+    function,
+    message
+});
+
+impl Diagnostic {
+    /// Returns `None` if `line` was not emitted by Wine's debug machinery.
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut rest = line.trim_end_matches(['\r', '\n']);
+
+        let mut timestamp = None;
+        let mut ids = Vec::new();
+        let class = loop {
+            let (token, after) = rest.split_once(':')?;
+            rest = after;
+            if let Some(class) = DebugClass::from_name(token) {
+                break class;
+            }
+            if timestamp.is_none()
+                && ids.is_empty()
+                && token.contains('.')
+                && let Ok(seconds) = token.parse()
+            {
+                timestamp = Some(seconds);
+            } else if ids.len() < 2
+                && !token.is_empty()
+                && token.bytes().all(|byte| byte.is_ascii_hexdigit())
+            {
+                ids.push(u32::from_str_radix(token, 16).ok()?);
+            } else {
+                return None;
+            }
+        };
+
+        let (channel, rest) = rest.split_once(':')?;
+        if channel.is_empty()
+            || !channel
+                .bytes()
+                .all(|byte| byte.is_ascii_alphanumeric() || byte == b'_')
+        {
+            return None;
+        }
+        let (function, message) = rest.split_once(' ').unwrap_or((rest, ""));
+
+        let (pid, tid) = match ids[..] {
+            [pid, tid] => (Some(pid), Some(tid)),
+            [tid] => (None, Some(tid)),
+            _ => (None, None),
+        };
+        Some(Self {
+            timestamp,
+            pid,
+            tid,
+            class,
+            channel: channel.to_owned(),
+            function: function.to_owned(),
+            message: message.to_owned(),
+        })
+    }
+}
+
+/// The output of a program with Wine's diagnostics taken out of its stderr.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DemuxedOutput {
+    pub status: ExitStatus,
+    pub exit: WineExit,
+    pub stdout: Vec<u8>,
+    /// What the program itself wrote to stderr.
+    pub stderr: Vec<u8>,
+    pub diagnostics: Vec<Diagnostic>,
+    /// The [`WineCommand::codepage`] of the program, or else UTF-8, which
+    /// [`DemuxedOutput::stdout_text`] and [`DemuxedOutput::stderr_text`]
+    /// decode with.
+    pub codepage: Codepage,
+    /// How long the program ran, from spawning the loader to its exit.
+    pub elapsed: Duration,
+}
+
+/// Splits `stderr` into the lines written by the program and the diagnostics
+/// written by Wine.
+pub fn demultiplex(stderr: &[u8]) -> (Vec<u8>, Vec<Diagnostic>) {
+    let mut program_stderr = Vec::new();
+    let mut diagnostics = Vec::new();
+    for line in stderr.split_inclusive(|&byte| byte == b'\n') {
+        match Diagnostic::parse(&String::from_utf8_lossy(line)) {
+            Some(diagnostic) => diagnostics.push(diagnostic),
+            None => program_stderr.extend_from_slice(line),
+        }
+    }
+    (program_stderr, diagnostics)
+}
+
+impl WineCommand<'_> {
+    /// Like [`WineCommand::output`], but with the diagnostics separated from
+    /// the program's own stderr.
+    pub fn output_demuxed(&self) -> io::Result<DemuxedOutput> {
+        let start = Instant::now();
+        let output = self.output()?;
+        let elapsed = start.elapsed();
+        let (stderr, diagnostics) = demultiplex(&output.stderr);
+        Ok(DemuxedOutput {
+            status: output.status,
+            exit: WineExit::interpret(
+                output.status,
+                &String::from_utf8_lossy(&stderr),
+            ),
+            stdout: output.stdout,
+            stderr,
+            diagnostics,
+            codepage: self.codepage.unwrap_or(Codepage::UTF8),
+            elapsed,
+        })
+    }
+}
+
+#[cfg(feature = "log")]
+impl DebugClass {
+    pub fn log_level(&self) -> log::Level {
+        match self {
+            Self::Trace => log::Level::Trace,
+            Self::Fixme => log::Level::Debug,
+            Self::Warn => log::Level::Warn,
+            Self::Error => log::Level::Error,
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl DebugClass {
+    pub fn tracing_level(&self) -> tracing::Level {
+        match self {
+            Self::Trace => tracing::Level::TRACE,
+            Self::Fixme => tracing::Level::DEBUG,
+            Self::Warn => tracing::Level::WARN,
+            Self::Error => tracing::Level::ERROR,
+        }
+    }
+}
+
+/// Re-emits every diagnostic read from `reader` as a `log` record targeted at
+/// `wine::<channel>`. Other lines are program output and are logged at info
+/// level under `wine::stderr`.
+#[cfg(feature = "log")]
+pub fn forward_to_log(reader: impl BufRead) -> io::Result<()> {
+    for line in reader.lines() {
+        let line = line?;
+        match Diagnostic::parse(&line) {
+            Some(diagnostic) => log::log!(
+                target: &format!("wine::{}", diagnostic.channel),
+                diagnostic.class.log_level(),
+                "{}: {}",
+                diagnostic.function,
+                diagnostic.message
+            ),
+            None => log::info!(target: "wine::stderr", "{line}"),
+        }
+    }
+    Ok(())
+}
+
+/// Re-emits every diagnostic read from `reader` as a `tracing` event with the
+/// channel recorded as a field, since `tracing` targets must be static. Other
+/// lines are program output and are emitted at info level.
+#[cfg(feature = "tracing")]
+pub fn forward_to_tracing(reader: impl BufRead) -> io::Result<()> {
+    use tracing::Level;
+
+    macro_rules! emit {
+        ($level:expr, $diagnostic:expr) => {
+            tracing::event!(
+                target: "wine",
+                $level,
+                channel = %$diagnostic.channel,
+                function = %$diagnostic.function,
+                pid = $diagnostic.pid,
+                tid = $diagnostic.tid,
+                "{}",
+                $diagnostic.message
+            )
+        };
+    }
+
+    for line in reader.lines() {
+        let line = line?;
+        match Diagnostic::parse(&line) {
+            // `tracing::event!` only takes levels known at compile time.
+            Some(diagnostic) => match diagnostic.class.tracing_level() {
+                Level::TRACE => emit!(Level::TRACE, diagnostic),
+                Level::DEBUG => emit!(Level::DEBUG, diagnostic),
+                Level::INFO => emit!(Level::INFO, diagnostic),
+                Level::WARN => emit!(Level::WARN, diagnostic),
+                _ => emit!(Level::ERROR, diagnostic),
+            },
+            None => tracing::info!(target: "wine::stderr", "{line}"),
+        }
+    }
+    Ok(())
+}
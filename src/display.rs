@@ -0,0 +1,162 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::ffi::OsString;
+
+use crate::{ResolvedCommand, WineCommand};
+
+/// How gamescope scales the program up to the output resolution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Upscaler {
+    Linear,
+    Nearest,
+    /// AMD FidelityFX Super Resolution 1.0.
+    Fsr,
+    /// NVIDIA Image Scaling.
+    Nis,
+}
+
+impl Upscaler {
+    /// The spelling used by `gamescope --filter`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::Nearest => "nearest",
+            Self::Fsr => "fsr",
+            Self::Nis => "nis",
+        }
+    }
+}
+
+/// A gamescope session, which runs the program in a compositor of its own.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Gamescope {
+    /// The resolution the program renders at.
+    pub resolution: Option<(u32, u32)>,
+    /// The resolution of the gamescope window, which defaults to
+    /// [`Gamescope::resolution`].
+    pub output_resolution: Option<(u32, u32)>,
+    pub refresh_rate: Option<u32>,
+    pub upscaler: Option<Upscaler>,
+    pub fullscreen: bool,
+    /// Passed before the `--` that precedes the program, for options this
+    /// does not cover.
+    pub extra_args: Vec<String>,
+}
+
+/// A display of its own for a launch, started with it and torn down when it
+/// exits, since the wrapper runs the loader as its child. Killing the wrapper
+/// alone can leave the program or the display behind, so spawned launches
+/// should use [`crate::KillOnDrop::ProcessTree`]. Linux only: it is ignored
+/// elsewhere.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum VirtualDisplay {
+    Gamescope(Gamescope),
+    /// A headless X server through `xvfb-run`, e.g., to run installers on a
+    /// machine without a display.
+    Xvfb {
+        width: u32,
+        height: u32,
+    },
+}
+
+impl VirtualDisplay {
+    /// The programs and arguments to run the loader through.
+    pub(crate) fn wrapper(&self) -> Vec<OsString> {
+        if !cfg!(target_os = "linux") {
+            return Vec::new();
+        }
+        let mut wrapper: Vec<String> = Vec::new();
+        match self {
+            Self::Gamescope(gamescope) => {
+                wrapper.push("gamescope".into());
+                if let Some((width, height)) = gamescope.resolution {
+                    wrapper.extend([
+                        "-w".into(),
+                        width.to_string(),
+                        "-h".into(),
+                        height.to_string(),
+                    ]);
+                }
+                if let Some((width, height)) = gamescope.output_resolution {
+                    wrapper.extend([
+                        "-W".into(),
+                        width.to_string(),
+                        "-H".into(),
+                        height.to_string(),
+                    ]);
+                }
+                if let Some(refresh_rate) = gamescope.refresh_rate {
+                    wrapper.extend(["-r".into(), refresh_rate.to_string()]);
+                }
+                if let Some(upscaler) = gamescope.upscaler {
+                    wrapper.extend(["-F".into(), upscaler.as_str().into()]);
+                }
+                if gamescope.fullscreen {
+                    wrapper.push("-f".into());
+                }
+                wrapper.extend(gamescope.extra_args.iter().cloned());
+                wrapper.push("--".into());
+            }
+            Self::Xvfb { width, height } => {
+                wrapper.extend([
+                    "xvfb-run".into(),
+                    // Picks a free display number.
+                    "--auto-servernum".into(),
+                    format!("--server-args=-screen 0 {width}x{height}x24"),
+                ]);
+            }
+        }
+        wrapper.into_iter().map(Into::into).collect()
+    }
+}
+
+/// Which display server a launch talks to, instead of whichever the inherited
+/// `DISPLAY` and `WAYLAND_DISPLAY` lead Wine to.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub enum DisplayServer {
+    #[default]
+    Inherit,
+    /// Hides `WAYLAND_DISPLAY`, and sets `DISPLAY` to `display`, e.g., `:1`,
+    /// if given.
+    X11 { display: Option<String> },
+    /// Hides `DISPLAY`, so that the x11 driver steps aside, and sets
+    /// `WAYLAND_DISPLAY` to `display` if given. Wine only falls back to its
+    /// Wayland driver if the prefix lists it, e.g., through
+    /// [`crate::Prefix::set_graphics_drivers`].
+    Wayland { display: Option<String> },
+}
+
+impl DisplayServer {
+    pub(crate) fn apply(&self, command: &mut ResolvedCommand) {
+        let (hidden, shown, display) = match self {
+            Self::Inherit => return,
+            Self::X11 { display } => ("WAYLAND_DISPLAY", "DISPLAY", display),
+            Self::Wayland { display } => {
+                ("DISPLAY", "WAYLAND_DISPLAY", display)
+            }
+        };
+        command.unset_env(hidden);
+        if let Some(display) = display {
+            command.envs.push((shown.into(), display.into()));
+        }
+    }
+}
+
+impl WineCommand<'_> {
+    pub fn virtual_display(&mut self, display: VirtualDisplay) -> &mut Self {
+        self.virtual_display = Some(display);
+        self
+    }
+
+    pub fn display_server(
+        &mut self,
+        display_server: DisplayServer,
+    ) -> &mut Self {
+        self.display_server = display_server;
+        self
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::io;
+
+use crate::{
+    Prefix,
+    registry::{RegistryKey, RegistryValue},
+    serialize::{serialize_as_str, serialize_fields},
+    settings::app_defaults_key,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DllOverrideMode {
+    Native,
+    Builtin,
+    NativeThenBuiltin,
+    BuiltinThenNative,
+    Disabled,
+}
+
+impl DllOverrideMode {
+    /// The spelling used by the `DllOverrides` registry key.
+    pub fn as_registry_str(&self) -> &'static str {
+        match self {
+            Self::Native => "native",
+            Self::Builtin => "builtin",
+            Self::NativeThenBuiltin => "native,builtin",
+            Self::BuiltinThenNative => "builtin,native",
+            Self::Disabled => "",
+        }
+    }
+
+    /// The spelling used by `WINEDLLOVERRIDES`.
+    pub fn as_env_str(&self) -> &'static str {
+        match self {
+            Self::Native => "n",
+            Self::Builtin => "b",
+            Self::NativeThenBuiltin => "n,b",
+            Self::BuiltinThenNative => "b,n",
+            Self::Disabled => "",
+        }
+    }
+
+    /// Accepts both the registry and the `WINEDLLOVERRIDES` spellings.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim() {
+            "native" | "n" => Some(Self::Native),
+            "builtin" | "b" => Some(Self::Builtin),
+            "native,builtin" | "n,b" => Some(Self::NativeThenBuiltin),
+            "builtin,native" | "b,n" => Some(Self::BuiltinThenNative),
+            "" | "disabled" | "d" => Some(Self::Disabled),
+            _ => None,
+        }
+    }
+}
+
+serialize_as_str!(DllOverrideMode, as_registry_str);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DllOverride {
+    /// The library name without the `.dll` extension, e.g. `d3d11`.
+    pub dll: String,
+    pub mode: DllOverrideMode,
+}
+
+serialize_fields!(DllOverride { dll, mode });
+
+impl DllOverride {
+    pub fn new(dll: impl Into<String>, mode: DllOverrideMode) -> Self {
+        Self {
+            dll: dll.into(),
+            mode,
+        }
+    }
+}
+
+/// Renders overrides in the `WINEDLLOVERRIDES` syntax, where later entries for
+/// the same library take precedence.
+pub(crate) fn to_env_value<'a>(
+    dll_overrides: impl IntoIterator<Item = &'a DllOverride>,
+) -> String {
+    dll_overrides
+        .into_iter()
+        .map(|dll_override| {
+            format!("{}={}", dll_override.dll, dll_override.mode.as_env_str())
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+pub(crate) fn dll_overrides_key() -> RegistryKey {
+    RegistryKey::current_user("Software\\Wine\\DllOverrides")
+}
+
+impl Prefix {
+    /// Persists `dll_override` in the prefix-wide `DllOverrides` key.
+    pub fn set_dll_override(
+        &self,
+        dll_override: &DllOverride,
+    ) -> io::Result<()> {
+        self.set_registry_value(
+            &dll_overrides_key(),
+            Some(&dll_override.dll),
+            dll_override.mode.as_registry_str(),
+        )
+    }
+
+    pub fn remove_dll_override(&self, dll: &str) -> io::Result<()> {
+        self.delete_registry_value(&dll_overrides_key(), Some(dll))
+    }
+
+    /// Persists `dll_override` for programs whose executable is named `exe`
+    /// only, taking precedence over the prefix-wide overrides.
+    pub fn set_app_dll_override(
+        &self,
+        exe: &str,
+        dll_override: &DllOverride,
+    ) -> io::Result<()> {
+        self.set_registry_value(
+            &app_defaults_key(exe).join("DllOverrides"),
+            Some(&dll_override.dll),
+            dll_override.mode.as_registry_str(),
+        )
+    }
+
+    pub fn remove_app_dll_override(
+        &self,
+        exe: &str,
+        dll: &str,
+    ) -> io::Result<()> {
+        self.delete_registry_value(
+            &app_defaults_key(exe).join("DllOverrides"),
+            Some(dll),
+        )
+    }
+
+    /// The overrides set with [`Prefix::set_app_dll_override`] for `exe`,
+    /// skipping values Wine would not understand either.
+    pub fn app_dll_overrides(&self, exe: &str) -> io::Result<Vec<DllOverride>> {
+        let key = app_defaults_key(exe).join("DllOverrides");
+        if !self.registry_key_exists(&key)? {
+            return Ok(Vec::new());
+        }
+        Ok(key
+            .values(self)?
+            .filter_map(|(dll, value)| match value {
+                RegistryValue::String(mode) => Some(DllOverride::new(
+                    dll?,
+                    DllOverrideMode::from_name(&mode)?,
+                )),
+                _ => None,
+            })
+            .collect())
+    }
+}
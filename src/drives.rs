@@ -0,0 +1,288 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    fs, io,
+    os::unix,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    Prefix,
+    registry::{RegistryKey, RegistryValue},
+    serialize::{serialize_as_str, serialize_fields},
+};
+
+/// What Windows programs are told a drive is, which some copy-protected
+/// installers check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DriveType {
+    HardDisk,
+    CdRom,
+    Network,
+    Floppy,
+}
+
+impl DriveType {
+    /// The spelling used by the `Drives` registry key.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::HardDisk => "hd",
+            Self::CdRom => "cdrom",
+            Self::Network => "network",
+            Self::Floppy => "floppy",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "hd" => Some(Self::HardDisk),
+            "cdrom" => Some(Self::CdRom),
+            "network" => Some(Self::Network),
+            "floppy" => Some(Self::Floppy),
+            _ => None,
+        }
+    }
+}
+
+serialize_as_str!(DriveType, as_str);
+
+/// A drive letter mapped in `dosdevices`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Drive {
+    /// Lowercase, e.g., `d`.
+    pub letter: char,
+    /// The host directory the drive leads to, as written in the symlink.
+    pub target: PathBuf,
+    /// `None` if Wine decides from the host filesystem.
+    pub drive_type: Option<DriveType>,
+}
+
+serialize_fields!(Drive {
+    letter,
+    target,
+    drive_type
+});
+
+/// A host directory mapped as a CD-ROM by [`Prefix::mount_cdrom`], which is
+/// unmapped when dropped.
+#[derive(Debug)]
+pub struct MountedCdRom<'a> {
+    prefix: &'a Prefix,
+    letter: char,
+    /// The `.windows-label` written into the directory, removed on unmount.
+    label_file: Option<PathBuf>,
+}
+
+impl MountedCdRom<'_> {
+    pub fn letter(&self) -> char {
+        self.letter
+    }
+
+    /// The Windows path of the root of the drive, e.g., `D:\`.
+    pub fn root(&self) -> String {
+        format!("{}:\\", self.letter.to_ascii_uppercase())
+    }
+
+    /// Unmaps the drive, reporting the errors that dropping it ignores.
+    pub fn unmount(mut self) -> io::Result<()> {
+        self.unmount_inner()
+    }
+
+    fn unmount_inner(&mut self) -> io::Result<()> {
+        if let Some(label_file) = self.label_file.take() {
+            fs::remove_file(label_file)?;
+        }
+        self.prefix.unmap_drive(self.letter)
+    }
+}
+
+impl Drop for MountedCdRom<'_> {
+    fn drop(&mut self) {
+        let _ = self.unmount_inner();
+    }
+}
+
+fn drives_key() -> RegistryKey {
+    RegistryKey::local_machine("Software\\Wine\\Drives")
+}
+
+/// Lowercases `letter`, failing unless it is one.
+fn drive_letter(letter: char) -> io::Result<char> {
+    if letter.is_ascii_alphabetic() {
+        Ok(letter.to_ascii_lowercase())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("`{letter}` is not a drive letter"),
+        ))
+    }
+}
+
+impl Prefix {
+    fn drive_link(&self, letter: char) -> io::Result<PathBuf> {
+        Ok(self
+            .path
+            .join("dosdevices")
+            .join(format!("{}:", drive_letter(letter)?)))
+    }
+
+    /// The drives of the prefix, in order of their letters.
+    pub fn drives(&self) -> io::Result<Vec<Drive>> {
+        let types: Vec<_> = if self.registry_key_exists(&drives_key())? {
+            drives_key().values(self)?.collect()
+        } else {
+            Vec::new()
+        };
+        let mut drives = Vec::new();
+        for entry in fs::read_dir(self.path.join("dosdevices"))? {
+            let entry = entry?;
+            let name = entry.file_name();
+            // Entries like `d::` name the device backing a drive instead.
+            let Some(letter @ [_, b':']) = name.to_str().map(str::as_bytes)
+            else {
+                continue;
+            };
+            let Ok(target) = fs::read_link(entry.path()) else {
+                continue;
+            };
+            let drive_type = types.iter().find_map(|(name, value)| {
+                match (name.as_deref(), value) {
+                    (Some(name), RegistryValue::String(drive_type))
+                        if name.as_bytes().eq_ignore_ascii_case(letter) =>
+                    {
+                        DriveType::from_name(drive_type)
+                    }
+                    _ => None,
+                }
+            });
+            drives.push(Drive {
+                letter: char::from(letter[0]).to_ascii_lowercase(),
+                target,
+                drive_type,
+            });
+        }
+        drives.sort_by_key(|drive| drive.letter);
+        Ok(drives)
+    }
+
+    /// Maps `letter` to the host directory `target`, replacing any existing
+    /// mapping of that letter.
+    pub fn map_drive(
+        &self,
+        letter: char,
+        target: impl AsRef<Path>,
+    ) -> io::Result<()> {
+        let _serialized = self.serialize_mutation()?;
+        let link = self.drive_link(letter)?;
+        if link.is_symlink() {
+            fs::remove_file(&link)?;
+        }
+        unix::fs::symlink(target, link)
+    }
+
+    /// Removes the mapping of `letter` along with its type, doing nothing if
+    /// it is not mapped.
+    pub fn unmap_drive(&self, letter: char) -> io::Result<()> {
+        let _serialized = self.serialize_mutation()?;
+        let link = self.drive_link(letter)?;
+        if link.is_symlink() {
+            fs::remove_file(&link)?;
+        }
+        let name = format!("{}:", drive_letter(letter)?);
+        if self.registry_value(&drives_key(), Some(&name))?.is_some() {
+            self.delete_registry_value(&drives_key(), Some(&name))?;
+        }
+        Ok(())
+    }
+
+    pub fn set_drive_type(
+        &self,
+        letter: char,
+        drive_type: DriveType,
+    ) -> io::Result<()> {
+        self.set_registry_value(
+            &drives_key(),
+            Some(&format!("{}:", drive_letter(letter)?)),
+            drive_type.as_str(),
+        )
+    }
+
+    pub fn drive_type(&self, letter: char) -> io::Result<Option<DriveType>> {
+        let name = format!("{}:", drive_letter(letter)?);
+        match self.registry_value(&drives_key(), Some(&name))? {
+            Some(RegistryValue::String(drive_type)) => {
+                Ok(DriveType::from_name(&drive_type))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Sets the volume label of `letter` through the `.windows-label` file
+    /// Wine reads at the root of the drive, like `winecfg` does.
+    pub fn set_drive_label(&self, letter: char, label: &str) -> io::Result<()> {
+        fs::write(
+            self.drive_link(letter)?.join(".windows-label"),
+            format!("{label}\n"),
+        )
+    }
+
+    /// Sets the volume serial number of `letter` through the
+    /// `.windows-serial` file Wine reads at the root of the drive.
+    pub fn set_drive_serial(
+        &self,
+        letter: char,
+        serial: u32,
+    ) -> io::Result<()> {
+        fs::write(
+            self.drive_link(letter)?.join(".windows-serial"),
+            format!("{serial:x}\n"),
+        )
+    }
+
+    /// Maps `dir`, e.g., an extracted ISO image, to the first free letter
+    /// from `D:` as a CD-ROM labeled `label`, for installers that insist on
+    /// disc media.
+    pub fn mount_cdrom(
+        &self,
+        dir: impl AsRef<Path>,
+        label: &str,
+    ) -> io::Result<MountedCdRom<'_>> {
+        let dir = dir.as_ref();
+        if !dir.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not a directory", dir.display()),
+            ));
+        }
+        let taken = self.drives()?;
+        let letter = ('d'..='y')
+            .find(|letter| {
+                taken.iter().all(|drive| drive.letter != *letter)
+                    && !self.path.join(format!("dosdevices/{letter}:")).exists()
+            })
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::StorageFull,
+                    "every drive letter is taken",
+                )
+            })?;
+
+        self.map_drive(letter, dir)?;
+        let mut mounted = MountedCdRom {
+            prefix: self,
+            letter,
+            label_file: None,
+        };
+        self.set_drive_type(letter, DriveType::CdRom)?;
+        let label_file = dir.join(".windows-label");
+        if !label_file.exists() {
+            self.set_drive_label(letter, label)?;
+            mounted.label_file = Some(label_file);
+        }
+        Ok(mounted)
+    }
+}
@@ -0,0 +1,206 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Installing [DXVK](https://github.com/doitsujin/dxvk) into a prefix.
+//!
+//! This mirrors the upstream `setup_dxvk.sh`: Wine's own libraries are moved
+//! aside to `<dll>.dll.old` and restored on uninstallation.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    DllOverride, DllOverrideMode, Error, Prefix,
+    dll_overrides::dll_overrides_key,
+};
+
+/// Libraries shipped by DXVK that replace Wine's Direct3D implementation.
+pub const DLLS: &[&str] = &["d3d9", "d3d10core", "d3d11", "dxgi"];
+
+const VERSION_FILE: &str = "dxvk-version";
+
+/// An extracted DXVK release, containing `x64` and `x32` directories.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Release {
+    path: PathBuf,
+    version: Option<String>,
+}
+
+impl Release {
+    /// Infers the version from a directory named like the release tarballs,
+    /// e.g. `dxvk-2.3`.
+    pub fn at(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let version = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix("dxvk-"))
+            .map(str::to_owned);
+        Self { path, version }
+    }
+
+    pub fn with_version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+}
+
+/// Pairs each DXVK architecture directory with the system directory of the
+/// prefix that it belongs in.
+fn library_dirs(prefix: &Prefix) -> Vec<(&'static str, PathBuf)> {
+    let windows = prefix.drive_c().join("windows");
+    if prefix.is_64_bit() {
+        vec![
+            ("x64", windows.join("system32")),
+            ("x32", windows.join("syswow64")),
+        ]
+    } else {
+        vec![("x32", windows.join("system32"))]
+    }
+}
+
+pub fn install(prefix: &Prefix, release: &Release) -> io::Result<()> {
+    let _lock = prefix.lock()?;
+    for (arch, system_dir) in library_dirs(prefix) {
+        for dll in DLLS {
+            let file_name = format!("{dll}.dll");
+            let source = release.path.join(arch).join(&file_name);
+            if !source.is_file() {
+                return Err(Error::NotInstalled(format!(
+                    "DXVK release is missing {}",
+                    source.display()
+                ))
+                .into());
+            }
+            let destination = system_dir.join(&file_name);
+            let backup = system_dir.join(format!("{file_name}.old"));
+            if destination.exists() && !backup.exists() {
+                fs::rename(&destination, &backup)?;
+            }
+            fs::copy(&source, &destination)?;
+        }
+    }
+
+    for dll in DLLS {
+        prefix.set_dll_override(&DllOverride::new(
+            *dll,
+            DllOverrideMode::Native,
+        ))?;
+    }
+
+    fs::create_dir_all(prefix.metadata_dir())?;
+    fs::write(
+        prefix.metadata_dir().join(VERSION_FILE),
+        release.version().unwrap_or_default(),
+    )
+}
+
+/// Restores the libraries that [`install`] moved aside. A library that had
+/// none to move aside was copied there by [`install`] and is deleted, unless
+/// DXVK was not installed through it.
+pub fn uninstall(prefix: &Prefix) -> io::Result<()> {
+    let _lock = prefix.lock()?;
+    let was_installed = installed(prefix)?.is_some();
+    for dll in DLLS {
+        // `reg delete` fails on values that are already gone.
+        if prefix
+            .registry_value(&dll_overrides_key(), Some(dll))?
+            .is_some()
+        {
+            prefix.remove_dll_override(dll)?;
+        }
+    }
+
+    for (_, system_dir) in library_dirs(prefix) {
+        for dll in DLLS {
+            let file_name = format!("{dll}.dll");
+            let library = system_dir.join(&file_name);
+            let backup = system_dir.join(format!("{file_name}.old"));
+            if backup.exists() {
+                fs::rename(&backup, library)?;
+            } else if was_installed {
+                match fs::remove_file(library) {
+                    Err(error) if error.kind() != io::ErrorKind::NotFound => {
+                        return Err(error);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    match fs::remove_file(prefix.metadata_dir().join(VERSION_FILE)) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Installation {
+    /// `None` if DXVK was installed from a release of unknown version.
+    pub version: Option<String>,
+}
+
+/// Returns `None` if DXVK was not installed through [`install`].
+pub fn installed(prefix: &Prefix) -> io::Result<Option<Installation>> {
+    match fs::read_to_string(prefix.metadata_dir().join(VERSION_FILE)) {
+        Ok(version) => {
+            let version = version.trim();
+            Ok(Some(Installation {
+                version: (!version.is_empty()).then(|| version.to_owned()),
+            }))
+        }
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use crate::{PrefixBuilder, runner::RecordingRunner};
+
+    #[test]
+    fn uninstall_deletes_libraries_without_backups() {
+        let path = env::temp_dir()
+            .join(format!("wine-rs-dxvk-test-{}", process::id()));
+        let system32 = path.join("drive_c/windows/system32");
+        fs::create_dir_all(&system32).unwrap();
+        fs::create_dir_all(path.join(".wine-rs")).unwrap();
+        fs::write(path.join(".wine-rs").join(super::VERSION_FILE), "2.3")
+            .unwrap();
+        fs::write(system32.join("d3d11.dll"), "dxvk").unwrap();
+        fs::write(system32.join("dxgi.dll"), "dxvk").unwrap();
+        fs::write(system32.join("dxgi.dll.old"), "wine").unwrap();
+        let (prefix, runner) =
+            RecordingRunner::attach(PrefixBuilder::new(&path).build());
+
+        super::uninstall(&prefix).unwrap();
+        assert!(!system32.join("d3d11.dll").exists());
+        assert_eq!(
+            fs::read_to_string(system32.join("dxgi.dll")).unwrap(),
+            "wine"
+        );
+        assert!(!system32.join("dxgi.dll.old").exists());
+        // No override is set, so none is deleted.
+        assert!(
+            runner
+                .take()
+                .iter()
+                .all(|command| !command.args.iter().any(|arg| arg == "delete"))
+        );
+        assert!(super::installed(&prefix).unwrap().is_none());
+
+        fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{error, ffi::OsString, fmt, io, path::PathBuf, process::ExitStatus};
+
+use crate::{Prefix, VersionMismatch};
+
+/// The ways operations of this crate fail, carried inside the [`io::Error`]
+/// they return so that the error kind still applies. [`Error::from`]
+/// recovers the category, or gives [`Error::Io`] for plain I/O errors.
+#[derive(Debug)]
+pub enum Error {
+    /// There is no prefix at the path.
+    PrefixMissing(PathBuf),
+    /// A Wine binary, such as the loader or the wineserver, does not exist.
+    WineNotFound(PathBuf),
+    /// The wineserver could not be started.
+    ServerStartFailed(ExitStatus),
+    /// Registry data that could not be understood.
+    RegistryParse(String),
+    /// A registry key that an operation needs does not exist, which names
+    /// it.
+    RegistryKeyMissing(String),
+    /// A host tool or guest component that an operation needs is not
+    /// installed, which names it.
+    NotInstalled(String),
+    /// A program exited unsuccessfully.
+    ProcessCrashed {
+        program: OsString,
+        status: ExitStatus,
+        /// What it wrote to stderr.
+        stderr: String,
+    },
+    /// An operation did not finish in time, which names it.
+    Timeout(String),
+    Cancelled,
+    /// An operation that needs the wineserver to be stopped, which names it.
+    PrefixBusy(String),
+    /// The wineserver serving the prefix belongs to a different Wine build.
+    VersionMismatch(VersionMismatch),
+    /// Something the Wine build or the host cannot do, which says why.
+    Unsupported(String),
+    Io(io::Error),
+}
+
+impl Error {
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            Self::PrefixMissing(_)
+            | Self::WineNotFound(_)
+            | Self::RegistryKeyMissing(_)
+            | Self::NotInstalled(_) => io::ErrorKind::NotFound,
+            Self::ServerStartFailed(_) | Self::ProcessCrashed { .. } => {
+                io::ErrorKind::Other
+            }
+            Self::RegistryParse(_) => io::ErrorKind::InvalidData,
+            Self::Timeout(_) => io::ErrorKind::TimedOut,
+            Self::Cancelled => io::ErrorKind::Interrupted,
+            Self::PrefixBusy(_) | Self::VersionMismatch(_) => {
+                io::ErrorKind::ResourceBusy
+            }
+            Self::Unsupported(_) => io::ErrorKind::Unsupported,
+            Self::Io(error) => error.kind(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PrefixMissing(path) => {
+                write!(f, "there is no prefix at {}", path.display())
+            }
+            Self::WineNotFound(path) => {
+                write!(f, "`{}` does not exist", path.display())
+            }
+            Self::ServerStartFailed(status) => {
+                write!(f, "`wineserver` failed ({status})")
+            }
+            Self::RegistryParse(message) => f.write_str(message),
+            Self::RegistryKeyMissing(key) => {
+                write!(f, "registry key {key} does not exist")
+            }
+            Self::NotInstalled(what) => write!(f, "{what} is not installed"),
+            Self::ProcessCrashed {
+                program,
+                status,
+                stderr,
+            } => {
+                write!(f, "`{}` failed ({status})", program.display())?;
+                if !stderr.is_empty() {
+                    write!(f, ": {stderr}")?;
+                }
+                Ok(())
+            }
+            Self::Timeout(operation) => write!(f, "{operation} timed out"),
+            Self::Cancelled => f.write_str("the operation was cancelled"),
+            Self::PrefixBusy(action) => {
+                write!(f, "cannot {action} while the prefix is running")
+            }
+            Self::VersionMismatch(mismatch) => write!(
+                f,
+                "the prefix is served by another Wine build: {mismatch}"
+            ),
+            Self::Unsupported(message) => f.write_str(message),
+            Self::Io(error) => error.fmt(f),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::VersionMismatch(mismatch) => Some(mismatch),
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        if error.get_ref().is_some_and(|inner| inner.is::<Self>()) {
+            let inner = error.into_inner().expect("checked above");
+            *inner.downcast::<Self>().expect("checked above")
+        } else {
+            Self::Io(error)
+        }
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Io(error) => error,
+            error => io::Error::new(error.kind(), error),
+        }
+    }
+}
+
+impl Prefix {
+    /// Fails with [`Error::PrefixMissing`] unless the prefix directory
+    /// exists.
+    pub(crate) fn check_exists(&self) -> io::Result<()> {
+        if self.path.is_dir() {
+            Ok(())
+        } else {
+            Err(Error::PrefixMissing(self.path.clone()).into())
+        }
+    }
+}
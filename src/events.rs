@@ -0,0 +1,175 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    fmt, io,
+    path::PathBuf,
+    process::ExitStatus,
+    sync::{Arc, mpsc},
+};
+
+use crate::{Prefix, WineCommand};
+
+/// Something that happened to a prefix, reported to the handlers added with
+/// [`Prefix::with_event_handler`], e.g., to keep a GUI up to date.
+///
+/// With the `serde` feature, events serialize as maps whose `type` field is
+/// the variant name in snake case.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PrefixEvent {
+    /// A launch found the prefix uninitialized and is running
+    /// [`Prefix::initialize`] first, as
+    /// [`crate::PrefixConfig::auto_initialize`] asks, which takes a while.
+    /// [`PrefixEvent::PrefixCreated`] follows.
+    InitializingPrefix { prefix: PathBuf },
+    /// [`Prefix::initialize`] finished.
+    PrefixCreated { prefix: PathBuf },
+    /// A program was spawned in the background.
+    ProcessSpawned {
+        prefix: PathBuf,
+        program: String,
+        pid: u32,
+    },
+    /// A program that was run to completion exited. Spawned programs are
+    /// not followed.
+    ProcessExited {
+        prefix: PathBuf,
+        program: String,
+        /// `None` if the program was killed by a signal.
+        exit_code: Option<i32>,
+    },
+    LaunchFailed {
+        prefix: PathBuf,
+        program: String,
+        error: String,
+    },
+    /// A value or subkey of `key` was set or deleted, or `key` itself was.
+    RegistryModified { prefix: PathBuf, key: String },
+}
+
+impl Prefix {
+    /// Calls `handler` with every event on this prefix, from whichever thread
+    /// caused it.
+    pub fn with_event_handler(
+        mut self,
+        handler: impl Fn(&PrefixEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.hooks.events.push(Arc::new(handler));
+        self
+    }
+
+    /// Like [`Prefix::with_event_handler`], but sending the events through a
+    /// channel.
+    pub fn with_event_channel(self) -> (Self, mpsc::Receiver<PrefixEvent>) {
+        let (sender, receiver) = mpsc::channel();
+        let prefix = self.with_event_handler(move |event| {
+            let _ = sender.send(event.clone());
+        });
+        (prefix, receiver)
+    }
+
+    pub(crate) fn emit(&self, event: impl FnOnce() -> PrefixEvent) {
+        if self.hooks.events.is_empty() {
+            return;
+        }
+        let event = event();
+        for handler in &self.hooks.events {
+            handler(&event);
+        }
+    }
+}
+
+impl WineCommand<'_> {
+    fn program_name(&self) -> String {
+        self.program.to_string_lossy().into_owned()
+    }
+
+    pub(crate) fn emit_spawn(&self, result: Result<u32, &io::Error>) {
+        self.prefix.emit(|| match result {
+            Ok(pid) => PrefixEvent::ProcessSpawned {
+                prefix: self.prefix.path.clone(),
+                program: self.program_name(),
+                pid,
+            },
+            Err(error) => self.launch_failed(error),
+        });
+    }
+
+    pub(crate) fn emit_exit(&self, result: Result<ExitStatus, &io::Error>) {
+        self.prefix.emit(|| match result {
+            Ok(status) => PrefixEvent::ProcessExited {
+                prefix: self.prefix.path.clone(),
+                program: self.program_name(),
+                exit_code: status.code(),
+            },
+            Err(error) => self.launch_failed(error),
+        });
+    }
+
+    fn launch_failed(&self, error: &io::Error) -> PrefixEvent {
+        PrefixEvent::LaunchFailed {
+            prefix: self.prefix.path.clone(),
+            program: self.program_name(),
+            error: error.to_string(),
+        }
+    }
+}
+
+impl Prefix {
+    pub(crate) fn emit_registry_modified(&self, key: &impl fmt::Display) {
+        self.emit(|| PrefixEvent::RegistryModified {
+            prefix: self.path.clone(),
+            key: key.to_string(),
+        });
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for PrefixEvent {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        macro_rules! event {
+            ($type:literal, $($field:ident),*) => {{
+                let mut state = serializer.serialize_struct(
+                    "PrefixEvent",
+                    1 + [$(stringify!($field)),*].len(),
+                )?;
+                state.serialize_field("type", $type)?;
+                $(state.serialize_field(stringify!($field), $field)?;)*
+                state.end()
+            }};
+        }
+
+        match self {
+            Self::InitializingPrefix { prefix } => {
+                event!("initializing_prefix", prefix)
+            }
+            Self::PrefixCreated { prefix } => event!("prefix_created", prefix),
+            Self::ProcessSpawned {
+                prefix,
+                program,
+                pid,
+            } => event!("process_spawned", prefix, program, pid),
+            Self::ProcessExited {
+                prefix,
+                program,
+                exit_code,
+            } => event!("process_exited", prefix, program, exit_code),
+            Self::LaunchFailed {
+                prefix,
+                program,
+                error,
+            } => event!("launch_failed", prefix, program, error),
+            Self::RegistryModified { prefix, key } => {
+                event!("registry_modified", prefix, key)
+            }
+        }
+    }
+}
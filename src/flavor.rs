@@ -0,0 +1,162 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{io, path::Path};
+
+use crate::{
+    CommandRunner, Defaults, ResolvedCommand,
+    installation::parse_version_output,
+};
+
+/// The distribution of Wine a prefix runs on, which determines the spelling of
+/// several tuning variables.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WineFlavor {
+    #[default]
+    Upstream,
+    Staging,
+    CrossOver,
+    GamePortingToolkit,
+    Proton,
+}
+
+impl WineFlavor {
+    /// Guesses the flavor of the installation rooted at `install_path` (the
+    /// directory containing `bin/`) from its layout alone.
+    pub fn detect_from_layout(install_path: &Path) -> Self {
+        let path = install_path.to_string_lossy();
+        if path.contains("game-porting-toolkit")
+            || install_path
+                .join("lib/external/D3DMetal.framework")
+                .exists()
+        {
+            Self::GamePortingToolkit
+        } else if path.contains("CrossOver")
+            || install_path.join("share/crossover").exists()
+        {
+            Self::CrossOver
+        } else if install_path
+            .parent()
+            .is_some_and(|parent| parent.join("proton").is_file())
+        {
+            Self::Proton
+        } else {
+            Self::Upstream
+        }
+    }
+
+    /// Runs `wine --version` through `runner` and classifies its output,
+    /// falling back to the layout for builds that do not identify themselves.
+    pub fn detect(runner: &dyn CommandRunner, wine: &Path) -> io::Result<Self> {
+        let mut command = ResolvedCommand::new(wine, ".");
+        Defaults::with(|defaults| defaults.environment.apply(&mut command));
+        command.args.push("--version".into());
+        let version = parse_version_output(runner.output(&command)?);
+        Ok(version
+            .as_deref()
+            .and_then(Self::from_version_string)
+            .unwrap_or_else(|| {
+                wine.parent()
+                    .and_then(Path::parent)
+                    .map(Self::detect_from_layout)
+                    .unwrap_or_default()
+            }))
+    }
+
+    /// Classifies a `wine --version` line such as `wine-9.0 (Staging)`.
+    pub fn from_version_string(version: &str) -> Option<Self> {
+        if version.contains("Staging") {
+            Some(Self::Staging)
+        } else if version.contains("CrossOver") {
+            Some(Self::CrossOver)
+        } else if version.contains("Proton") {
+            Some(Self::Proton)
+        } else {
+            None
+        }
+    }
+
+    pub fn esync_var(&self) -> &'static str {
+        match self {
+            Self::CrossOver => "ESYNC",
+            _ => "WINEESYNC",
+        }
+    }
+
+    /// Flavors that do not implement msync get the `MSYNC` spelling, which
+    /// they ignore, so enabling it still reaches builds patched out of tree.
+    pub fn msync_var(&self) -> &'static str {
+        match self {
+            Self::GamePortingToolkit => "WINEMSYNC",
+            _ => "MSYNC",
+        }
+    }
+
+    /// `None` if the flavor does not implement fsync.
+    pub fn fsync_var(&self) -> Option<&'static str> {
+        match self {
+            Self::Upstream | Self::Staging | Self::Proton => Some("WINEFSYNC"),
+            _ => None,
+        }
+    }
+
+    /// `None` if the flavor lacks the staging patch that honors it.
+    pub fn large_address_aware_var(&self) -> Option<&'static str> {
+        match self {
+            Self::Upstream => None,
+            _ => Some("WINE_LARGE_ADDRESS_AWARE"),
+        }
+    }
+
+    /// `None` unless the flavor is Proton, which introduced this toggle.
+    pub fn simulate_writecopy_var(&self) -> Option<&'static str> {
+        match self {
+            Self::Proton => Some("WINE_SIMULATE_WRITECOPY"),
+            _ => None,
+        }
+    }
+
+    /// `None` if the flavor lacks the staging shared memory patch.
+    pub fn staging_shared_memory_var(&self) -> Option<&'static str> {
+        match self {
+            Self::Staging | Self::Proton => Some("STAGING_SHARED_MEMORY"),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use crate::runner::RecordingRunner;
+
+    use super::WineFlavor;
+
+    #[test]
+    fn detect_asks_the_runner_and_falls_back_to_the_layout() {
+        let root = env::temp_dir()
+            .join(format!("wine-rs-flavor-test-{}", process::id()));
+        fs::create_dir_all(root.join("share/crossover")).unwrap();
+        let wine = root.join("bin/wine");
+        let runner = RecordingRunner::default();
+
+        let flavor = WineFlavor::detect(&runner, &wine).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(flavor, WineFlavor::CrossOver);
+        let commands = runner.take();
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].program, wine);
+        assert_eq!(commands[0].args, ["--version"]);
+    }
+
+    #[test]
+    fn every_flavor_spells_msync() {
+        assert_eq!(WineFlavor::Upstream.msync_var(), "MSYNC");
+        assert_eq!(WineFlavor::Proton.msync_var(), "MSYNC");
+        assert_eq!(WineFlavor::GamePortingToolkit.msync_var(), "WINEMSYNC");
+    }
+}
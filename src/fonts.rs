@@ -0,0 +1,184 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{Prefix, registry::RegistryKey, serialize::serialize_fields};
+
+const FONT_EXTENSIONS: &[&str] = &["ttf", "ttc", "otf"];
+
+/// The keys Windows NT and Windows 9x programs look up fonts under.
+const FONT_KEYS: &[&str] = &[
+    "Software\\Microsoft\\Windows NT\\CurrentVersion\\Fonts",
+    "Software\\Microsoft\\Windows\\CurrentVersion\\Fonts",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InstalledFont {
+    pub path: PathBuf,
+    /// The full name recorded in the font, if it could be read.
+    pub name: Option<String>,
+}
+
+serialize_fields!(InstalledFont { path, name });
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes(bytes.try_into().ok()?))
+}
+
+/// Reads the full name (name ID 4) from the `name` table of a TrueType or
+/// OpenType font, using the first font of a collection.
+fn font_full_name(data: &[u8]) -> Option<String> {
+    let mut font_offset = 0;
+    if data.get(..4)? == b"ttcf" {
+        font_offset = read_u32(data, 12)? as usize;
+    }
+    let table_count = read_u16(data, font_offset + 4)? as usize;
+    let name_table = (0..table_count).find_map(|index| {
+        let record = font_offset + 12 + index * 16;
+        (data.get(record..record + 4)? == b"name")
+            .then(|| read_u32(data, record + 8))
+            .flatten()
+    })? as usize;
+
+    let record_count = read_u16(data, name_table + 2)? as usize;
+    let strings = name_table + read_u16(data, name_table + 4)? as usize;
+    let mut fallback = None;
+    for index in 0..record_count {
+        let record = name_table + 6 + index * 12;
+        let platform = read_u16(data, record)?;
+        let name_id = read_u16(data, record + 6)?;
+        if name_id != 4 {
+            continue;
+        }
+        let length = read_u16(data, record + 8)? as usize;
+        let start = strings + read_u16(data, record + 10)? as usize;
+        let bytes = data.get(start..start + length)?;
+        match platform {
+            // Windows and Unicode names are UTF-16BE.
+            0 | 3 => {
+                let units = bytes
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_be_bytes([pair[0], pair[1]]));
+                return char::decode_utf16(units)
+                    .collect::<Result<String, _>>()
+                    .ok();
+            }
+            1 if fallback.is_none() => {
+                fallback =
+                    Some(bytes.iter().map(|&byte| byte as char).collect());
+            }
+            _ => {}
+        }
+    }
+    fallback
+}
+
+fn is_font_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            FONT_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+        })
+}
+
+impl Prefix {
+    pub fn fonts_dir(&self) -> PathBuf {
+        self.windows_dir().join("Fonts")
+    }
+
+    /// Copies a TrueType or OpenType font into the prefix and registers it so
+    /// programs that enumerate fonts through the registry find it.
+    pub fn install_font(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> io::Result<InstalledFont> {
+        let path = path.as_ref();
+        if !is_font_file(path) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not a TTF, TTC, or OTF font", path.display()),
+            ));
+        }
+        let file_name = path.file_name().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "font path has no name")
+        })?;
+        let file_name_str = file_name.to_str().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "font file name is not valid UTF-8",
+            )
+        })?;
+
+        let data = fs::read(path)?;
+        let name = font_full_name(&data);
+
+        let _serialized = self.serialize_mutation()?;
+
+        let fonts_dir = self.fonts_dir();
+        fs::create_dir_all(&fonts_dir)?;
+        let installed_path = fonts_dir.join(file_name);
+        fs::write(&installed_path, &data)?;
+
+        let kind = if data.starts_with(b"OTTO") {
+            "OpenType"
+        } else {
+            "TrueType"
+        };
+        let display_name = name.as_deref().unwrap_or_else(|| {
+            Path::new(file_name_str)
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or(file_name_str)
+        });
+        let value_name = format!("{display_name} ({kind})");
+        for key in FONT_KEYS {
+            self.set_registry_value(
+                &RegistryKey::local_machine(*key),
+                Some(&value_name),
+                file_name_str,
+            )?;
+        }
+
+        Ok(InstalledFont {
+            path: installed_path,
+            name,
+        })
+    }
+
+    /// Lists the font files in `C:\windows\Fonts`, which includes the fonts
+    /// Wine itself ships with.
+    pub fn installed_fonts(&self) -> io::Result<Vec<InstalledFont>> {
+        let entries = match fs::read_dir(self.fonts_dir()) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok(Vec::new());
+            }
+            Err(error) => return Err(error),
+        };
+        let mut fonts = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if !is_font_file(&path) {
+                continue;
+            }
+            let name =
+                fs::read(&path).ok().and_then(|data| font_full_name(&data));
+            fonts.push(InstalledFont { path, name });
+        }
+        fonts.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(fonts)
+    }
+}
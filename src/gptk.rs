@@ -0,0 +1,63 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Specifics of Apple's Game Porting Toolkit (GPTk) Wine build.
+
+use std::path::{Path, PathBuf};
+
+use crate::{DllOverride, DllOverrideMode};
+
+/// Where the Homebrew formula installs GPTk.
+pub const DEFAULT_INSTALL_PATH: &str = "/usr/local/opt/game-porting-toolkit";
+
+/// Libraries that D3DMetal provides in place of Wine's Direct3D.
+const D3DMETAL_DLLS: &[&str] = &["dxgi", "d3d9", "d3d10core", "d3d11", "d3d12"];
+
+/// Returns the GPTk installation at [`DEFAULT_INSTALL_PATH`], if there is one.
+pub fn detect() -> Option<PathBuf> {
+    let path = Path::new(DEFAULT_INSTALL_PATH);
+    path.join("bin/wine64")
+        .is_file()
+        .then(|| path.to_path_buf())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GptkConfig {
+    /// Translate Direct3D through D3DMetal. When disabled, Wine's builtin
+    /// Direct3D implementation is used instead.
+    pub d3dmetal: bool,
+    /// Show the Metal performance HUD.
+    pub metal_hud: bool,
+}
+
+impl Default for GptkConfig {
+    fn default() -> Self {
+        Self {
+            d3dmetal: true,
+            metal_hud: false,
+        }
+    }
+}
+
+impl GptkConfig {
+    pub(crate) fn env(&self) -> Vec<(&'static str, &'static str)> {
+        let mut env = Vec::new();
+        if self.metal_hud {
+            env.push(("MTL_HUD_ENABLED", "1"));
+        }
+        env
+    }
+
+    pub(crate) fn dll_overrides(&self) -> Vec<DllOverride> {
+        if self.d3dmetal {
+            return Vec::new();
+        }
+        D3DMETAL_DLLS
+            .iter()
+            .map(|dll| DllOverride::new(*dll, DllOverrideMode::Builtin))
+            .collect()
+    }
+}
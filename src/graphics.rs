@@ -0,0 +1,111 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    ffi::{OsStr, OsString},
+    path::PathBuf,
+};
+
+use crate::WineCommand;
+
+/// An element of the DXVK overlay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DxvkHudElement {
+    Fps,
+    FrameTimes,
+    /// The GPU and driver.
+    DeviceInfo,
+    Memory,
+    GpuLoad,
+    Version,
+    /// The Direct3D feature level the program uses.
+    Api,
+    /// Every element.
+    Full,
+}
+
+impl DxvkHudElement {
+    /// The spelling used by `DXVK_HUD`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fps => "fps",
+            Self::FrameTimes => "frametimes",
+            Self::DeviceInfo => "devinfo",
+            Self::Memory => "memory",
+            Self::GpuLoad => "gpuload",
+            Self::Version => "version",
+            Self::Api => "api",
+            Self::Full => "full",
+        }
+    }
+}
+
+/// Which GPU renders a launch and which overlays it shows, set through the
+/// variables that the graphics drivers and translation layers read.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct GraphicsEnvironment {
+    /// Offloads rendering to another GPU through Mesa's `DRI_PRIME`, e.g.,
+    /// `1` or a PCI tag like `pci-0000_01_00_0`.
+    pub dri_prime: Option<String>,
+    /// Offloads rendering to an NVIDIA GPU with the proprietary driver.
+    pub nvidia_prime_offload: bool,
+    /// Restricts the Vulkan loader to these driver manifests, e.g.,
+    /// `/usr/share/vulkan/icd.d/radeon_icd.x86_64.json`.
+    pub vulkan_icds: Vec<PathBuf>,
+    pub dxvk_hud: Vec<DxvkHudElement>,
+    /// Settings of MoltenVK on macOS, named without their `MVK_CONFIG_`
+    /// prefix, e.g., `("RESUME_LOST_DEVICE", "1")`.
+    pub moltenvk: Vec<(String, String)>,
+}
+
+impl GraphicsEnvironment {
+    pub fn env(&self) -> Vec<(OsString, OsString)> {
+        let mut env: Vec<(OsString, OsString)> = Vec::new();
+        if let Some(dri_prime) = &self.dri_prime {
+            env.push(("DRI_PRIME".into(), dri_prime.into()));
+        }
+        if self.nvidia_prime_offload {
+            env.extend(
+                [
+                    ("__NV_PRIME_RENDER_OFFLOAD", "1"),
+                    ("__GLX_VENDOR_LIBRARY_NAME", "nvidia"),
+                    ("__VK_LAYER_NV_optimus", "NVIDIA_only"),
+                ]
+                .map(|(name, value)| (name.into(), value.into())),
+            );
+        }
+        if !self.vulkan_icds.is_empty() {
+            let icds = self
+                .vulkan_icds
+                .iter()
+                .map(|icd| icd.as_os_str())
+                .collect::<Vec<_>>()
+                .join(OsStr::new(":"));
+            // Vulkan loaders from 1.3.207 read the new name.
+            env.push(("VK_DRIVER_FILES".into(), icds.clone()));
+            env.push(("VK_ICD_FILENAMES".into(), icds));
+        }
+        if !self.dxvk_hud.is_empty() {
+            let hud = self
+                .dxvk_hud
+                .iter()
+                .map(DxvkHudElement::as_str)
+                .collect::<Vec<_>>()
+                .join(",");
+            env.push(("DXVK_HUD".into(), hud.into()));
+        }
+        for (name, value) in &self.moltenvk {
+            env.push((format!("MVK_CONFIG_{name}").into(), value.into()));
+        }
+        env
+    }
+}
+
+impl WineCommand<'_> {
+    pub fn graphics(&mut self, graphics: &GraphicsEnvironment) -> &mut Self {
+        self.envs(graphics.env())
+    }
+}
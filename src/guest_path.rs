@@ -0,0 +1,134 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::Prefix;
+
+fn escape_error(windows_path: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        format!("`{windows_path}` leads outside the prefix"),
+    )
+}
+
+/// Finds the entry of `dir` named `name` regardless of case, like Wine does,
+/// preferring an exact match.
+fn find_entry(dir: &Path, name: &str) -> PathBuf {
+    let exact = dir.join(name);
+    if exact.exists() || exact.is_symlink() {
+        return exact;
+    }
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.file_name())
+        .find(|entry| {
+            entry
+                .to_str()
+                .is_some_and(|entry| entry.eq_ignore_ascii_case(name))
+        })
+        .map_or(exact, |entry| dir.join(entry))
+}
+
+/// The deepest ancestor of `path` that exists, resolved through symlinks.
+fn canonical_ancestor(path: &Path) -> io::Result<PathBuf> {
+    let mut ancestor = path;
+    loop {
+        match fs::canonicalize(ancestor) {
+            Ok(canonical) => return Ok(canonical),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                match ancestor.parent() {
+                    Some(parent) => ancestor = parent,
+                    None => return Err(error),
+                }
+            }
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+impl Prefix {
+    /// Maps an absolute Windows path such as `C:\Program Files\App\app.exe`
+    /// to where it lives on the host, matching names case-insensitively like
+    /// Wine.
+    ///
+    /// Fails with [`io::ErrorKind::PermissionDenied`] if the path would leave
+    /// the prefix, whether through `..`, a drive mapped to a host directory,
+    /// or a symlink such as the user folders Wine links into `$HOME`. The file
+    /// itself need not exist.
+    pub fn resolve_guest_path(
+        &self,
+        windows_path: &str,
+    ) -> io::Result<PathBuf> {
+        let path = windows_path.strip_prefix(r"\\?\").unwrap_or(windows_path);
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("`{windows_path}` is not an absolute Windows path"),
+            )
+        };
+        let mut chars = path.chars();
+        let (Some(drive), Some(':')) = (chars.next(), chars.next()) else {
+            return Err(invalid());
+        };
+        if !drive.is_ascii_alphabetic() {
+            return Err(invalid());
+        }
+        let rest = chars.as_str();
+        if !rest.is_empty() && !rest.starts_with(['\\', '/']) {
+            return Err(invalid());
+        }
+
+        let prefix_root = fs::canonicalize(&self.path)?;
+        let drive_root = fs::canonicalize(
+            self.path
+                .join("dosdevices")
+                .join(format!("{}:", drive.to_ascii_lowercase())),
+        )
+        .map_err(|error| {
+            if error.kind() == io::ErrorKind::NotFound {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("drive {drive}: is not mapped in this prefix"),
+                )
+            } else {
+                error
+            }
+        })?;
+        if !drive_root.starts_with(&prefix_root) {
+            return Err(escape_error(windows_path));
+        }
+
+        let mut components = Vec::new();
+        for component in rest.split(['\\', '/']) {
+            match component {
+                "" | "." => {}
+                ".." => {
+                    if components.pop().is_none() {
+                        return Err(escape_error(windows_path));
+                    }
+                }
+                // Alternate data streams and device names have no host file.
+                component if component.contains(':') => return Err(invalid()),
+                component => components.push(component),
+            }
+        }
+
+        let mut host_path = drive_root;
+        for component in components {
+            host_path = find_entry(&host_path, component);
+        }
+        if !canonical_ancestor(&host_path)?.starts_with(&prefix_root) {
+            return Err(escape_error(windows_path));
+        }
+        Ok(host_path)
+    }
+}
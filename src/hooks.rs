@@ -0,0 +1,173 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    io,
+    process::ExitStatus,
+    sync::Arc,
+};
+
+use crate::{
+    CancelHandle, Prefix, PrefixEvent, Progress, ResolvedCommand, WineCommand,
+};
+
+/// Called right before a launch with the command about to run, which it may
+/// still change, e.g., to add a variable.
+pub type PreLaunchHook = dyn Fn(&mut ResolvedCommand) + Send + Sync;
+
+/// Called once a launch has exited or failed to start.
+pub type PostExitHook =
+    dyn Fn(&ResolvedCommand, Result<ExitStatus, &io::Error>) + Send + Sync;
+
+/// The hooks of a prefix or command, compared by identity so that
+/// [`Prefix`] keeps its derives.
+#[derive(Default, Clone)]
+pub(crate) struct Hooks {
+    pre_launch: Vec<Arc<PreLaunchHook>>,
+    post_exit: Vec<Arc<PostExitHook>>,
+    pub(crate) events: Vec<Arc<EventHandler>>,
+    pub(crate) progress: Vec<Arc<dyn Progress>>,
+    pub(crate) cancel: Option<CancelHandle>,
+}
+
+pub(crate) type EventHandler = dyn Fn(&PrefixEvent) + Send + Sync;
+
+impl Hooks {
+    pub(crate) fn extend(&mut self, other: &Hooks) {
+        self.pre_launch.extend(other.pre_launch.iter().cloned());
+        self.post_exit.extend(other.post_exit.iter().cloned());
+        self.events.extend(other.events.iter().cloned());
+        self.progress.extend(other.progress.iter().cloned());
+        if self.cancel.is_none() {
+            self.cancel.clone_from(&other.cancel);
+        }
+    }
+
+    pub(crate) fn pre_launch(&self, command: &mut ResolvedCommand) {
+        for hook in &self.pre_launch {
+            hook(command);
+        }
+    }
+
+    pub(crate) fn post_exit(
+        &self,
+        command: &ResolvedCommand,
+        result: Result<ExitStatus, &io::Error>,
+    ) {
+        for hook in &self.post_exit {
+            hook(command, result);
+        }
+    }
+
+    fn pointers(&self) -> impl Iterator<Item = *const ()> {
+        let pre_launch =
+            self.pre_launch.iter().map(|hook| Arc::as_ptr(hook).cast());
+        let post_exit =
+            self.post_exit.iter().map(|hook| Arc::as_ptr(hook).cast());
+        let events = self
+            .events
+            .iter()
+            .map(|handler| Arc::as_ptr(handler).cast());
+        let progress = self
+            .progress
+            .iter()
+            .map(|progress| Arc::as_ptr(progress).cast());
+        let cancel = self.cancel.iter().map(CancelHandle::as_ptr);
+        pre_launch
+            .chain(post_exit)
+            .chain(events)
+            .chain(progress)
+            .chain(cancel)
+    }
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hooks")
+            .field("pre_launch", &self.pre_launch.len())
+            .field("post_exit", &self.post_exit.len())
+            .field("events", &self.events.len())
+            .field("progress", &self.progress.len())
+            .field("cancel", &self.cancel.is_some())
+            .finish()
+    }
+}
+
+impl PartialEq for Hooks {
+    fn eq(&self, other: &Self) -> bool {
+        self.pre_launch.len() == other.pre_launch.len()
+            && self.post_exit.len() == other.post_exit.len()
+            && self.pointers().eq(other.pointers())
+    }
+}
+
+impl Eq for Hooks {}
+
+impl Hash for Hooks {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for pointer in self.pointers() {
+            pointer.hash(state);
+        }
+    }
+}
+
+impl Prefix {
+    /// Runs `hook` before every launch of a program in this prefix, after the
+    /// hooks of the command.
+    pub fn with_pre_launch_hook(
+        mut self,
+        hook: impl Fn(&mut ResolvedCommand) + Send + Sync + 'static,
+    ) -> Self {
+        self.hooks.pre_launch.push(Arc::new(hook));
+        self
+    }
+
+    /// Runs `hook` after every launch of a program in this prefix that runs
+    /// to completion, i.e., except for spawned ones, and after every launch
+    /// that fails to start.
+    pub fn with_post_exit_hook(
+        mut self,
+        hook: impl Fn(&ResolvedCommand, Result<ExitStatus, &io::Error>)
+        + Send
+        + Sync
+        + 'static,
+    ) -> Self {
+        self.hooks.post_exit.push(Arc::new(hook));
+        self
+    }
+}
+
+impl WineCommand<'_> {
+    /// Like [`Prefix::with_pre_launch_hook`], for this command only.
+    pub fn pre_launch_hook(
+        &mut self,
+        hook: impl Fn(&mut ResolvedCommand) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.hooks.pre_launch.push(Arc::new(hook));
+        self
+    }
+
+    /// Like [`Prefix::with_post_exit_hook`], for this command only.
+    pub fn post_exit_hook(
+        &mut self,
+        hook: impl Fn(&ResolvedCommand, Result<ExitStatus, &io::Error>)
+        + Send
+        + Sync
+        + 'static,
+    ) -> &mut Self {
+        self.hooks.post_exit.push(Arc::new(hook));
+        self
+    }
+
+    /// The hooks of the command followed by those of the prefix.
+    pub(crate) fn all_hooks(&self) -> Hooks {
+        let mut hooks = self.hooks.clone();
+        hooks.extend(&self.prefix.hooks);
+        hooks
+    }
+}
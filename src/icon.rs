@@ -0,0 +1,452 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Extracting the icon of a Windows executable or DLL, e.g., for launcher
+//! UIs, as PNG or as the ICNS of macOS application bundles.
+//!
+//! The icon is the first icon group in the resources, which is the one
+//! Explorer shows. Its images are stored either as PNG, which is passed
+//! through, or as device-independent bitmaps, which are converted.
+
+use std::{fs, io, path::Path};
+
+use crate::pe::{self, Resource, ResourceId};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// The ICNS element types that hold PNG data, by the size of their images.
+const ICNS_TYPES: &[(u32, &[u8; 4])] = &[
+    (16, b"icp4"),
+    (32, b"icp5"),
+    (64, b"icp6"),
+    (128, b"ic07"),
+    (256, b"ic08"),
+    (512, b"ic09"),
+    (1024, b"ic10"),
+];
+
+/// One of the images of an icon, in the size and color depth Windows picks
+/// it for.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IconImage {
+    pub width: u32,
+    pub height: u32,
+    /// Bits per pixel, e.g., 32 for images with an alpha channel.
+    pub bit_count: u16,
+    /// PNG, or a bitmap without its file header followed by the mask.
+    data: Vec<u8>,
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_owned())
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        bytes.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in bytes.chunks(5552) {
+        for &byte in chunk {
+            a += u32::from(byte);
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+fn push_png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend(kind);
+    png.extend(data);
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+/// Encodes top-down RGBA pixels as a PNG, storing rather than compressing
+/// them, which icons are small enough for.
+fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    let mut scanlines = Vec::with_capacity(rgba.len() + height as usize);
+    for row in rgba.chunks(width as usize * 4) {
+        // No filter.
+        scanlines.push(0);
+        scanlines.extend(row);
+    }
+    let mut zlib = vec![0x78, 0x01];
+    let blocks: Vec<_> = scanlines.chunks(0xffff).collect();
+    for (index, block) in blocks.iter().enumerate() {
+        zlib.push(u8::from(index + 1 == blocks.len()));
+        let length = block.len() as u16;
+        zlib.extend(length.to_le_bytes());
+        zlib.extend((!length).to_le_bytes());
+        zlib.extend(*block);
+    }
+    zlib.extend(adler32(&scanlines).to_be_bytes());
+
+    let mut header = Vec::new();
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+    // 8 bits per channel of RGBA, deflated, filtered per line, and not
+    // interlaced.
+    header.extend([8, 6, 0, 0, 0]);
+
+    let mut png = PNG_SIGNATURE.to_vec();
+    push_png_chunk(&mut png, b"IHDR", &header);
+    push_png_chunk(&mut png, b"IDAT", &zlib);
+    push_png_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+/// Decodes a bitmap with 1, 4, 8, 24, or 32 bits per pixel followed by its
+/// 1-bit transparency mask, as icons store them, into top-down RGBA.
+fn decode_dib(dib: &[u8]) -> Option<(u32, u32, Vec<u8>)> {
+    let header_size = u32_at(dib, 0)? as usize;
+    let width = i32::from_le_bytes(dib.get(4..8)?.try_into().ok()?);
+    // Twice the height, for the image and then the mask.
+    let height = i32::from_le_bytes(dib.get(8..12)?.try_into().ok()?) / 2;
+    let bit_count = u16_at(dib, 14)?;
+    let compression = u32_at(dib, 16)?;
+    let colors_used = u32_at(dib, 32)?;
+    let (width, height) = (
+        u32::try_from(width)
+            .ok()
+            .filter(|width| (1..=1024).contains(width))?,
+        u32::try_from(height)
+            .ok()
+            .filter(|height| (1..=1024).contains(height))?,
+    );
+    // Uncompressed, or with the default masks for 32 bits per pixel.
+    if compression != 0 && !(compression == 3 && bit_count == 32) {
+        return None;
+    }
+    let mut palette_offset = header_size;
+    if compression == 3 && header_size == 40 {
+        palette_offset += 12;
+    }
+    let palette_length = match bit_count {
+        1 | 4 | 8 if colors_used == 0 => 1 << bit_count,
+        1 | 4 | 8 => colors_used as usize,
+        24 | 32 => 0,
+        _ => return None,
+    };
+    let palette =
+        dib.get(palette_offset..palette_offset + 4 * palette_length)?;
+    let pixels_offset = palette_offset + 4 * palette_length;
+    let stride = (width as usize * usize::from(bit_count)).div_ceil(32) * 4;
+    let mask_stride = (width as usize).div_ceil(32) * 4;
+    let mask_offset = pixels_offset + stride * height as usize;
+
+    let (width, height) = (width as usize, height as usize);
+    // Bitmaps store the bottom row first.
+    let row_of = |y: usize| height - 1 - y;
+    let masked = |x: usize, y: usize| {
+        dib.get(mask_offset + row_of(y) * mask_stride + x / 8)
+            .is_some_and(|byte| byte & (0x80 >> (x % 8)) != 0)
+    };
+
+    let mut rgba = Vec::with_capacity(width * height * 4);
+    for y in 0..height {
+        let row_start = pixels_offset + row_of(y) * stride;
+        let row = dib.get(row_start..row_start + stride)?;
+        for x in 0..width {
+            let [b, g, r, a] = match bit_count {
+                32 => row.get(4 * x..4 * x + 4)?.try_into().ok()?,
+                24 => {
+                    let bgr = row.get(3 * x..3 * x + 3)?;
+                    [bgr[0], bgr[1], bgr[2], 0xff]
+                }
+                _ => {
+                    let bit = x * usize::from(bit_count);
+                    let shift = 8 - usize::from(bit_count) - bit % 8;
+                    let index = (usize::from(row[bit / 8]) >> shift)
+                        & ((1 << bit_count) - 1);
+                    let bgr = palette.get(4 * index..4 * index + 3)?;
+                    [bgr[0], bgr[1], bgr[2], 0xff]
+                }
+            };
+            let a = if bit_count < 32 && masked(x, y) { 0 } else { a };
+            rgba.extend([r, g, b, a]);
+        }
+    }
+    // Some 32-bit images leave the alpha channel empty and rely on the mask.
+    if bit_count == 32 && rgba.chunks(4).all(|pixel| pixel[3] == 0) {
+        for (index, pixel) in rgba.chunks_mut(4).enumerate() {
+            pixel[3] = if masked(index % width, index / width) {
+                0
+            } else {
+                0xff
+            };
+        }
+    }
+    let (width, height) = (width as u32, height as u32);
+    Some((width, height, rgba))
+}
+
+impl IconImage {
+    pub fn is_png(&self) -> bool {
+        self.data.starts_with(PNG_SIGNATURE)
+    }
+
+    /// The image as a PNG file. Fails with [`io::ErrorKind::InvalidData`]
+    /// for bitmaps this crate cannot decode, e.g., compressed ones.
+    pub fn to_png(&self) -> io::Result<Vec<u8>> {
+        if self.is_png() {
+            return Ok(self.data.clone());
+        }
+        let (width, height, rgba) = decode_dib(&self.data)
+            .ok_or_else(|| invalid("unsupported icon bitmap"))?;
+        Ok(encode_png(width, height, &rgba))
+    }
+}
+
+/// Parses a group icon directory, whose entries name the icon resources
+/// holding each image.
+fn group_images(
+    group: &Resource,
+    resources: &[Resource],
+) -> io::Result<Vec<IconImage>> {
+    let malformed = || invalid("malformed icon group");
+    let count = u16_at(&group.data, 4).ok_or_else(malformed)?;
+    let mut images = Vec::new();
+    for index in 0..usize::from(count) {
+        let entry = group
+            .data
+            .get(6 + 14 * index..6 + 14 * (index + 1))
+            .ok_or_else(malformed)?;
+        // A size of zero means 256.
+        let size = |byte: u8| if byte == 0 { 256 } else { u32::from(byte) };
+        let id = ResourceId::Id(u16_at(entry, 12).ok_or_else(malformed)?);
+        let candidates = resources.iter().filter(|resource| {
+            resource.kind == ResourceId::ICON && resource.name == id
+        });
+        let Some(icon) = candidates
+            .clone()
+            .find(|resource| resource.language == group.language)
+            .or_else(|| candidates.clone().next())
+        else {
+            continue;
+        };
+        let mut image = IconImage {
+            width: size(entry[0]),
+            height: size(entry[1]),
+            bit_count: u16_at(entry, 6).ok_or_else(malformed)?,
+            data: icon.data.clone(),
+        };
+        // Directories of PNG images often leave the depth unset.
+        if image.bit_count == 0 && image.is_png() {
+            image.bit_count = 32;
+        }
+        images.push(image);
+    }
+    Ok(images)
+}
+
+/// The images of the icon of the executable or DLL at `path`, which is
+/// empty if it has none.
+pub fn images(path: impl AsRef<Path>) -> io::Result<Vec<IconImage>> {
+    let resources = pe::resources(path)?;
+    match resources
+        .iter()
+        .find(|resource| resource.kind == ResourceId::GROUP_ICON)
+    {
+        Some(group) => group_images(group, &resources),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// The largest image of the icon of `path`, preferring deeper colors among
+/// those of the same size.
+pub fn best(path: impl AsRef<Path>) -> io::Result<Option<IconImage>> {
+    Ok(images(path)?
+        .into_iter()
+        .max_by_key(|image| (image.width * image.height, image.bit_count)))
+}
+
+/// Writes the [`best`] image of the icon of `path` as a PNG to `dest`,
+/// returning whether there was one.
+pub fn export_png(
+    path: impl AsRef<Path>,
+    dest: impl AsRef<Path>,
+) -> io::Result<bool> {
+    match best(path)? {
+        Some(image) => {
+            fs::write(dest, image.to_png()?)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Packs `images` as an ICNS file, keeping the deepest image of every size
+/// that ICNS can hold and skipping the others, e.g., 48x48. Fails with
+/// [`io::ErrorKind::InvalidInput`] if no image is left.
+pub fn to_icns(images: &[IconImage]) -> io::Result<Vec<u8>> {
+    let mut elements = Vec::new();
+    for (size, kind) in ICNS_TYPES {
+        let Some(image) = images
+            .iter()
+            .filter(|image| image.width == *size && image.height == *size)
+            .max_by_key(|image| image.bit_count)
+        else {
+            continue;
+        };
+        let png = image.to_png()?;
+        elements.extend(*kind);
+        elements.extend((png.len() as u32 + 8).to_be_bytes());
+        elements.extend(png);
+    }
+    if elements.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "no image has a size ICNS supports",
+        ));
+    }
+    let mut icns = b"icns".to_vec();
+    icns.extend((elements.len() as u32 + 8).to_be_bytes());
+    icns.extend(elements);
+    Ok(icns)
+}
+
+/// Writes the icon of `path` as an ICNS file to `dest`, returning whether
+/// it had one, see [`to_icns`].
+pub fn export_icns(
+    path: impl AsRef<Path>,
+    dest: impl AsRef<Path>,
+) -> io::Result<bool> {
+    let images = images(path)?;
+    if images.is_empty() {
+        return Ok(false);
+    }
+    fs::write(dest, to_icns(&images)?)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io};
+
+    use super::IconImage;
+    use crate::pe::fixtures::{put_u16, put_u32, write_executable};
+
+    /// A bitmap as icons store it, with `pixels` and `mask` bottom row first.
+    fn dib(
+        size: u32,
+        bit_count: u16,
+        palette: &[u8],
+        pixels: &[u8],
+        mask: &[u8],
+    ) -> Vec<u8> {
+        let mut dib = vec![0; 40];
+        put_u32(&mut dib, 0, 40);
+        put_u32(&mut dib, 4, size);
+        put_u32(&mut dib, 8, 2 * size);
+        put_u16(&mut dib, 12, 1);
+        put_u16(&mut dib, 14, bit_count);
+        dib.extend_from_slice(palette);
+        dib.extend_from_slice(pixels);
+        dib.extend_from_slice(mask);
+        dib
+    }
+
+    /// A group icon directory listing `(size, bit_count, id)` images.
+    fn group(entries: &[(u8, u16, u16)]) -> Vec<u8> {
+        let mut group = vec![0; 6 + 14 * entries.len()];
+        put_u16(&mut group, 2, 1);
+        put_u16(&mut group, 4, entries.len() as u16);
+        for (index, (size, bit_count, id)) in entries.iter().enumerate() {
+            let entry = 6 + 14 * index;
+            group[entry] = *size;
+            group[entry + 1] = *size;
+            put_u16(&mut group, entry + 4, 1);
+            put_u16(&mut group, entry + 6, *bit_count);
+            put_u16(&mut group, entry + 12, *id);
+        }
+        group
+    }
+
+    #[test]
+    fn reads_icon_images() {
+        // Red and green over blue and white, with the red pixel masked out.
+        let true_color = dib(
+            2,
+            24,
+            &[],
+            &[255, 0, 0, 255, 255, 255, 0, 0, 0, 0, 255, 0, 255, 0, 0, 0],
+            &[0, 0, 0, 0, 0x80, 0, 0, 0],
+        );
+        let monochrome =
+            dib(4, 1, &[0, 0, 0, 0, 255, 255, 255, 0], &[0xa0; 16], &[0; 16]);
+        let png = super::encode_png(4, 4, &[0x80; 4 * 4 * 4]);
+        let path = write_executable(
+            "icon",
+            &[
+                (3, 1, &true_color),
+                (3, 2, &monochrome),
+                (3, 3, &png),
+                (14, 1, &group(&[(2, 24, 1), (4, 1, 2), (4, 0, 3)])),
+            ],
+        );
+
+        let images = super::images(&path).unwrap();
+        let shapes: Vec<_> = images
+            .iter()
+            .map(|image| (image.width, image.height, image.bit_count))
+            .collect();
+        assert_eq!(shapes, [(2, 2, 24), (4, 4, 1), (4, 4, 32)]);
+
+        let best = super::best(&path).unwrap().unwrap();
+        assert!(best.is_png());
+        assert_eq!(best.to_png().unwrap(), png);
+
+        let converted = images[0].to_png().unwrap();
+        assert!(converted.starts_with(super::PNG_SIGNATURE));
+        assert_eq!(&converted[16..24], [0, 0, 0, 2, 0, 0, 0, 2]);
+        // The top row of the stored scanlines, after the filter byte.
+        assert_eq!(&converted[49..57], [255, 0, 0, 0, 0, 255, 0, 255]);
+        assert_eq!(
+            images[1].to_png().unwrap()[16..24],
+            [0, 0, 0, 4, 0, 0, 0, 4]
+        );
+
+        let empty = IconImage {
+            width: 256,
+            height: 256,
+            bit_count: 24,
+            data: dib(0, 24, &[], &[], &[]),
+        };
+        let error = empty.to_png().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        fs::remove_file(path).unwrap();
+    }
+}
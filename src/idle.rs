@@ -0,0 +1,122 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    io,
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{GuestProcess, Prefix};
+
+/// How often [`Prefix::shut_down_when_idle`] lists the processes.
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The processes that Wine runs for itself, which do not keep a prefix busy.
+const SYSTEM_PROCESSES: &[&str] = &[
+    "conhost.exe",
+    "explorer.exe",
+    "plugplay.exe",
+    "rpcss.exe",
+    "services.exe",
+    "svchost.exe",
+    "winedbg.exe",
+    "winedevice.exe",
+];
+
+fn is_system_process(process: &GuestProcess) -> bool {
+    SYSTEM_PROCESSES
+        .iter()
+        .any(|name| process.image_name.eq_ignore_ascii_case(name))
+}
+
+/// Watches a prefix from a background thread, started with
+/// [`Prefix::shut_down_when_idle`]. Dropping it stops watching and leaves
+/// the wineserver running.
+#[derive(Debug)]
+pub struct IdleShutdown {
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<thread::JoinHandle<io::Result<bool>>>,
+}
+
+impl IdleShutdown {
+    pub fn is_finished(&self) -> bool {
+        self.thread
+            .as_ref()
+            .is_none_or(thread::JoinHandle::is_finished)
+    }
+
+    /// Blocks until the wineserver has gone down, returning whether it was
+    /// shut down for being idle rather than exiting on its own.
+    pub fn wait(mut self) -> io::Result<bool> {
+        self.join()
+    }
+
+    /// Stops watching, leaving the wineserver running.
+    pub fn cancel(mut self) -> io::Result<()> {
+        self.stop = None;
+        self.join().map(drop)
+    }
+
+    fn join(&mut self) -> io::Result<bool> {
+        match self.thread.take() {
+            Some(thread) => thread.join().unwrap_or_else(|_| {
+                Err(io::Error::other("the idle watcher panicked"))
+            }),
+            None => Ok(false),
+        }
+    }
+}
+
+impl Drop for IdleShutdown {
+    fn drop(&mut self) {
+        self.stop = None;
+        let _ = self.join();
+    }
+}
+
+impl Prefix {
+    /// Kills the wineserver once no programs other than those Wine runs for
+    /// itself have been running in the prefix for `idle`, e.g., to keep a
+    /// server started with [`crate::Persistence::Forever`] for fast launches
+    /// without leaving it running long after the last one.
+    ///
+    /// Programs are found with [`Prefix::processes`] every few seconds, so
+    /// one that starts and exits between two looks goes unnoticed.
+    pub fn shut_down_when_idle(&self, idle: Duration) -> IdleShutdown {
+        let (stop, stopped) = mpsc::channel();
+        let prefix = self.clone();
+        let thread = thread::spawn(move || {
+            let mut idle_since = None;
+            loop {
+                if !prefix.is_server_running() {
+                    return Ok(false);
+                }
+                if prefix
+                    .processes()?
+                    .iter()
+                    .any(|process| !is_system_process(process))
+                {
+                    idle_since = None;
+                } else if idle_since.get_or_insert_with(Instant::now).elapsed()
+                    >= idle
+                {
+                    prefix.kill_all()?;
+                    return Ok(true);
+                }
+                match stopped.recv_timeout(IDLE_POLL_INTERVAL.min(idle)) {
+                    Err(mpsc::RecvTimeoutError::Timeout) => {}
+                    _ => return Ok(false),
+                }
+            }
+        });
+        IdleShutdown {
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+}
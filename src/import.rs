@@ -0,0 +1,437 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Importing prefixes configured with Lutris or Bottles.
+//!
+//! Both tools store their settings as YAML. Only the block mappings, scalars,
+//! and scalar sequences they write are understood, which avoids pulling in a
+//! YAML implementation for a handful of keys. Files whose indentation does not
+//! line up or whose quotes are unterminated are rejected rather than read in
+//! part.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{DllOverride, DllOverrideMode, PrefixBuilder, PrefixConfig};
+
+/// A prefix described by another tool, ready to be built once the Wine
+/// installation to run it with is chosen.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ImportedPrefix {
+    pub path: PathBuf,
+    pub config: PrefixConfig,
+    /// The program the configuration launches, if it names one.
+    pub program: Option<PathBuf>,
+    /// `win32` or `win64`.
+    pub arch: Option<String>,
+    /// The name the tool gave its Wine build, e.g., `lutris-7.2-2`.
+    pub runner: Option<String>,
+}
+
+impl ImportedPrefix {
+    /// A builder for the prefix, on which the Wine binaries still need to be
+    /// set unless they live in `<prefix>/bin`.
+    pub fn prefix_builder(&self) -> PrefixBuilder {
+        let mut builder = PrefixBuilder::new(&self.path);
+        builder.config(self.config.clone());
+        builder
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Scalar(String),
+    Mapping(Vec<(String, Node)>),
+    Sequence(Vec<Node>),
+}
+
+impl Node {
+    fn get(&self, key: &str) -> Option<&Node> {
+        match self {
+            Self::Mapping(entries) => entries
+                .iter()
+                .find(|(entry_key, _)| entry_key == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Scalar(value) if !value.is_empty() => Some(value),
+            _ => None,
+        }
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        match self.as_str()? {
+            "true" | "True" | "yes" | "on" | "1" => Some(true),
+            "false" | "False" | "no" | "off" | "0" => Some(false),
+            _ => None,
+        }
+    }
+
+    fn entries(&self) -> &[(String, Node)] {
+        match self {
+            Self::Mapping(entries) => entries,
+            _ => &[],
+        }
+    }
+}
+
+struct Line<'a> {
+    indent: usize,
+    text: &'a str,
+}
+
+fn unquote(value: &str) -> io::Result<String> {
+    let value = value.trim();
+    for quote in ['"', '\''] {
+        if let Some(rest) = value.strip_prefix(quote) {
+            return match rest.split_once(quote) {
+                Some((inner, _)) => Ok(inner.to_owned()),
+                None => Err(invalid_data(&format!(
+                    "unterminated quote in YAML scalar `{value}`"
+                ))),
+            };
+        }
+    }
+    // An unquoted scalar ends at a comment.
+    Ok(value
+        .split_once(" #")
+        .map_or(value, |(value, _)| value)
+        .trim_end()
+        .to_owned())
+}
+
+fn misaligned(line: &Line) -> io::Error {
+    invalid_data(&format!(
+        "YAML line `{}` does not line up with the lines above it",
+        line.text
+    ))
+}
+
+fn parse_yaml(text: &str) -> io::Result<Node> {
+    let lines: Vec<_> = text
+        .lines()
+        .filter(|line| {
+            let trimmed = line.trim();
+            !trimmed.is_empty() && !trimmed.starts_with('#') && trimmed != "---"
+        })
+        .map(|line| {
+            let text = line.trim_start();
+            Line {
+                indent: line.len() - text.len(),
+                text: text.trim_end(),
+            }
+        })
+        .collect();
+    let mut index = 0;
+    let Some(first) = lines.first() else {
+        return Ok(Node::Mapping(Vec::new()));
+    };
+    let root = parse_block(&lines, &mut index, first.indent)?;
+    match lines.get(index) {
+        Some(line) => Err(misaligned(line)),
+        None => Ok(root),
+    }
+}
+
+fn parse_block(
+    lines: &[Line],
+    index: &mut usize,
+    indent: usize,
+) -> io::Result<Node> {
+    if lines[*index].text.starts_with('-') {
+        let mut items = Vec::new();
+        while let Some(line) = lines.get(*index)
+            && line.indent == indent
+            && line.text.starts_with('-')
+        {
+            items.push(Node::Scalar(unquote(&line.text[1..])?));
+            *index += 1;
+            // Nested structure inside sequence items is not needed.
+            while lines.get(*index).is_some_and(|line| line.indent > indent) {
+                *index += 1;
+            }
+        }
+        return Ok(Node::Sequence(items));
+    }
+
+    let mut entries = Vec::new();
+    while let Some(line) = lines.get(*index)
+        && line.indent >= indent
+    {
+        if line.indent > indent {
+            *index += 1;
+            continue;
+        }
+        let (key, value) = match line.text.split_once(": ") {
+            Some((key, value)) => (key, value.trim()),
+            None => (line.text.trim_end_matches(':'), ""),
+        };
+        let key = unquote(key)?;
+        *index += 1;
+        let value = match value {
+            "" => match lines.get(*index) {
+                Some(next) if next.indent > indent => {
+                    let value = parse_block(lines, index, next.indent)?;
+                    // The block ended at a line indented between it and
+                    // its key.
+                    if let Some(line) = lines.get(*index)
+                        && line.indent > indent
+                    {
+                        return Err(misaligned(line));
+                    }
+                    value
+                }
+                // Sequences may sit at the same indentation as their key.
+                Some(next)
+                    if next.indent == indent && next.text.starts_with('-') =>
+                {
+                    parse_block(lines, index, indent)?
+                }
+                _ => Node::Scalar(String::new()),
+            },
+            "{}" => Node::Mapping(Vec::new()),
+            "[]" => Node::Sequence(Vec::new()),
+            value => Node::Scalar(unquote(value)?),
+        };
+        entries.push((key, value));
+    }
+    Ok(Node::Mapping(entries))
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Copies a mapping of DLL names to modes into `config`, skipping modes that
+/// are not understood.
+fn import_dll_overrides(config: &mut PrefixConfig, overrides: Option<&Node>) {
+    for (dll, mode) in overrides.map(Node::entries).unwrap_or_default() {
+        if let Some(mode) = mode
+            .as_str()
+            .or(Some(""))
+            .and_then(DllOverrideMode::from_name)
+        {
+            config.dll_overrides.push(DllOverride::new(dll, mode));
+        }
+    }
+}
+
+fn import_env(config: &mut PrefixConfig, env: Option<&Node>) {
+    for (key, value) in env.map(Node::entries).unwrap_or_default() {
+        let value = value.as_str().unwrap_or_default();
+        config.extra_env.push((key.into(), value.into()));
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_owned())
+}
+
+/// Reads a Lutris game configuration, such as one in
+/// `~/.local/share/lutris/games/`.
+pub fn lutris(path: impl AsRef<Path>) -> io::Result<ImportedPrefix> {
+    let root = parse_yaml(&fs::read_to_string(path)?)?;
+    let game = root.get("game");
+    let game_str =
+        |key| game.and_then(|game| game.get(key)).and_then(Node::as_str);
+    let prefix_path = game_str("prefix").ok_or_else(|| {
+        invalid_data("Lutris configuration has no game.prefix")
+    })?;
+
+    let mut config = PrefixConfig::default();
+    let wine = root.get("wine");
+    let wine_bool = |key| {
+        wine.and_then(|wine| wine.get(key))
+            .and_then(Node::as_bool)
+            .unwrap_or(false)
+    };
+    config.esync = wine_bool("esync");
+    config.fsync = wine_bool("fsync");
+    config.msync = wine_bool("msync");
+    import_dll_overrides(
+        &mut config,
+        wine.and_then(|wine| wine.get("overrides")),
+    );
+    import_env(
+        &mut config,
+        root.get("system").and_then(|system| system.get("env")),
+    );
+
+    Ok(ImportedPrefix {
+        path: expand_home(prefix_path),
+        config,
+        program: game_str("exe").map(expand_home),
+        arch: game_str("arch").map(str::to_owned),
+        runner: wine
+            .and_then(|wine| wine.get("version"))
+            .and_then(Node::as_str)
+            .map(str::to_owned),
+    })
+}
+
+/// The directory Bottles keeps bottles without a custom path in.
+pub fn bottles_dir() -> Option<PathBuf> {
+    let home = PathBuf::from(env::var_os("HOME")?);
+    Some(home.join(".local/share/bottles/bottles"))
+}
+
+/// Reads the `bottle.yml` of a bottle created with Bottles.
+pub fn bottles(path: impl AsRef<Path>) -> io::Result<ImportedPrefix> {
+    let path = path.as_ref();
+    let root = parse_yaml(&fs::read_to_string(path)?)?;
+    let string = |key| root.get(key).and_then(Node::as_str);
+
+    let bottle_path = match string("Path") {
+        Some(bottle_path)
+            if root.get("Custom_Path").and_then(Node::as_bool)
+                == Some(true) =>
+        {
+            expand_home(bottle_path)
+        }
+        Some(name) => bottles_dir()
+            .ok_or_else(|| invalid_data("HOME is not set"))?
+            .join(name),
+        // The configuration lives at the root of the bottle.
+        None => path.parent().unwrap_or(Path::new(".")).to_path_buf(),
+    };
+
+    let mut config = PrefixConfig::default();
+    match root
+        .get("Parameters")
+        .and_then(|parameters| parameters.get("sync"))
+        .and_then(Node::as_str)
+    {
+        Some("esync") => config.esync = true,
+        Some("fsync" | "futex2") => config.fsync = true,
+        _ => {}
+    }
+    import_dll_overrides(&mut config, root.get("DLL_Overrides"));
+    import_env(&mut config, root.get("Environment_Variables"));
+
+    Ok(ImportedPrefix {
+        path: bottle_path,
+        config,
+        program: None,
+        arch: string("Arch").map(str::to_owned),
+        runner: string("Runner").map(str::to_owned),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, io, path::PathBuf, process};
+
+    use super::{Node, parse_yaml};
+    use crate::{DllOverride, DllOverrideMode};
+
+    fn scalar(value: &str) -> Node {
+        Node::Scalar(value.to_owned())
+    }
+
+    #[test]
+    fn parses_mappings_sequences_and_scalars() {
+        let root = parse_yaml(
+            "---\n\
+             # A comment.\n\
+             name: 'My Game' # trailing\n\
+             empty: {}\n\
+             wine:\n\
+             \x20 esync: true\n\
+             \x20 overrides:\n\
+             \x20   d3d11: \"native\"\n\
+             libs:\n\
+             - a.dll\n\
+             - \"b.dll\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            root,
+            Node::Mapping(vec![
+                ("name".to_owned(), scalar("My Game")),
+                ("empty".to_owned(), Node::Mapping(Vec::new())),
+                (
+                    "wine".to_owned(),
+                    Node::Mapping(vec![
+                        ("esync".to_owned(), scalar("true")),
+                        (
+                            "overrides".to_owned(),
+                            Node::Mapping(vec![(
+                                "d3d11".to_owned(),
+                                scalar("native")
+                            )])
+                        ),
+                    ])
+                ),
+                (
+                    "libs".to_owned(),
+                    Node::Sequence(vec![scalar("a.dll"), scalar("b.dll")])
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn rejects_indentation_that_does_not_line_up() {
+        let between = parse_yaml("game:\n    exe: a\n  prefix: b\n");
+        assert_eq!(between.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        let dedented = parse_yaml("  game: a\nprefix: b\n");
+        assert_eq!(dedented.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_an_unterminated_quote() {
+        let error = parse_yaml("name: \"My Game\n").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn reads_a_lutris_configuration() {
+        let path = env::temp_dir()
+            .join(format!("wine-rs-import-test-{}.yml", process::id()));
+        fs::write(
+            &path,
+            "game:\n\
+             \x20 exe: /games/app.exe\n\
+             \x20 prefix: /games/prefix\n\
+             wine:\n\
+             \x20 msync: true\n\
+             \x20 overrides:\n\
+             \x20   d3d11: native\n\
+             \x20 version: lutris-7.2-2\n\
+             system:\n\
+             \x20 env:\n\
+             \x20   DXVK_HUD: fps\n",
+        )
+        .unwrap();
+        let imported = super::lutris(&path).unwrap();
+        assert_eq!(imported.path, PathBuf::from("/games/prefix"));
+        assert_eq!(imported.program, Some(PathBuf::from("/games/app.exe")));
+        assert_eq!(imported.runner.as_deref(), Some("lutris-7.2-2"));
+        assert!(imported.config.msync && !imported.config.esync);
+        assert_eq!(
+            imported.config.dll_overrides,
+            [DllOverride::new("d3d11", DllOverrideMode::Native)]
+        );
+        assert_eq!(
+            imported.config.extra_env,
+            [("DXVK_HUD".into(), "fps".into())]
+        );
+
+        fs::write(&path, "wine:\n  esync: true\n").unwrap();
+        let missing = super::lutris(&path).unwrap_err();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(missing.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,195 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    env, error, fmt, fs,
+    path::{Path, PathBuf},
+    process::Output,
+};
+
+use crate::{
+    Defaults, PrefixBuilder, ResolvedCommand, WineFlavor, gptk,
+    serialize::serialize_fields,
+};
+
+/// `bin/` directories that Wine is commonly installed into besides those on
+/// `PATH`.
+#[cfg(target_os = "macos")]
+const KNOWN_BIN_DIRS: &[&str] = &[
+    "/opt/homebrew/bin",
+    "/usr/local/bin",
+    "/Applications/Wine Stable.app/Contents/Resources/wine/bin",
+    "/Applications/Wine Staging.app/Contents/Resources/wine/bin",
+    "/Applications/Wine Devel.app/Contents/Resources/wine/bin",
+    "/Applications/CrossOver.app/Contents/SharedSupport/CrossOver/bin",
+];
+
+#[cfg(not(target_os = "macos"))]
+const KNOWN_BIN_DIRS: &[&str] = &[
+    "/usr/bin",
+    "/usr/local/bin",
+    "/opt/wine-stable/bin",
+    "/opt/wine-staging/bin",
+    "/opt/wine-devel/bin",
+    "/opt/cxoffice/bin",
+];
+
+/// A `wineserver` from a different Wine release than the loader, which
+/// makes launches fail with protocol errors.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VersionMismatch {
+    /// The version number of the loader, e.g., `9.0`.
+    pub wine: String,
+    pub wineserver: String,
+}
+
+impl fmt::Display for VersionMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "wine {} cannot talk to wineserver {}",
+            self.wine, self.wineserver
+        )
+    }
+}
+
+serialize_fields!(VersionMismatch { wine, wineserver });
+
+impl error::Error for VersionMismatch {}
+
+/// The version number in the `--version` output of `wine`, such as
+/// `wine-9.0 (Staging)`, or of `wineserver`, such as `Wine 9.0`.
+pub(crate) fn version_number(output: &str) -> Option<&str> {
+    output
+        .split_whitespace()
+        .map(|word| word.strip_prefix("wine-").unwrap_or(word))
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+/// Compares the `--version` outputs of a loader and a wineserver, which are
+/// compatible if either is unknown.
+pub(crate) fn check_versions(
+    wine: Option<&str>,
+    wineserver: Option<&str>,
+) -> Result<(), VersionMismatch> {
+    match (
+        wine.and_then(version_number),
+        wineserver.and_then(version_number),
+    ) {
+        (Some(wine), Some(wineserver)) if wine != wineserver => {
+            Err(VersionMismatch {
+                wine: wine.to_owned(),
+                wineserver: wineserver.to_owned(),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The `--version` output of `binary`, run in the environment of
+/// [`Defaults::environment`] since it belongs to no prefix.
+fn version_output(binary: &Path) -> Option<String> {
+    let mut command = ResolvedCommand::new(binary, ".");
+    Defaults::with(|defaults| defaults.environment.apply(&mut command));
+    command.args.push("--version".into());
+    parse_version_output(command.to_command().output().ok()?)
+}
+
+pub(crate) fn parse_version_output(output: Output) -> Option<String> {
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_owned())
+        .filter(|version| !version.is_empty())
+}
+
+/// A Wine build found on this system.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WineInstallation {
+    /// The loader, `wine` or, for builds that only ship one, `wine64`.
+    pub wine: PathBuf,
+    /// The directory containing `bin/` and `share/wine/`.
+    pub root: PathBuf,
+    /// The output of `wine --version`, e.g., `wine-9.0 (Staging)`.
+    pub version: Option<String>,
+    pub flavor: WineFlavor,
+}
+
+impl WineInstallation {
+    /// Describes the build whose loader is `wine`, running it to read its
+    /// version.
+    pub fn at(wine: impl AsRef<Path>) -> Self {
+        let wine = wine.as_ref().to_path_buf();
+        let root = wine
+            .parent()
+            .and_then(Path::parent)
+            .unwrap_or(Path::new("/"))
+            .to_path_buf();
+        let version = version_output(&wine);
+        let flavor = version
+            .as_deref()
+            .and_then(WineFlavor::from_version_string)
+            .unwrap_or_else(|| WineFlavor::detect_from_layout(&root));
+        Self {
+            wine,
+            root,
+            version,
+            flavor,
+        }
+    }
+
+    /// Finds every Wine build on `PATH` and in the usual installation
+    /// locations for this platform, including Game Porting Toolkit, so an
+    /// application can let the user pick one. Builds reachable through
+    /// several paths are listed once.
+    pub fn discover() -> Vec<Self> {
+        let mut bin_dirs: Vec<PathBuf> = env::var_os("PATH")
+            .map(|path| env::split_paths(&path).collect())
+            .unwrap_or_default();
+        bin_dirs.extend(KNOWN_BIN_DIRS.iter().map(PathBuf::from));
+        if let Some(gptk) = gptk::detect() {
+            bin_dirs.push(gptk.join("bin"));
+        }
+
+        let mut seen = Vec::new();
+        let mut installations = Vec::new();
+        for bin_dir in bin_dirs {
+            let Some(wine) = ["wine", "wine64"]
+                .iter()
+                .map(|name| bin_dir.join(name))
+                .find(|wine| wine.is_file())
+            else {
+                continue;
+            };
+            let Ok(canonical) = fs::canonicalize(&wine) else {
+                continue;
+            };
+            if seen.contains(&canonical) {
+                continue;
+            }
+            seen.push(canonical);
+            installations.push(Self::at(wine));
+        }
+        installations
+    }
+
+    /// Checks that the `wineserver` next to the loader comes from the same
+    /// release, which is not a given when several builds share a `bin/`.
+    pub fn check_compat(&self) -> Result<(), VersionMismatch> {
+        let wineserver = self.wine.with_file_name("wineserver");
+        check_versions(
+            self.version.as_deref(),
+            version_output(&wineserver).as_deref(),
+        )
+    }
+
+    /// Starts building a prefix at `path` that runs this build.
+    pub fn prefix_builder(&self, path: impl AsRef<Path>) -> PrefixBuilder {
+        let mut builder = PrefixBuilder::new(path);
+        builder.wine(&self.wine);
+        builder
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    fs,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    Prefix, WineCommand,
+    pe::{self, ResourceId},
+};
+
+/// The signature of OLE compound files, which Windows Installer packages are.
+const COMPOUND_FILE_MAGIC: [u8; 8] =
+    [0xd0, 0xcf, 0x11, 0xe0, 0xa1, 0xb1, 0x1a, 0xe1];
+
+/// How much of the data appended to an executable is searched for the
+/// header of an NSIS installer.
+const OVERLAY_SEARCH_LENGTH: u64 = 4096;
+
+/// The framework an installer was built with, which decides the switches
+/// that make it install without asking anything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InstallerKind {
+    Nsis,
+    InnoSetup,
+    /// Projects of the Basic MSI kind. InstallScript ones additionally need a
+    /// response file recorded with `/r`, passed as `/f1<path>`.
+    InstallShield,
+    /// An executable that runs an embedded Windows Installer package, e.g., a
+    /// WiX Burn bundle.
+    MsiWrapper,
+    /// A Windows Installer package, run with `msiexec`.
+    Msi,
+}
+
+impl InstallerKind {
+    /// The switches for an unattended installation that does not restart.
+    pub fn silent_args(&self) -> &'static [&'static str] {
+        match self {
+            Self::Nsis => &["/S"],
+            Self::InnoSetup => {
+                &["/VERYSILENT", "/SUPPRESSMSGBOXES", "/NORESTART", "/SP-"]
+            }
+            Self::InstallShield => &["/s", "/v/qn"],
+            Self::MsiWrapper => &["/quiet", "/norestart"],
+            Self::Msi => &["/qn", "/norestart"],
+        }
+    }
+
+    /// The switch that installs into the Windows directory `dir`, for the
+    /// frameworks with one. NSIS requires it to be the last argument.
+    pub fn install_dir_arg(&self, dir: &str) -> Option<String> {
+        match self {
+            Self::Nsis => Some(format!("/D={dir}")),
+            Self::InnoSetup => Some(format!("/DIR={dir}")),
+            Self::InstallShield | Self::MsiWrapper | Self::Msi => None,
+        }
+    }
+}
+
+/// Whether `haystack` contains `needle` as ASCII or as UTF-16, the encoding
+/// of version information.
+fn contains_text(haystack: &[u8], needle: &str) -> bool {
+    let utf16: Vec<u8> =
+        needle.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    [needle.as_bytes(), &utf16].iter().any(|needle| {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == *needle)
+    })
+}
+
+/// Recognizes the framework of the executable at `path` by the header NSIS
+/// appends, the sections of WiX Burn, and the manifest, version
+/// information, and data that the others embed as resources.
+fn detect_executable(path: &Path) -> io::Result<Option<InstallerKind>> {
+    let mut file = fs::File::open(path)?;
+    let layout = pe::Layout::read(&mut file)?;
+    if layout
+        .sections
+        .iter()
+        .any(|section| section.name == ".wixburn")
+    {
+        return Ok(Some(InstallerKind::MsiWrapper));
+    }
+
+    let mut overlay = Vec::new();
+    file.seek(SeekFrom::Start(layout.overlay_offset()))?;
+    (&mut file)
+        .take(OVERLAY_SEARCH_LENGTH)
+        .read_to_end(&mut overlay)?;
+    if contains_text(&overlay, "NullsoftInst") {
+        return Ok(Some(InstallerKind::Nsis));
+    }
+
+    let resources = layout.resources(&mut file)?;
+    let texts: Vec<_> = resources
+        .iter()
+        .filter(|resource| {
+            [ResourceId::MANIFEST, ResourceId::VERSION].contains(&resource.kind)
+        })
+        .map(|resource| resource.data.as_slice())
+        .collect();
+    let mentions =
+        |needle| texts.iter().any(|text| contains_text(text, needle));
+    let kind = if mentions("Nullsoft.NSIS") {
+        Some(InstallerKind::Nsis)
+    } else if mentions("JR.Inno.Setup")
+        || mentions("Inno Setup")
+        // The setup loader finds its data through this resource.
+        || resources.iter().any(|resource| {
+            resource.kind == ResourceId::RCDATA
+                && resource.data.starts_with(b"rDlPtS")
+        })
+    {
+        Some(InstallerKind::InnoSetup)
+    } else if mentions("InstallShield") {
+        Some(InstallerKind::InstallShield)
+    } else if mentions("MSI Wrapper") || mentions("WixBundle") {
+        Some(InstallerKind::MsiWrapper)
+    } else {
+        None
+    };
+    Ok(kind)
+}
+
+/// An installer on the host whose framework has been looked up, to run it
+/// unattended with [`Installer::silent_command`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Installer {
+    path: PathBuf,
+    kind: Option<InstallerKind>,
+}
+
+impl Installer {
+    /// Inspects the executable or `.msi` package at `path`. Fails with
+    /// [`io::ErrorKind::InvalidData`] if it is neither.
+    pub fn detect(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let mut magic = [0; 8];
+        let length = fs::File::open(path)?.read(&mut magic)?;
+        let kind = if magic == COMPOUND_FILE_MAGIC {
+            Some(InstallerKind::Msi)
+        } else if length >= 2 && &magic[..2] == b"MZ" {
+            detect_executable(path)?
+        } else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{} is neither an executable nor a Windows Installer \
+                     package",
+                    path.display()
+                ),
+            ));
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            kind,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// `None` if the framework is not one this crate recognizes.
+    pub fn kind(&self) -> Option<InstallerKind> {
+        self.kind
+    }
+
+    /// Runs the installer in `prefix` with the switches of its framework,
+    /// to which more can be added, or without any if it was not recognized.
+    pub fn silent_command<'a>(&self, prefix: &'a Prefix) -> WineCommand<'a> {
+        let mut command = match self.kind {
+            Some(InstallerKind::Msi) => {
+                let mut command = prefix.builtin_command("msiexec");
+                command.arg("/i").host_path_arg(&self.path);
+                command
+            }
+            _ => prefix.builtin_command(&self.path),
+        };
+        if let Some(kind) = self.kind {
+            command.args(kind.silent_args());
+        }
+        command
+    }
+}
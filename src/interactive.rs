@@ -0,0 +1,125 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    io::{self, Write},
+    process::{ChildStdin, ExitStatus},
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+use crate::{Error, OutputLine, WineChild, WineCommand};
+
+/// A running console program driven through its stdin, launched with
+/// [`WineCommand::spawn_interactive`].
+///
+/// Output arrives a line at a time, so a prompt is only seen once the program
+/// ends it with a newline.
+#[derive(Debug)]
+pub struct InteractiveChild {
+    child: WineChild,
+    stdin: Option<ChildStdin>,
+    lines: mpsc::Receiver<OutputLine>,
+}
+
+impl WineCommand<'_> {
+    /// Spawns the command with its stdin, stdout, and stderr piped, e.g., to
+    /// answer the text prompts of an installer. Programs that need a real
+    /// console to read input should run under
+    /// [`crate::StartMode::Console`].
+    pub fn spawn_interactive(&self) -> io::Result<InteractiveChild> {
+        let (mut child, lines) = self.spawn_streaming(true)?;
+        let stdin = child.child.stdin.take();
+        Ok(InteractiveChild {
+            child,
+            stdin,
+            lines,
+        })
+    }
+}
+
+impl InteractiveChild {
+    fn stdin(&mut self) -> io::Result<&mut ChildStdin> {
+        self.stdin.as_mut().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::BrokenPipe, "stdin was closed")
+        })
+    }
+
+    pub fn write(&mut self, bytes: impl AsRef<[u8]>) -> io::Result<()> {
+        let stdin = self.stdin()?;
+        stdin.write_all(bytes.as_ref())?;
+        stdin.flush()
+    }
+
+    /// Writes `line` followed by the Windows line ending.
+    pub fn send_line(&mut self, line: impl AsRef<str>) -> io::Result<()> {
+        self.write(format!("{}\r\n", line.as_ref()))
+    }
+
+    /// Closes stdin so that the program reads the end of its input.
+    pub fn close_stdin(&mut self) {
+        self.stdin = None;
+    }
+
+    /// Waits up to `timeout` for the next line of output, returning `None`
+    /// once the program and everything sharing its output have exited.
+    pub fn next_line(
+        &mut self,
+        timeout: Duration,
+    ) -> io::Result<Option<OutputLine>> {
+        match self.lines.recv_timeout(timeout) {
+            Ok(line) => Ok(Some(line)),
+            Err(mpsc::RecvTimeoutError::Disconnected) => Ok(None),
+            Err(mpsc::RecvTimeoutError::Timeout) => Err(Error::Timeout(
+                "waiting for output from the program".to_owned(),
+            )
+            .into()),
+        }
+    }
+
+    /// Reads output until a line on stdout contains `needle`, returning every
+    /// line read including that one.
+    pub fn expect(
+        &mut self,
+        needle: impl AsRef<str>,
+        timeout: Duration,
+    ) -> io::Result<Vec<OutputLine>> {
+        let deadline = Instant::now() + timeout;
+        let mut lines = Vec::new();
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let Some(line) = self.next_line(remaining)? else {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!(
+                        "the program exited before printing `{}`",
+                        needle.as_ref()
+                    ),
+                ));
+            };
+            let found = matches!(
+                &line,
+                OutputLine::Stdout(text) if text.contains(needle.as_ref())
+            );
+            lines.push(line);
+            if found {
+                return Ok(lines);
+            }
+        }
+    }
+
+    pub fn child(&mut self) -> &mut WineChild {
+        &mut self.child
+    }
+
+    /// Closes stdin and waits for the program to exit, discarding output that
+    /// was not read.
+    pub fn wait(&mut self) -> io::Result<ExitStatus> {
+        self.close_stdin();
+        while self.lines.recv().is_ok() {}
+        self.child.wait()
+    }
+}
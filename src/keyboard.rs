@@ -0,0 +1,243 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::io;
+
+use crate::{
+    Error, Prefix,
+    registry::{RegistryBatch, RegistryKey, RegistryValue},
+};
+
+/// A keyboard layout of the guest, i.e., an input locale and how keys map to
+/// characters in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyboardLayout {
+    EnglishUs,
+    EnglishUk,
+    /// Dvorak on US English.
+    EnglishUsDvorak,
+    German,
+    /// Swiss German.
+    GermanSwiss,
+    French,
+    Spanish,
+    Italian,
+    PortugueseBrazil,
+    Russian,
+    Polish,
+    /// Japanese, whose input method is provided by the host through XIM.
+    Japanese,
+    Korean,
+    ChineseSimplified,
+    ChineseTraditional,
+    /// A keyboard layout identifier, e.g., `0x0000_0413` for Dutch, where the
+    /// low word is the language and the high word the variant of the layout.
+    Other(u32),
+}
+
+impl KeyboardLayout {
+    /// The keyboard layout identifier, written as eight hex digits in the
+    /// registry, e.g., `00000409`.
+    pub fn id(&self) -> u32 {
+        match self {
+            Self::EnglishUs => 0x0000_0409,
+            Self::EnglishUk => 0x0000_0809,
+            Self::EnglishUsDvorak => 0x0001_0409,
+            Self::German => 0x0000_0407,
+            Self::GermanSwiss => 0x0000_0807,
+            Self::French => 0x0000_040c,
+            Self::Spanish => 0x0000_040a,
+            Self::Italian => 0x0000_0410,
+            Self::PortugueseBrazil => 0x0000_0416,
+            Self::Russian => 0x0000_0419,
+            Self::Polish => 0x0000_0415,
+            Self::Japanese => 0x0000_0411,
+            Self::Korean => 0x0000_0412,
+            Self::ChineseSimplified => 0x0000_0804,
+            Self::ChineseTraditional => 0x0000_0404,
+            Self::Other(id) => *id,
+        }
+    }
+
+    pub fn from_id(id: u32) -> Self {
+        [
+            Self::EnglishUs,
+            Self::EnglishUk,
+            Self::EnglishUsDvorak,
+            Self::German,
+            Self::GermanSwiss,
+            Self::French,
+            Self::Spanish,
+            Self::Italian,
+            Self::PortugueseBrazil,
+            Self::Russian,
+            Self::Polish,
+            Self::Japanese,
+            Self::Korean,
+            Self::ChineseSimplified,
+            Self::ChineseTraditional,
+        ]
+        .into_iter()
+        .find(|layout| layout.id() == id)
+        .unwrap_or(Self::Other(id))
+    }
+
+    /// The language identifier of the input locale, e.g., `0x0409`.
+    pub fn language(&self) -> u16 {
+        self.id() as u16
+    }
+}
+
+/// How the host input method shows text that is being composed, e.g., in
+/// Japanese, which Wine forwards to it through XIM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImeInputStyle {
+    /// In a window of the input method.
+    Root,
+    /// In place, where the text is inserted.
+    OverTheSpot,
+    /// In an area the program reserves for it.
+    OffTheSpot,
+}
+
+impl ImeInputStyle {
+    /// The spelling of the `InputStyle` value of the X11 driver.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Root => "root",
+            Self::OverTheSpot => "overthespot",
+            Self::OffTheSpot => "offthespot",
+        }
+    }
+}
+
+fn keyboard_layout_key() -> RegistryKey {
+    RegistryKey::current_user("Keyboard Layout")
+}
+
+fn preload_key() -> RegistryKey {
+    keyboard_layout_key().join("Preload")
+}
+
+fn substitutes_key() -> RegistryKey {
+    keyboard_layout_key().join("Substitutes")
+}
+
+fn format_id(id: u32) -> String {
+    format!("{id:08x}")
+}
+
+impl Prefix {
+    /// Makes `layouts` the input locales of the user, the first of which is
+    /// the default. Fails with [`io::ErrorKind::InvalidInput`] if it is
+    /// empty.
+    ///
+    /// Layouts that are a variant of their language, such as Dvorak, are
+    /// listed through substitutes, like the Windows settings do.
+    pub fn set_keyboard_layouts(
+        &self,
+        layouts: &[KeyboardLayout],
+    ) -> io::Result<()> {
+        if layouts.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "at least one keyboard layout is required",
+            ));
+        }
+        let mut batch = RegistryBatch::new();
+        batch
+            .delete_key(&preload_key())
+            .delete_key(&substitutes_key());
+        for (index, layout) in layouts.iter().enumerate() {
+            let id = layout.id();
+            let preloaded = if id >> 16 == 0 {
+                id
+            } else {
+                // `d` marks an ID that only names an entry of `Substitutes`.
+                let substitute = 0xd000_0000
+                    | ((index as u32 & 0xfff) << 16)
+                    | (id & 0xffff);
+                batch.set_value(
+                    &substitutes_key(),
+                    Some(&format_id(substitute)),
+                    format_id(id),
+                );
+                substitute
+            };
+            batch.set_value(
+                &preload_key(),
+                Some(&(index + 1).to_string()),
+                format_id(preloaded),
+            );
+        }
+        self.apply_registry_batch(&batch)
+    }
+
+    /// The input locales of the user in order, the first of which is the
+    /// default, or empty if Wine uses the one of the host.
+    pub fn keyboard_layouts(&self) -> io::Result<Vec<KeyboardLayout>> {
+        let parse_id = |text: &str| {
+            u32::from_str_radix(text, 16).map_err(|_| {
+                io::Error::from(Error::RegistryParse(format!(
+                    "malformed keyboard layout `{text}`"
+                )))
+            })
+        };
+        let keyboard_layout = keyboard_layout_key();
+        if !self.registry_key_exists(&keyboard_layout)? {
+            return Ok(Vec::new());
+        }
+        // Both subkeys are read with a single query.
+        let (mut preload, mut substitutes) = (Vec::new(), Vec::new());
+        for contents in keyboard_layout.walk(self)? {
+            if contents
+                .key
+                .path()
+                .eq_ignore_ascii_case(preload_key().path())
+            {
+                preload = contents.values;
+            } else if contents
+                .key
+                .path()
+                .eq_ignore_ascii_case(substitutes_key().path())
+            {
+                substitutes = contents.values;
+            }
+        }
+        let mut preloaded = Vec::new();
+        for (name, value) in preload {
+            let (Some(index), RegistryValue::String(id)) =
+                (name.and_then(|name| name.parse::<u32>().ok()), value)
+            else {
+                continue;
+            };
+            let id = substitutes
+                .iter()
+                .find_map(|(name, value)| match value {
+                    RegistryValue::String(substitute)
+                        if name.as_deref().is_some_and(|name| {
+                            name.eq_ignore_ascii_case(&id)
+                        }) =>
+                    {
+                        Some(substitute.clone())
+                    }
+                    _ => None,
+                })
+                .unwrap_or(id);
+            preloaded.push((index, KeyboardLayout::from_id(parse_id(&id)?)));
+        }
+        preloaded.sort_by_key(|(index, _)| *index);
+        Ok(preloaded.into_iter().map(|(_, layout)| layout).collect())
+    }
+
+    pub fn set_ime_input_style(&self, style: ImeInputStyle) -> io::Result<()> {
+        self.set_registry_value(
+            &RegistryKey::current_user("Software\\Wine\\X11 Driver"),
+            Some("InputStyle"),
+            style.as_str(),
+        )
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    process,
+    sync::{Arc, Condvar, Mutex},
+    task::{Context, Poll, Waker},
+    thread,
+};
+
+use crate::{Prefix, trace};
+
+/// The signal that `wineserver -k` sends to the processes of a prefix.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KillSignal {
+    /// `SIGINT`, which the wineserver sends when given no signal.
+    #[default]
+    Interrupt,
+    Terminate,
+    /// `SIGKILL`, for processes stuck ignoring the other signals.
+    Kill,
+}
+
+impl KillSignal {
+    fn to_arg(self) -> &'static str {
+        match self {
+            Self::Interrupt => "-k",
+            Self::Terminate => "-k15",
+            Self::Kill => "-k9",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct KillState {
+    result: Option<io::Result<process::Output>>,
+    waker: Option<Waker>,
+}
+
+/// A `wineserver -k` started by [`Prefix::kill_all_in_background`], which can
+/// be waited on or awaited from any async runtime.
+#[derive(Debug)]
+pub struct KillAllHandle {
+    state: Arc<(Mutex<KillState>, Condvar)>,
+}
+
+impl KillAllHandle {
+    pub fn is_finished(&self) -> bool {
+        self.state.0.lock().unwrap().result.is_some()
+    }
+
+    /// Blocks until the wineserver has gone down.
+    pub fn wait(self) -> io::Result<process::Output> {
+        let (state, finished) = &*self.state;
+        let mut state = finished
+            .wait_while(state.lock().unwrap(), |state| state.result.is_none())
+            .unwrap();
+        state.result.take().expect("checked by the wait")
+    }
+}
+
+impl Future for KillAllHandle {
+    type Output = io::Result<process::Output>;
+
+    fn poll(
+        self: Pin<&mut Self>,
+        context: &mut Context<'_>,
+    ) -> Poll<Self::Output> {
+        let mut state = self.state.0.lock().unwrap();
+        if let Some(result) = state.result.take() {
+            Poll::Ready(result)
+        } else {
+            state.waker = Some(context.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+impl Prefix {
+    /// Like [`Prefix::kill_all`], but delivers `signal` instead.
+    pub fn kill_all_with(
+        &self,
+        signal: KillSignal,
+    ) -> io::Result<process::Output> {
+        let command = self.wineserver_command(signal.to_arg());
+        trace::operation(
+            "kill_all_with",
+            &self.path,
+            Some(&self.wineserver),
+            || self.runner.0.output(&command),
+        )
+    }
+
+    /// Like [`Prefix::kill_all_with`], but returns right away, e.g., so that a
+    /// GUI does not stall while the processes of the prefix shut down.
+    pub fn kill_all_in_background(&self, signal: KillSignal) -> KillAllHandle {
+        let state =
+            Arc::new((Mutex::new(KillState::default()), Condvar::new()));
+        let handle = KillAllHandle {
+            state: state.clone(),
+        };
+        let prefix = self.clone();
+        thread::spawn(move || {
+            let result = prefix.kill_all_with(signal);
+            let (state, finished) = &*state;
+            let mut state = state.lock().unwrap();
+            state.result = Some(result);
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+            finished.notify_all();
+        });
+        handle
+    }
+
+    /// [`Prefix::kill_all_with`] for async code, which runs on a thread of its
+    /// own rather than blocking the executor.
+    pub async fn kill_all_async(
+        &self,
+        signal: KillSignal,
+    ) -> io::Result<process::Output> {
+        self.kill_all_in_background(signal).await
+    }
+}
@@ -0,0 +1,299 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs, io,
+    path::Path,
+    str::FromStr,
+};
+
+use crate::{
+    Prefix, ResolvedCommand, WineCommand, serialize::serialize_fields,
+};
+
+/// The environment a launch applies, i.e., the variables derived from
+/// [`crate::PrefixConfig`] and those set on the command, frozen by
+/// [`Prefix::capture_environment`] so that it can be stored, compared with
+/// [`LaunchEnvironment::changes_from`], and put back with
+/// [`WineCommand::restore_environment`].
+///
+/// Variables inherited from the current process are only part of it when
+/// the environment is cleared and they are forwarded. Names and values that
+/// are not UTF-8 are converted lossily.
+///
+/// Environments are stored as lines of `set NAME=value`, `unset NAME`, and a
+/// lone `clear`, where `#` at the start of a line starts a comment and `\n`
+/// and `\\` escape newlines and backslashes in values.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct LaunchEnvironment {
+    /// Whether the environment of the current process is discarded.
+    pub env_clear: bool,
+    pub vars: BTreeMap<String, String>,
+    /// Variables removed from the inherited environment.
+    pub unset: BTreeSet<String>,
+}
+
+serialize_fields!(LaunchEnvironment {
+    env_clear,
+    vars,
+    unset
+});
+
+/// A difference between two [`LaunchEnvironment`]s.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EnvironmentChange {
+    Added {
+        name: String,
+        value: String,
+    },
+    Removed {
+        name: String,
+        value: String,
+    },
+    Changed {
+        name: String,
+        from: String,
+        to: String,
+    },
+}
+
+impl fmt::Display for EnvironmentChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added { name, value } => write!(f, "+{name}={value}"),
+            Self::Removed { name, value } => write!(f, "-{name}={value}"),
+            Self::Changed { name, from, to } => {
+                write!(f, "~{name}={from} -> {to}")
+            }
+        }
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(value: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(char) = chars.next() {
+        if char == '\\' {
+            match chars.next()? {
+                'n' => unescaped.push('\n'),
+                '\\' => unescaped.push('\\'),
+                _ => return None,
+            }
+        } else {
+            unescaped.push(char);
+        }
+    }
+    Some(unescaped)
+}
+
+impl LaunchEnvironment {
+    /// The environment `command` launches with, where later entries of
+    /// [`ResolvedCommand::envs`] win.
+    pub fn of(command: &ResolvedCommand) -> Self {
+        Self {
+            env_clear: command.env_clear,
+            vars: command
+                .envs
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        name.to_string_lossy().into_owned(),
+                        value.to_string_lossy().into_owned(),
+                    )
+                })
+                .collect(),
+            unset: command
+                .unset_envs
+                .iter()
+                .map(|name| name.to_string_lossy().into_owned())
+                .collect(),
+        }
+    }
+
+    /// Replaces the environment of `command` with this one.
+    pub fn apply(&self, command: &mut ResolvedCommand) {
+        command.env_clear = self.env_clear;
+        command.envs = self
+            .vars
+            .iter()
+            .map(|(name, value)| (name.into(), value.into()))
+            .collect();
+        command.unset_envs = self.unset.iter().map(Into::into).collect();
+    }
+
+    /// How the variables of this environment differ from those of `earlier`,
+    /// in order of their names.
+    pub fn changes_from(&self, earlier: &Self) -> Vec<EnvironmentChange> {
+        let names: BTreeSet<_> =
+            earlier.vars.keys().chain(self.vars.keys()).collect();
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let name = name.clone();
+                match (earlier.vars.get(&name), self.vars.get(&name)) {
+                    (None, Some(value)) => Some(EnvironmentChange::Added {
+                        name,
+                        value: value.clone(),
+                    }),
+                    (Some(value), None) => Some(EnvironmentChange::Removed {
+                        name,
+                        value: value.clone(),
+                    }),
+                    (Some(from), Some(to)) if from != to => {
+                        Some(EnvironmentChange::Changed {
+                            name,
+                            from: from.clone(),
+                            to: to.clone(),
+                        })
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+}
+
+impl FromStr for LaunchEnvironment {
+    type Err = io::Error;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let invalid = |line_number: usize, message: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {line_number}: {message}"),
+            )
+        };
+        let mut environment = Self::default();
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line == "clear" {
+                environment.env_clear = true;
+                continue;
+            }
+            match line.split_once(' ') {
+                Some(("set", assignment)) => {
+                    let (name, value) = assignment
+                        .split_once('=')
+                        .ok_or_else(|| invalid(line_number, "expected `=`"))?;
+                    let value = unescape(value).ok_or_else(|| {
+                        invalid(line_number, "malformed escape")
+                    })?;
+                    environment.vars.insert(name.to_owned(), value);
+                }
+                Some(("unset", name)) => {
+                    environment.unset.insert(name.to_owned());
+                }
+                _ => {
+                    return Err(invalid(
+                        line_number,
+                        "expected `set`, `unset`, or `clear`",
+                    ));
+                }
+            }
+        }
+        Ok(environment)
+    }
+}
+
+impl fmt::Display for LaunchEnvironment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.env_clear {
+            writeln!(f, "clear")?;
+        }
+        for name in &self.unset {
+            writeln!(f, "unset {name}")?;
+        }
+        for (name, value) in &self.vars {
+            writeln!(f, "set {name}={}", escape(value))?;
+        }
+        Ok(())
+    }
+}
+
+impl Prefix {
+    /// The environment that launches in the prefix apply before anything is
+    /// set on the command itself.
+    pub fn capture_environment(&self) -> LaunchEnvironment {
+        self.wine_command("").capture_environment()
+    }
+}
+
+impl WineCommand<'_> {
+    /// The environment the command launches with, before pre-launch hooks.
+    pub fn capture_environment(&self) -> LaunchEnvironment {
+        LaunchEnvironment::of(&self.resolve())
+    }
+
+    /// Launches with `environment` verbatim in place of the one the prefix
+    /// and the command would apply, e.g., one captured with an earlier
+    /// version of the configuration.
+    pub fn restore_environment(
+        &mut self,
+        environment: &LaunchEnvironment,
+    ) -> &mut Self {
+        self.restored_environment = Some(environment.clone());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::LaunchEnvironment;
+
+    #[test]
+    fn parses_and_round_trips() {
+        let environment: LaunchEnvironment = "# Captured before the update.\n\
+                                              clear\n\
+                                              unset DISPLAY\n\
+                                              set WINEDEBUG=-all\n\
+                                              set MOTD=a\\nb \\\\ c=d\n"
+            .parse()
+            .unwrap();
+        assert!(environment.env_clear);
+        assert!(environment.unset.contains("DISPLAY"));
+        assert_eq!(environment.vars["WINEDEBUG"], "-all");
+        assert_eq!(environment.vars["MOTD"], "a\nb \\ c=d");
+        assert_eq!(
+            environment
+                .to_string()
+                .parse::<LaunchEnvironment>()
+                .unwrap(),
+            environment
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        for source in [
+            "set WINEDEBUG",
+            "set MOTD=a\\tb",
+            "set MOTD=trailing\\",
+            "export WINEDEBUG=-all",
+            "clear all",
+        ] {
+            let error = source.parse::<LaunchEnvironment>().unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{source}");
+        }
+    }
+}
@@ -0,0 +1,145 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    ffi::OsStr,
+    io,
+    process::{self, ExitStatus},
+};
+
+use crate::{DebugRules, Prefix, ResolvedCommand, WineCommand, retry, trace};
+
+/// A [`WineCommand`] resolved once, so that launching many programs with the
+/// same options does not rebuild `WINEDEBUG` and the rest of the environment
+/// every time.
+#[derive(Debug, Clone)]
+pub struct Launcher<'a> {
+    prefix: &'a Prefix,
+    base: ResolvedCommand,
+    /// Where the program goes in `base.args`, after any wrappers and
+    /// `start`.
+    program_index: usize,
+}
+
+impl<'a> Launcher<'a> {
+    /// Captures everything about `command` except its program and arguments,
+    /// which each launch supplies.
+    pub fn new(command: &WineCommand<'a>) -> Self {
+        let base = command.resolve();
+        let program_index = base.args.len() - 1 - command.args.len();
+        Self {
+            prefix: command.prefix,
+            base,
+            program_index,
+        }
+    }
+
+    pub fn resolve<I, S>(
+        &self,
+        program: impl AsRef<OsStr>,
+        args: I,
+    ) -> ResolvedCommand
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let mut resolved = self.base.clone();
+        resolved.args.truncate(self.program_index);
+        resolved.args.push(program.as_ref().to_os_string());
+        resolved
+            .args
+            .extend(args.into_iter().map(|arg| arg.as_ref().to_os_string()));
+        resolved
+    }
+
+    pub fn spawn<I, S>(
+        &self,
+        program: impl AsRef<OsStr>,
+        args: I,
+    ) -> io::Result<process::Child>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let command = self.resolve(&program, args);
+        trace::operation(
+            "spawn",
+            &self.prefix.path,
+            Some(program.as_ref()),
+            || {
+                self.prefix.with_retry(
+                    || self.prefix.runner.0.spawn(&command),
+                    |_| false,
+                )
+            },
+        )
+    }
+
+    pub fn status<I, S>(
+        &self,
+        program: impl AsRef<OsStr>,
+        args: I,
+    ) -> io::Result<ExitStatus>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let command = self.resolve(&program, args);
+        trace::operation(
+            "status",
+            &self.prefix.path,
+            Some(program.as_ref()),
+            || {
+                self.prefix.with_retry(
+                    || self.prefix.runner.0.status(&command),
+                    |_| false,
+                )
+            },
+        )
+    }
+
+    pub fn output<I, S>(
+        &self,
+        program: impl AsRef<OsStr>,
+        args: I,
+    ) -> io::Result<process::Output>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let command = self.resolve(&program, args);
+        trace::operation(
+            "output",
+            &self.prefix.path,
+            Some(program.as_ref()),
+            || {
+                self.prefix.with_retry(
+                    || self.prefix.runner.0.output(&command),
+                    retry::is_transient_output,
+                )
+            },
+        )
+    }
+}
+
+impl<'a> From<&WineCommand<'a>> for Launcher<'a> {
+    fn from(command: &WineCommand<'a>) -> Self {
+        Self::new(command)
+    }
+}
+
+impl Prefix {
+    /// A [`Launcher`] for programs run with `debug_rules` and the prefix
+    /// defaults otherwise.
+    pub fn with_debug_rules<'a>(
+        &'a self,
+        debug_rules: &DebugRules<'a>,
+    ) -> Launcher<'a> {
+        let mut command = self.wine_command("");
+        command.debug_rules(debug_rules);
+        Launcher::new(&command)
+    }
+}
@@ -5,12 +5,159 @@
 // obtain one at https://mozilla.org/MPL/2.0/.
 
 use std::{
+    env,
     ffi::{OsStr, OsString},
-    io,
-    path::{Path, PathBuf},
+    fmt, io,
+    os::unix::ffi::{OsStrExt, OsStringExt},
+    path::{self, Path, PathBuf},
     process::{self, Command},
+    sync::Arc,
 };
 
+pub use audio::AudioServer;
+pub use automation::MouseButton;
+pub use batch::{BatchResult, PrefixBatch};
+pub use builder::PrefixBuilder;
+pub use cancel::CancelHandle;
+pub use clock::FakeClock;
+pub use codepage::Codepage;
+pub use command::{
+    ConsoleBackend, EnvironmentPolicy, KillOnDrop, Loader, Priority,
+    REQUIRED_HOST_VARS, ResolvedCommand, StartMode, WineCommand,
+};
+pub use defaults::Defaults;
+pub use display::{DisplayServer, Gamescope, Upscaler, VirtualDisplay};
+pub use dll_overrides::{DllOverride, DllOverrideMode};
+pub use drives::{Drive, DriveType, MountedCdRom};
+pub use error::Error;
+pub use events::PrefixEvent;
+pub use flavor::WineFlavor;
+pub use fonts::InstalledFont;
+pub use graphics::{DxvkHudElement, GraphicsEnvironment};
+pub use hooks::{PostExitHook, PreLaunchHook};
+pub use idle::IdleShutdown;
+pub use installation::{VersionMismatch, WineInstallation};
+pub use installer::{Installer, InstallerKind};
+pub use interactive::InteractiveChild;
+pub use keyboard::{ImeInputStyle, KeyboardLayout};
+pub use kill::{KillAllHandle, KillSignal};
+pub use launch_environment::{EnvironmentChange, LaunchEnvironment};
+pub use launcher::Launcher;
+pub use limits::ResourceLimits;
+pub use lock::PrefixLock;
+pub use manifest::{MANIFEST_AREAS, Manifest, ManifestReport};
+pub use output::WineExit;
+pub use pool::{PooledPrefix, PrefixPool};
+pub use processes::GuestProcess;
+pub use profile::Profile;
+pub use progress::{Progress, ProgressUpdate};
+pub use proxy::{Proxy, ProxyKind};
+pub use recording::LaunchRecording;
+pub use retry::RetryPolicy;
+pub use runner::{CommandRunner, SystemRunner};
+pub use script::Script;
+#[cfg(feature = "json")]
+pub use serialize::ToJson;
+pub use server::{
+    Persistence, ServerLog, ServerOptions, ServerProcess, StopStage,
+};
+pub use services::{GuestService, ServiceStartType};
+pub use shell::FileAssociation;
+pub use shell_folders::{KnownFolder, ShellFolder, ShellFolderTarget};
+pub use shortcut::{Shortcut, ShortcutLocation};
+pub use store::PrefixStore;
+pub use stream::{OutputLine, WineChild};
+pub use timing::LaunchTimings;
+pub use usage::DiskUsage;
+pub use verify::{PrefixIssue, REGISTRY_FILES, VerifyReport};
+pub use watch::{InstallWatcher, InstalledFile, InstalledFileKind};
+pub use watchdog::{Hang, Watchdog};
+pub use window::WineWindow;
+pub use wow64::Wow64Mode;
+
+mod audio;
+mod automation;
+mod batch;
+mod boot;
+mod builder;
+mod builtins;
+mod cancel;
+mod certificates;
+mod clipboard;
+mod clock;
+mod codepage;
+mod command;
+pub mod crash;
+pub mod crossover;
+mod defaults;
+pub mod diagnostics;
+mod display;
+mod dll_overrides;
+mod drives;
+pub mod dxvk;
+mod error;
+mod events;
+mod flavor;
+mod fonts;
+pub mod gptk;
+mod graphics;
+mod guest_path;
+mod hooks;
+pub mod icon;
+mod idle;
+pub mod import;
+mod installation;
+mod installer;
+mod interactive;
+mod keyboard;
+mod kill;
+mod launch_environment;
+mod launcher;
+mod limits;
+mod lock;
+mod manifest;
+mod output;
+mod pdb;
+pub mod pe;
+mod pool;
+mod processes;
+mod profile;
+mod progress;
+pub mod proton;
+mod proxy;
+mod recording;
+pub mod registry;
+mod relocate;
+mod retry;
+mod runner;
+pub mod runtimes;
+mod sandbox;
+mod screenshot;
+mod script;
+mod serialize;
+mod server;
+mod services;
+pub mod settings;
+mod sha1;
+mod sha256;
+mod shell;
+mod shell_folders;
+mod shortcut;
+mod snapshot;
+mod store;
+mod stream;
+mod timing;
+mod trace;
+mod tree;
+mod usage;
+#[cfg(feature = "camino")]
+mod utf8;
+mod verify;
+mod watch;
+mod watchdog;
+mod window;
+mod wow64;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DebugClass {
     Trace,
@@ -20,16 +167,34 @@ pub enum DebugClass {
 }
 
 impl DebugClass {
-    pub fn as_os_str(&self) -> &OsStr {
-        OsStr::new(match self {
+    /// Inverse of [`DebugClass::as_os_str`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "trace" => Some(Self::Trace),
+            "warn" => Some(Self::Warn),
+            "err" => Some(Self::Error),
+            "fixme" => Some(Self::Fixme),
+            _ => None,
+        }
+    }
+
+    /// The spelling used by `WINEDEBUG` and the lines Wine prints.
+    pub fn as_str(&self) -> &'static str {
+        match self {
             Self::Trace => "trace",
             Self::Warn => "warn",
             Self::Error => "err",
             Self::Fixme => "fixme",
-        })
+        }
+    }
+
+    pub fn as_os_str(&self) -> &OsStr {
+        OsStr::new(self.as_str())
     }
 }
 
+serialize::serialize_as_str!(DebugClass, as_str);
+
 // $ rg -g '*.c' -g '*.h' '^.*WINE_(DEFAULT|DECLARE)_DEBUG_CHANNEL\(([^)]+)\).*'
 // -or '$2' --no-filename dlls/ programs/ |  awk '!seen[$0]++'
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -51,6 +216,29 @@ pub enum DebugChannel<'a> {
     Other(&'a str),
 }
 
+impl<'a> DebugChannel<'a> {
+    /// Inverse of [`DebugChannel::as_os_str`].
+    pub fn from_name(name: &'a str) -> Self {
+        match name {
+            "all" => Self::All,
+            "heap" => Self::Heap,
+            "loaddll" => Self::LoadDll,
+            "module" => Self::Module,
+            "pid" => Self::Pid,
+            "relay" => Self::Relay,
+            "seh" => Self::Seh,
+            "server" => Self::Server,
+            "snoop" => Self::Snoop,
+            "synchronous" => Self::Synchronous,
+            "timestamp" => Self::Timestamp,
+            "fps" => Self::Fps,
+            "debugstr" => Self::DebugString,
+            "threadname" => Self::ThreadName,
+            other => Self::Other(other),
+        }
+    }
+}
+
 impl DebugChannel<'_> {
     pub fn as_os_str(&self) -> &OsStr {
         OsStr::new(match self {
@@ -91,12 +279,12 @@ impl<'a> DebugRules<'a> {
         Self::default()
     }
 
-    pub fn add(&'a mut self, rule: DebugRule<'a>) -> &'a mut Self {
+    pub fn add(&mut self, rule: DebugRule<'a>) -> &mut Self {
         self.rules.push(rule);
         self
     }
 
-    pub fn enable(&'a mut self, channel: DebugChannel<'a>) -> &'a mut Self {
+    pub fn enable(&mut self, channel: DebugChannel<'a>) -> &mut Self {
         self.rules.push(DebugRule {
             process: None,
             class: None,
@@ -106,7 +294,7 @@ impl<'a> DebugRules<'a> {
         self
     }
 
-    pub fn disable(&'a mut self, channel: DebugChannel<'a>) -> &'a mut Self {
+    pub fn disable(&mut self, channel: DebugChannel<'a>) -> &mut Self {
         self.rules.push(DebugRule {
             process: None,
             class: None,
@@ -115,6 +303,185 @@ impl<'a> DebugRules<'a> {
         });
         self
     }
+
+    /// Appends the rules of `other`, which take precedence over these.
+    pub fn merge(&mut self, other: DebugRules<'a>) -> &mut Self {
+        self.extend(other);
+        self
+    }
+
+    /// Removes rules overridden by a later rule for the same process, class,
+    /// and channel, keeping the later rule in its position.
+    pub fn normalize(&mut self) -> &mut Self {
+        let mut rules = Vec::with_capacity(self.rules.len());
+        for (i, rule) in self.rules.iter().enumerate() {
+            let is_overridden = self.rules[i + 1..].iter().any(|later| {
+                later.process == rule.process
+                    && later.class == rule.class
+                    && later.channel == rule.channel
+            });
+            if !is_overridden {
+                rules.push(rule.clone());
+            }
+        }
+        self.rules = rules;
+        self
+    }
+
+    /// Parses a `WINEDEBUG` value such as `warn+heap,-relay,app.exe:+seh`.
+    pub fn parse(value: &'a str) -> Result<Self, DebugRuleIssue> {
+        let mut rules = Self::new();
+        for option in value.split(',').filter(|option| !option.is_empty()) {
+            let (process, option) = match option.split_once(':') {
+                Some((process, option)) => (Some(OsStr::new(process)), option),
+                None => (None, option),
+            };
+            let (class, sign, channel) = match option.find(['+', '-']) {
+                Some(index) => (
+                    &option[..index],
+                    &option[index..index + 1],
+                    &option[index + 1..],
+                ),
+                // Wine treats a bare channel name as enabling it.
+                None => ("", "+", option),
+            };
+            let class = match class {
+                "" => None,
+                class => {
+                    Some(DebugClass::from_name(class).ok_or_else(|| {
+                        DebugRuleIssue::UnknownClass(class.to_owned())
+                    })?)
+                }
+            };
+            if !is_valid_channel_name(channel) {
+                return Err(DebugRuleIssue::InvalidChannel(channel.to_owned()));
+            }
+            rules.add(DebugRule {
+                process,
+                class,
+                channel: DebugChannel::from_name(channel),
+                is_enabled: sign == "+",
+            });
+        }
+        Ok(rules)
+    }
+
+    /// Reports the rules that would not render to a meaningful `WINEDEBUG`
+    /// value.
+    pub fn validate(&self) -> Vec<DebugRuleIssue> {
+        let mut issues = Vec::new();
+        for rule in &self.rules {
+            if let Some(process) = rule.process
+                && (process.is_empty()
+                    || process
+                        .as_bytes()
+                        .iter()
+                        .any(|byte| b",:".contains(byte)))
+            {
+                issues.push(DebugRuleIssue::InvalidProcess(process.to_owned()));
+            }
+            if let DebugChannel::Other(channel) = rule.channel {
+                if let Some((class, _)) = channel.split_once(['+', '-', ':'])
+                    && !class.is_empty()
+                    && DebugClass::from_name(class).is_none()
+                {
+                    issues.push(DebugRuleIssue::UnknownClass(class.to_owned()));
+                } else if !is_valid_channel_name(channel) {
+                    issues.push(DebugRuleIssue::InvalidChannel(
+                        channel.to_owned(),
+                    ));
+                }
+            }
+        }
+        issues
+    }
+}
+
+fn is_valid_channel_name(channel: &str) -> bool {
+    !channel.is_empty()
+        && channel
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'_')
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DebugRuleIssue {
+    /// A class prefix other than `trace`, `warn`, `err`, or `fixme`.
+    UnknownClass(String),
+    InvalidChannel(String),
+    InvalidProcess(OsString),
+}
+
+impl fmt::Display for DebugRuleIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownClass(class) => {
+                write!(f, "unknown debug class `{class}`")
+            }
+            Self::InvalidChannel(channel) => {
+                write!(f, "invalid debug channel `{channel}`")
+            }
+            Self::InvalidProcess(process) => {
+                write!(f, "invalid debug process `{}`", process.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for DebugRuleIssue {}
+
+impl DebugRules<'_> {
+    /// Renders the rules in the `WINEDEBUG` syntax.
+    pub fn to_env_value(&self) -> OsString {
+        let mut debug_env_value = OsString::new();
+        for (i, debug_rule) in self.rules.iter().enumerate() {
+            if i > 0 {
+                debug_env_value.push(",");
+            }
+            if let Some(process) = &debug_rule.process {
+                debug_env_value.push(process);
+                debug_env_value.push(":");
+            }
+            if let Some(class) = &debug_rule.class {
+                debug_env_value.push(class.as_os_str());
+            }
+            debug_env_value.push(if debug_rule.is_enabled { "+" } else { "-" });
+            debug_env_value.push(debug_rule.channel.as_os_str());
+        }
+        debug_env_value
+    }
+}
+
+impl<'a> FromIterator<DebugRule<'a>> for DebugRules<'a> {
+    fn from_iter<I: IntoIterator<Item = DebugRule<'a>>>(iter: I) -> Self {
+        Self {
+            rules: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<'a> Extend<DebugRule<'a>> for DebugRules<'a> {
+    fn extend<I: IntoIterator<Item = DebugRule<'a>>>(&mut self, iter: I) {
+        self.rules.extend(iter);
+    }
+}
+
+impl<'a> IntoIterator for DebugRules<'a> {
+    type Item = DebugRule<'a>;
+    type IntoIter = std::vec::IntoIter<DebugRule<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.rules.into_iter()
+    }
+}
+
+impl<'a, 'b> IntoIterator for &'b DebugRules<'a> {
+    type Item = &'b DebugRule<'a>;
+    type IntoIter = std::slice::Iter<'b, DebugRule<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.rules.iter()
+    }
 }
 
 impl<'a> AsRef<DebugRules<'a>> for DebugRules<'a> {
@@ -123,10 +490,157 @@ impl<'a> AsRef<DebugRules<'a>> for DebugRules<'a> {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Locale {
+    EnglishUs,
+    EnglishUk,
+    German,
+    French,
+    Spanish,
+    Italian,
+    PortugueseBrazil,
+    Russian,
+    Polish,
+    Japanese,
+    Korean,
+    ChineseSimplified,
+    ChineseTraditional,
+    /// A POSIX locale name such as `nl_NL.UTF-8`.
+    Other(OsString),
+}
+
+impl Locale {
+    pub fn as_os_str(&self) -> &OsStr {
+        match self {
+            Self::Other(other) => other,
+            Self::EnglishUs => "en_US.UTF-8".as_ref(),
+            Self::EnglishUk => "en_GB.UTF-8".as_ref(),
+            Self::German => "de_DE.UTF-8".as_ref(),
+            Self::French => "fr_FR.UTF-8".as_ref(),
+            Self::Spanish => "es_ES.UTF-8".as_ref(),
+            Self::Italian => "it_IT.UTF-8".as_ref(),
+            Self::PortugueseBrazil => "pt_BR.UTF-8".as_ref(),
+            Self::Russian => "ru_RU.UTF-8".as_ref(),
+            Self::Polish => "pl_PL.UTF-8".as_ref(),
+            Self::Japanese => "ja_JP.UTF-8".as_ref(),
+            Self::Korean => "ko_KR.UTF-8".as_ref(),
+            Self::ChineseSimplified => "zh_CN.UTF-8".as_ref(),
+            Self::ChineseTraditional => "zh_TW.UTF-8".as_ref(),
+        }
+    }
+}
+
+/// The processors reported to Windows programs through `WINE_CPU_TOPOLOGY`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CpuTopology {
+    pub cpus: Vec<u32>,
+}
+
+impl CpuTopology {
+    /// Exposes host processors `0..count`.
+    pub fn first(count: u32) -> Self {
+        Self {
+            cpus: (0..count).collect(),
+        }
+    }
+
+    pub fn to_env_value(&self) -> String {
+        let cpus = self
+            .cpus
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+        format!("{}:{cpus}", self.cpus.len())
+    }
+}
+
+// The loader consults a different variable for fallback library lookup on each
+// platform.
+#[cfg(target_os = "macos")]
+pub const DYNAMIC_LIBRARY_PATH_VAR: &str = "DYLD_FALLBACK_LIBRARY_PATH";
+#[cfg(not(target_os = "macos"))]
+pub const DYNAMIC_LIBRARY_PATH_VAR: &str = "LD_LIBRARY_PATH";
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum WinePathEntry {
+    /// A directory on the host, reached through Wine's `Z:` drive.
+    Host(PathBuf),
+    /// A path already in Windows form, e.g. `C:\tools`.
+    Windows(OsString),
+}
+
+impl WinePathEntry {
+    pub fn to_windows_path(&self) -> OsString {
+        match self {
+            Self::Host(path) => host_to_windows_path(path),
+            Self::Windows(path) => path.clone(),
+        }
+    }
+}
+
+fn host_to_windows_path(path: &Path) -> OsString {
+    let path = path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut windows_path = b"Z:".to_vec();
+    windows_path.extend(
+        path.as_os_str()
+            .as_bytes()
+            .iter()
+            .map(|&byte| if byte == b'/' { b'\\' } else { byte }),
+    );
+    OsString::from_vec(windows_path)
+}
+
 #[derive(Debug, Default, PartialEq, Eq, Hash, Clone)]
 pub struct PrefixConfig {
     pub esync: bool,
     pub msync: bool,
+    pub fsync: bool,
+    /// Overrides the flavor detected from the layout of the installation,
+    /// which decides how the sync options above are spelled.
+    pub flavor: Option<WineFlavor>,
+    /// Lets 32-bit programs address 4GiB of memory.
+    pub large_address_aware: bool,
+    pub simulate_writecopy: bool,
+    pub staging_shared_memory: bool,
+    pub cpu_topology: Option<CpuTopology>,
+    /// Keep [`Prefix::initialize`] from offering to download wine-mono and
+    /// wine-gecko.
+    pub skip_mono_gecko_install: bool,
+    /// Append the prefix's dynamic library paths to the value inherited from
+    /// the current environment instead of replacing it.
+    pub inherit_dynamic_library_paths: bool,
+    /// Directories prepended to the Windows `PATH` through `WINEPATH`.
+    pub wine_path: Vec<WinePathEntry>,
+    /// Additional variables set on every launched command, after the ones
+    /// derived from the options above.
+    pub extra_env: Vec<(OsString, OsString)>,
+    /// Wine derives the guest language and codepages from `LANG`/`LC_ALL`.
+    pub locale: Option<Locale>,
+    /// Sets the `*_proxy` variables on every launch, which Wine falls back
+    /// to when the registry configures no proxy, see [`Prefix::set_proxy`].
+    pub proxy: Option<Proxy>,
+    /// Sets `TZ` on every launch, e.g., `Europe/Berlin`, which Wine derives
+    /// the guest time zone from.
+    pub time_zone: Option<String>,
+    /// Applied through `WINEDLLOVERRIDES` on every launch, unlike
+    /// [`Prefix::set_dll_override`], which persists in the registry.
+    pub dll_overrides: Vec<DllOverride>,
+    /// Set when the Wine build is Apple's Game Porting Toolkit.
+    pub gptk: Option<gptk::GptkConfig>,
+    /// Retries launches that race wineserver startup or a prefix update.
+    pub retry: Option<RetryPolicy>,
+    /// Makes registry edits, installs, and `wineboot` runs wait for each
+    /// other through [`Prefix::lock`], since overlapping ones corrupt the
+    /// prefix. Program launches are not held up.
+    pub serialize_mutations: bool,
+    /// Makes launches run [`Prefix::initialize`] first if the prefix has not
+    /// been, reporting [`PrefixEvent::InitializingPrefix`], instead of
+    /// failing with loader errors in a half-created prefix.
+    pub auto_initialize: bool,
+    /// The host variables that launches and the wineserver see, falling back
+    /// to [`Defaults::environment`].
+    pub environment: Option<EnvironmentPolicy>,
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
@@ -135,105 +649,323 @@ pub struct Prefix {
     dynamic_library_paths: OsString,
 
     wine: OsString,
+    wine64: OsString,
     wineserver: OsString,
     regedit: OsString,
+    flavor: WineFlavor,
 
     config: PrefixConfig,
+    runner: runner::Runner,
+    hooks: hooks::Hooks,
 }
 
 impl Prefix {
+    /// Like [`Prefix::try_at`], but panics if there is no loader.
     pub fn at(
         path: impl AsRef<Path>,
         dynamic_library_paths: impl IntoIterator<Item = impl AsRef<Path>>,
         config: PrefixConfig,
     ) -> Self {
-        let path = path.as_ref().to_path_buf();
-        let wine: OsString = path.join("bin/wine").into();
-        let wineserver = path.join("bin/wineserver").into();
-        let regedit = path.join("bin/regedit").into();
-
-        assert!(
-            Path::new(&wine).is_file(),
-            "Invalid prefix (todo better error handling)"
-        );
+        Self::try_at(path, dynamic_library_paths, config)
+            .expect("Invalid prefix")
+    }
 
-        Self {
-            path,
-            dynamic_library_paths: dynamic_library_paths
-                .into_iter()
-                .enumerate()
-                .fold(OsString::new(), |mut acc, (i, into_cow)| {
-                    if i > 0 {
-                        acc.push(":");
-                    }
-                    acc.push(into_cow.as_ref());
-                    acc
-                }),
-            wine,
-            wineserver,
-            regedit,
-            config,
+    /// Fails with [`Error::WineNotFound`] unless the prefix has a loader at
+    /// `bin/wine`.
+    pub fn try_at(
+        path: impl AsRef<Path>,
+        dynamic_library_paths: impl IntoIterator<Item = impl AsRef<Path>>,
+        config: PrefixConfig,
+    ) -> io::Result<Self> {
+        let path = path.as_ref();
+        let wine = path.join("bin/wine");
+        if !wine.is_file() {
+            return Err(Error::WineNotFound(wine).into());
         }
+
+        let mut builder = PrefixBuilder::new(path);
+        builder
+            .dynamic_library_paths(dynamic_library_paths)
+            .config(config);
+        Ok(builder.build())
     }
 
+    /// Applies [`PrefixConfig::auto_initialize`] right away, since the
+    /// returned command is launched outside this crate, failing if the
+    /// prefix cannot be initialized.
     pub fn command<'b>(
         &'b self,
         use_start_exe: bool,
         program: impl AsRef<OsStr>,
         debug_rules: impl AsRef<DebugRules<'b>>,
-    ) -> Command {
-        let debug_rules = debug_rules.as_ref();
-        let mut command = Command::new(&self.wine);
-
-        command.current_dir(&self.path);
-
-        command.envs([
-            ("WINEPREFIX", self.path.as_os_str()),
-            ("DYLD_FALLBACK_LIBRARY_PATH", &self.dynamic_library_paths),
-        ]);
-        if self.config.esync {
-            command.env("ESYNC", "1");
-        }
-        if self.config.msync {
-            command.env("MSYNC", "1");
-        }
-        if !debug_rules.rules.is_empty() {
-            let mut debug_env_value = OsString::new();
-            for (i, debug_rule) in debug_rules.rules.iter().enumerate() {
-                if i > 0 {
-                    debug_env_value.push(",");
-                }
-                if let Some(process) = &debug_rule.process {
-                    debug_env_value.push(process);
-                    debug_env_value.push(":");
-                }
-                if let Some(class) = &debug_rule.class {
-                    debug_env_value.push(class.as_os_str());
-                    debug_env_value.push(":");
+    ) -> io::Result<Command> {
+        self.initialize_for_launch(program.as_ref())?;
+        Ok(WineCommand::new(self, program)
+            .start_mode(if use_start_exe {
+                StartMode::StartExe
+            } else {
+                StartMode::Direct
+            })
+            .debug_rules(debug_rules)
+            .to_command())
+    }
+
+    /// Routes every process this prefix launches through `runner`.
+    pub fn with_runner(mut self, runner: impl CommandRunner + 'static) -> Self {
+        self.runner = runner::Runner(Arc::new(runner));
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn flavor(&self) -> WineFlavor {
+        if let Some(flavor) = self.config.flavor {
+            flavor
+        } else if self.config.gptk.is_some() {
+            WineFlavor::GamePortingToolkit
+        } else {
+            self.flavor
+        }
+    }
+
+    pub fn drive_c(&self) -> PathBuf {
+        self.path.join("drive_c")
+    }
+
+    /// Whether the prefix was created with `WINEARCH=win64`, i.e., whether it
+    /// has a separate directory for 32-bit system libraries.
+    pub fn is_64_bit(&self) -> bool {
+        self.drive_c().join("windows/syswow64").is_dir()
+    }
+
+    /// The directory that the Wine binaries were installed into, which contains
+    /// `bin/` and `share/wine/`.
+    pub(crate) fn install_root(&self) -> &Path {
+        let wine = Path::new(&self.wine);
+        wine.parent().and_then(Path::parent).unwrap_or(wine)
+    }
+
+    /// Directory inside the prefix where this crate keeps its own state.
+    pub(crate) fn metadata_dir(&self) -> PathBuf {
+        self.path.join(".wine-rs")
+    }
+
+    pub fn wine_command(&self, program: impl AsRef<OsStr>) -> WineCommand<'_> {
+        WineCommand::new(self, program)
+    }
+
+    /// [`Prefix::wine_command`] with `args` already passed, which can be
+    /// followed by [`WineCommand::host_path_arg`] for paths on the host.
+    pub fn wine_command_with_args(
+        &self,
+        program: impl AsRef<OsStr>,
+        args: impl IntoIterator<Item = impl AsRef<OsStr>>,
+    ) -> WineCommand<'_> {
+        let mut command = WineCommand::new(self, program);
+        command.args(args);
+        command
+    }
+
+    fn dynamic_library_paths(&self) -> OsString {
+        let own = if self.dynamic_library_paths.is_empty() {
+            Defaults::with(|defaults| {
+                env::join_paths(&defaults.dynamic_library_paths)
+                    .unwrap_or_default()
+            })
+        } else {
+            self.dynamic_library_paths.clone()
+        };
+        if !self.config.inherit_dynamic_library_paths {
+            return own;
+        }
+        match env::var_os(DYNAMIC_LIBRARY_PATH_VAR) {
+            Some(mut inherited) if !inherited.is_empty() => {
+                if !own.is_empty() {
+                    inherited.push(":");
+                    inherited.push(&own);
                 }
-                debug_env_value.push(if debug_rule.is_enabled {
-                    "+"
-                } else {
-                    "-"
-                });
-                debug_env_value.push(debug_rule.channel.as_os_str());
+                inherited
             }
-            command.env("WINEDEBUG", debug_env_value);
+            _ => own,
         }
+    }
 
-        if use_start_exe {
-            command.arg("start");
+    /// Runs a builtin program to completion, turning a failed exit status into
+    /// an error carrying its stderr.
+    pub(crate) fn run_builtin(
+        &self,
+        program: &str,
+        args: impl IntoIterator<Item = impl AsRef<OsStr>>,
+    ) -> io::Result<process::Output> {
+        self.builtin_command(program).args(args).output_checked()
+    }
+
+    /// [`Prefix::wine_command`] for a program this crate runs and waits on
+    /// itself, which runs directly whatever [`Defaults::start_mode`] says.
+    pub(crate) fn builtin_command(
+        &self,
+        program: impl AsRef<OsStr>,
+    ) -> WineCommand<'_> {
+        let mut command = WineCommand::new(self, program);
+        command.start_mode(StartMode::Direct);
+        command
+    }
+
+    /// The host variables that processes of this prefix see.
+    pub(crate) fn environment_policy(&self) -> EnvironmentPolicy {
+        match &self.config.environment {
+            Some(environment) => environment.clone(),
+            None => Defaults::with(|defaults| defaults.environment.clone()),
         }
-        command.arg(program);
+    }
 
+    /// An invocation of the wineserver of this prefix with `arg`.
+    pub(crate) fn wineserver_command(&self, arg: &str) -> ResolvedCommand {
+        let mut command = ResolvedCommand::new(&self.wineserver, &self.path);
+        self.environment_policy().apply(&mut command);
+        command.args.push(arg.into());
+        command
+            .envs
+            .push(("WINEPREFIX".into(), self.path.as_os_str().to_os_string()));
         command
     }
 
+    pub(crate) fn kill_all_command(&self) -> ResolvedCommand {
+        self.wineserver_command("-k")
+    }
+
     pub fn kill_all(&self) -> io::Result<process::Output> {
-        Command::new(&self.wineserver)
-            .current_dir(&self.path)
-            .env("WINEPREFIX", self.path.as_os_str())
-            .arg("-k")
-            .output()
+        let command = self.kill_all_command();
+        trace::operation("kill_all", &self.path, Some(&self.wineserver), || {
+            self.runner.0.output(&command)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, ffi::OsStr, fs, process};
+
+    use crate::{
+        DebugChannel, DebugRule, DebugRuleIssue, DebugRules, PrefixBuilder,
+        PrefixConfig, runner::RecordingRunner,
+    };
+
+    #[test]
+    fn command_fails_when_auto_initialization_does() {
+        let path = env::temp_dir()
+            .join(format!("wine-rs-command-test-{}", process::id()));
+        let prefix = PrefixBuilder::new(&path)
+            .config(PrefixConfig {
+                auto_initialize: true,
+                ..Default::default()
+            })
+            .build();
+        assert!(prefix.command(false, "notepad", DebugRules::new()).is_err());
+        let _ = fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn kill_all_runs_the_wineserver_through_the_runner() {
+        let (prefix, runner) =
+            RecordingRunner::attach(PrefixBuilder::new("/prefix").build());
+        assert!(prefix.kill_all().unwrap().status.success());
+
+        let recorded = runner.take();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].program, "/prefix/bin/wineserver");
+        assert_eq!(recorded[0].args, ["-k"]);
+        assert_eq!(recorded[0].env("WINEPREFIX"), Some(OsStr::new("/prefix")));
+    }
+
+    #[test]
+    fn wine_command_with_args_passes_them_after_the_program() {
+        let prefix = PrefixBuilder::new("/prefix").build();
+        let resolved = prefix
+            .wine_command_with_args("regedit", ["/S", "patch.reg"])
+            .resolve();
+        assert_eq!(resolved.program, "/prefix/bin/wine");
+        assert_eq!(resolved.args, ["regedit", "/S", "patch.reg"]);
+    }
+
+    #[test]
+    fn debug_rules_parse_and_render() {
+        let rules = DebugRules::parse("warn+heap,-relay,app.exe:+seh,loaddll,")
+            .unwrap();
+        assert_eq!(rules.rules.len(), 4);
+        assert_eq!(rules.rules[2].process, Some(OsStr::new("app.exe")));
+        assert_eq!(rules.rules[3].channel, DebugChannel::LoadDll);
+        assert!(rules.rules[3].is_enabled);
+        assert_eq!(
+            rules.to_env_value(),
+            "warn+heap,-relay,app.exe:+seh,+loaddll"
+        );
+    }
+
+    #[test]
+    fn debug_rules_later_all_overrides_earlier_all() {
+        let mut rules = DebugRules::parse("+all,+relay,-all").unwrap();
+        assert_eq!(rules.rules[0].channel, DebugChannel::All);
+        rules.normalize();
+        assert_eq!(rules.to_env_value(), "+relay,-all");
+    }
+
+    #[test]
+    fn debug_rules_merge_then_normalize_keeps_the_later_rule() {
+        let mut rules = DebugRules::parse("+relay,-heap,warn+heap").unwrap();
+        rules.merge(DebugRules::parse("-relay,app.exe:+heap").unwrap());
+        rules.normalize();
+        assert_eq!(
+            rules.to_env_value(),
+            "-heap,warn+heap,-relay,app.exe:+heap"
+        );
+    }
+
+    #[test]
+    fn debug_rules_parse_rejects_bad_options() {
+        assert_eq!(
+            DebugRules::parse("bogus+heap"),
+            Err(DebugRuleIssue::UnknownClass("bogus".to_owned()))
+        );
+        assert_eq!(
+            DebugRules::parse("warn+"),
+            Err(DebugRuleIssue::InvalidChannel(String::new()))
+        );
+        assert_eq!(
+            DebugRules::parse("+he$p"),
+            Err(DebugRuleIssue::InvalidChannel("he$p".to_owned()))
+        );
+    }
+
+    #[test]
+    fn debug_rules_validate_reports_each_bad_rule() {
+        let rule = |process, channel| DebugRule {
+            process,
+            class: None,
+            channel: DebugChannel::Other(channel),
+            is_enabled: true,
+        };
+        let mut rules = DebugRules::new();
+        rules
+            .enable(DebugChannel::Heap)
+            .add(rule(None, "bogus+heap"))
+            .add(rule(None, "two words"))
+            .add(rule(Some(OsStr::new("a,b")), "relay"));
+        assert_eq!(
+            rules.validate(),
+            [
+                DebugRuleIssue::UnknownClass("bogus".to_owned()),
+                DebugRuleIssue::InvalidChannel("two words".to_owned()),
+                DebugRuleIssue::InvalidProcess("a,b".into()),
+            ]
+        );
+        assert!(
+            DebugRules::parse("warn+heap")
+                .unwrap()
+                .validate()
+                .is_empty()
+        );
     }
 }
@@ -0,0 +1,85 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::ffi::OsString;
+
+use crate::WineCommand;
+
+/// Caps on what a launched program and the processes it starts may use, so
+/// that a runaway Windows program cannot take down the host.
+///
+/// A wineserver that the launch starts is held to them too.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct ResourceLimits {
+    /// The memory of the whole process tree in bytes, enforced through a
+    /// transient systemd scope, which needs a user session of systemd. Linux
+    /// only: it is ignored elsewhere.
+    ///
+    /// Limiting the address space instead would break Wine, which reserves
+    /// most of it up front.
+    pub memory: Option<u64>,
+    /// The processes and threads of the tree, also through the systemd scope
+    /// and ignored outside Linux.
+    pub tasks: Option<u64>,
+    /// The open file descriptors of each process, i.e., `RLIMIT_NOFILE`.
+    pub open_files: Option<u64>,
+    /// Whether crashing processes may dump core. `None` keeps the limit
+    /// inherited from the caller.
+    pub core_dumps: Option<bool>,
+}
+
+impl ResourceLimits {
+    /// The programs and arguments to run the loader through.
+    pub(crate) fn wrapper(&self) -> Vec<OsString> {
+        let mut wrapper: Vec<OsString> = Vec::new();
+        if cfg!(target_os = "linux")
+            && (self.memory.is_some() || self.tasks.is_some())
+        {
+            wrapper.extend(
+                ["systemd-run", "--user", "--scope", "--quiet"].map(Into::into),
+            );
+            if let Some(memory) = self.memory {
+                wrapper.extend([
+                    "-p".into(),
+                    format!("MemoryMax={memory}").into(),
+                ]);
+            }
+            if let Some(tasks) = self.tasks {
+                wrapper
+                    .extend(["-p".into(), format!("TasksMax={tasks}").into()]);
+            }
+            wrapper.push("--".into());
+        }
+
+        let mut ulimits = Vec::new();
+        if let Some(open_files) = self.open_files {
+            ulimits.push(format!("ulimit -n {open_files}"));
+        }
+        match self.core_dumps {
+            Some(true) => ulimits.push("ulimit -c unlimited".into()),
+            Some(false) => ulimits.push("ulimit -c 0".into()),
+            None => {}
+        }
+        if !ulimits.is_empty() {
+            ulimits.push("exec \"$@\"".into());
+            // The `sh` after the script is its `$0`.
+            wrapper.extend([
+                "sh".into(),
+                "-c".into(),
+                ulimits.join(" && ").into(),
+                "sh".into(),
+            ]);
+        }
+        wrapper
+    }
+}
+
+impl WineCommand<'_> {
+    pub fn resource_limits(&mut self, limits: ResourceLimits) -> &mut Self {
+        self.resource_limits = limits;
+        self
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    fs::{self, File, TryLockError},
+    io,
+    path::PathBuf,
+    sync::Mutex,
+    thread::{self, ThreadId},
+};
+
+use crate::Prefix;
+
+/// The prefixes whose lock is held in this process, with the thread that
+/// took it, under which taking it again returns right away: a second `flock`
+/// on a new open file would wait on the first forever.
+static HELD: Mutex<Vec<(PathBuf, ThreadId)>> = Mutex::new(Vec::new());
+
+/// Exclusive advisory lock on a prefix, released on drop.
+///
+/// Only other users of this crate respect the lock; Wine itself does not.
+#[derive(Debug)]
+pub struct PrefixLock {
+    /// `None` if an outer lock taken on this thread holds the prefix.
+    held: Option<(File, PathBuf, ThreadId)>,
+}
+
+impl Drop for PrefixLock {
+    fn drop(&mut self) {
+        if let Some((_, path, thread)) = &self.held {
+            HELD.lock()
+                .unwrap()
+                .retain(|held| held.0 != *path || held.1 != *thread);
+        }
+    }
+}
+
+impl Prefix {
+    fn lock_file(&self) -> io::Result<File> {
+        let metadata_dir = self.metadata_dir();
+        fs::create_dir_all(&metadata_dir)?;
+        File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(metadata_dir.join("lock"))
+    }
+
+    /// An inner lock if this thread already holds the lock on the prefix.
+    fn reentrant_lock(&self) -> Option<PrefixLock> {
+        let thread = thread::current().id();
+        HELD.lock()
+            .unwrap()
+            .contains(&(self.path.clone(), thread))
+            .then_some(PrefixLock { held: None })
+    }
+
+    fn held_lock(&self, file: File) -> PrefixLock {
+        let thread = thread::current().id();
+        HELD.lock().unwrap().push((self.path.clone(), thread));
+        PrefixLock {
+            held: Some((file, self.path.clone(), thread)),
+        }
+    }
+
+    /// Blocks until no other process or thread holds the lock on the prefix.
+    /// The thread holding it can take it again, e.g., by calling
+    /// [`Prefix::update`] under it, which locks the prefix itself.
+    pub fn lock(&self) -> io::Result<PrefixLock> {
+        if let Some(lock) = self.reentrant_lock() {
+            return Ok(lock);
+        }
+        let file = self.lock_file()?;
+        file.lock()?;
+        Ok(self.held_lock(file))
+    }
+
+    /// Returns `None` if another process or thread holds the lock on the
+    /// prefix.
+    pub fn try_lock(&self) -> io::Result<Option<PrefixLock>> {
+        if let Some(lock) = self.reentrant_lock() {
+            return Ok(Some(lock));
+        }
+        let file = self.lock_file()?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(self.held_lock(file))),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(error)) => Err(error),
+        }
+    }
+
+    /// Takes [`Prefix::lock`] for an operation that modifies the prefix if
+    /// [`crate::PrefixConfig::serialize_mutations`] is set.
+    pub(crate) fn serialize_mutation(&self) -> io::Result<Option<PrefixLock>> {
+        if self.config.serialize_mutations {
+            self.lock().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process, thread};
+
+    use crate::PrefixBuilder;
+
+    #[test]
+    fn lock_is_reentrant_on_its_thread_only() {
+        let path = env::temp_dir()
+            .join(format!("wine-rs-lock-test-{}", process::id()));
+        fs::create_dir_all(&path).unwrap();
+        let prefix = PrefixBuilder::new(&path).build();
+
+        let outer = prefix.lock().unwrap();
+        let inner = prefix.lock().unwrap();
+        assert!(prefix.try_lock().unwrap().is_some());
+        thread::scope(|scope| {
+            scope.spawn(|| assert!(prefix.try_lock().unwrap().is_none()));
+        });
+        drop(inner);
+        thread::scope(|scope| {
+            scope.spawn(|| assert!(prefix.try_lock().unwrap().is_none()));
+        });
+        drop(outer);
+        thread::scope(|scope| {
+            scope.spawn(|| assert!(prefix.try_lock().unwrap().is_some()));
+        });
+
+        fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -0,0 +1,261 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{Prefix, serialize::serialize_fields, sha256};
+
+/// The directories, relative to the prefix, that [`Prefix::record_manifest`]
+/// hashes: those providing the DLLs and executables of Windows itself.
+pub const MANIFEST_AREAS: &[&str] = &[
+    "drive_c/windows/system32",
+    "drive_c/windows/syswow64",
+    "drive_c/windows/Fonts",
+];
+
+const MANIFEST_FILE: &str = "manifest.sha256";
+
+/// The SHA-256 of every file under some directories of a prefix.
+///
+/// It is stored in the format of `sha256sum`, with paths relative to the
+/// prefix, so it can also be checked with `sha256sum -c` from there.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Manifest {
+    /// Relative to the prefix.
+    pub files: BTreeMap<PathBuf, String>,
+}
+
+/// The differences between a prefix and a [`Manifest`] of it.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ManifestReport {
+    /// Relative to the prefix, as are the other lists.
+    pub modified: Vec<PathBuf>,
+    pub missing: Vec<PathBuf>,
+    /// Files under the recorded areas that the manifest does not list.
+    pub added: Vec<PathBuf>,
+}
+
+impl ManifestReport {
+    /// Whether every recorded file is unchanged, allowing for added ones.
+    pub fn is_intact(&self) -> bool {
+        self.modified.is_empty() && self.missing.is_empty()
+    }
+}
+
+serialize_fields!(ManifestReport {
+    modified,
+    missing,
+    added
+});
+
+/// The files under `dir`, following symlinks, in no particular order.
+/// Dangling symlinks and names that are not UTF-8 are skipped.
+fn files_under(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(error) => return Err(error),
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if path.to_str().is_none() {
+            continue;
+        }
+        if path.is_dir() {
+            files_under(&path, files)?;
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+impl Manifest {
+    /// Hashes the files under each of `areas`, which are relative to the
+    /// prefix.
+    pub fn record(
+        prefix: &Prefix,
+        areas: impl IntoIterator<Item = impl AsRef<Path>>,
+    ) -> io::Result<Self> {
+        let mut paths = Vec::new();
+        for area in areas {
+            files_under(&prefix.path.join(area), &mut paths)?;
+        }
+        let mut files = BTreeMap::new();
+        for path in paths {
+            let relative = path
+                .strip_prefix(&prefix.path)
+                .expect("found under the prefix")
+                .to_path_buf();
+            files.insert(relative, sha256::file_hex(&path)?);
+        }
+        Ok(Self { files })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    /// Compares the files of `prefix` with the manifest, looking for added
+    /// files only in `areas`.
+    pub fn verify(
+        &self,
+        prefix: &Prefix,
+        areas: impl IntoIterator<Item = impl AsRef<Path>>,
+    ) -> io::Result<ManifestReport> {
+        let mut report = ManifestReport::default();
+        for (relative, recorded) in &self.files {
+            let path = prefix.path.join(relative);
+            if !path.is_file() {
+                report.missing.push(relative.clone());
+            } else if !sha256::file_hex(&path)?.eq_ignore_ascii_case(recorded) {
+                report.modified.push(relative.clone());
+            }
+        }
+
+        let mut paths = Vec::new();
+        for area in areas {
+            files_under(&prefix.path.join(area), &mut paths)?;
+        }
+        report.added = paths
+            .into_iter()
+            .filter_map(|path| {
+                let relative = path.strip_prefix(&prefix.path).ok()?;
+                (!self.files.contains_key(relative))
+                    .then(|| relative.to_path_buf())
+            })
+            .collect();
+        report.added.sort();
+        Ok(report)
+    }
+}
+
+impl FromStr for Manifest {
+    type Err = io::Error;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let mut files = BTreeMap::new();
+        for (index, line) in source.lines().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            // `sha256sum` separates the path with a space and a mode marker.
+            let Some((sha256, path)) = line.split_once(' ') else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: expected `<sha256>  <path>`", index + 1),
+                ));
+            };
+            let path = path.strip_prefix([' ', '*']).unwrap_or(path);
+            files.insert(PathBuf::from(path), sha256.to_owned());
+        }
+        Ok(Self { files })
+    }
+}
+
+impl fmt::Display for Manifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (path, sha256) in &self.files {
+            writeln!(f, "{sha256}  {}", path.display())?;
+        }
+        Ok(())
+    }
+}
+
+impl Prefix {
+    fn manifest_path(&self) -> PathBuf {
+        self.metadata_dir().join(MANIFEST_FILE)
+    }
+
+    /// Records a [`Manifest`] of the [`MANIFEST_AREAS`] in the metadata
+    /// directory of the prefix, e.g., right after provisioning it, replacing
+    /// any recorded before.
+    pub fn record_manifest(&self) -> io::Result<Manifest> {
+        let manifest = Manifest::record(self, MANIFEST_AREAS)?;
+        fs::create_dir_all(self.metadata_dir())?;
+        manifest.save(self.manifest_path())?;
+        Ok(manifest)
+    }
+
+    /// Checks the prefix against the manifest saved by
+    /// [`Prefix::record_manifest`], which is helpful to rule out a corrupted
+    /// or tampered prefix before blaming Wine. Fails with
+    /// [`io::ErrorKind::NotFound`] if none was recorded.
+    pub fn verify_manifest(&self) -> io::Result<ManifestReport> {
+        Manifest::load(self.manifest_path())?.verify(self, MANIFEST_AREAS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, io, path::PathBuf, process};
+
+    use super::Manifest;
+    use crate::PrefixBuilder;
+
+    const ABC_SHA256: &str =
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    #[test]
+    fn parses_sha256sum_output() {
+        let manifest: Manifest = format!(
+            "{ABC_SHA256}  drive_c/windows/system32/a.dll\n\
+             \n\
+             {ABC_SHA256} *drive_c/windows/Fonts/b c.ttf\n"
+        )
+        .parse()
+        .unwrap();
+        assert_eq!(manifest.files.len(), 2);
+        assert_eq!(
+            manifest.files[&PathBuf::from("drive_c/windows/Fonts/b c.ttf")],
+            ABC_SHA256
+        );
+        assert_eq!(manifest.to_string().parse::<Manifest>().unwrap(), manifest);
+
+        let error = "no-separator".parse::<Manifest>().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn verify_reports_modified_missing_and_added_files() {
+        let path = env::temp_dir()
+            .join(format!("wine-rs-manifest-test-{}", process::id()));
+        let system32 = path.join("drive_c/windows/system32");
+        fs::create_dir_all(&system32).unwrap();
+        fs::write(system32.join("kept.dll"), "abc").unwrap();
+        fs::write(system32.join("modified.dll"), "abc").unwrap();
+        fs::write(system32.join("missing.dll"), "abc").unwrap();
+        let prefix = PrefixBuilder::new(&path).build();
+        let areas = ["drive_c/windows/system32"];
+
+        let manifest = Manifest::record(&prefix, areas).unwrap();
+        assert_eq!(
+            manifest.files[&PathBuf::from("drive_c/windows/system32/kept.dll")],
+            ABC_SHA256
+        );
+        fs::write(system32.join("modified.dll"), "abd").unwrap();
+        fs::remove_file(system32.join("missing.dll")).unwrap();
+        fs::write(system32.join("added.dll"), "abc").unwrap();
+        let report = manifest.verify(&prefix, areas).unwrap();
+        fs::remove_dir_all(&path).unwrap();
+
+        let relative =
+            |name| PathBuf::from("drive_c/windows/system32").join(name);
+        assert_eq!(report.modified, [relative("modified.dll")]);
+        assert_eq!(report.missing, [relative("missing.dll")]);
+        assert_eq!(report.added, [relative("added.dll")]);
+        assert!(!report.is_intact());
+    }
+}
@@ -0,0 +1,85 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{os::unix::process::ExitStatusExt, process::ExitStatus};
+
+use crate::diagnostics::DemuxedOutput;
+
+/// How a program run with [`crate::WineCommand::output_demuxed`] ended.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum WineExit {
+    Success,
+    /// The program exited with a nonzero code.
+    Failure(i32),
+    /// The loader never started the program, e.g., because it does not
+    /// exist, and said why on stderr.
+    LoadFailed(String),
+    /// A host signal ended the loader, e.g., through
+    /// [`crate::Prefix::kill_all`].
+    Signaled(i32),
+}
+
+impl WineExit {
+    pub(crate) fn interpret(status: ExitStatus, stderr: &str) -> Self {
+        if let Some(signal) = status.signal() {
+            return Self::Signaled(signal);
+        }
+        match status.code() {
+            Some(0) | None => Self::Success,
+            Some(code) => stderr
+                .lines()
+                .find(|line| line.starts_with("wine: "))
+                .map(|line| Self::LoadFailed(line.to_owned()))
+                .unwrap_or(Self::Failure(code)),
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        *self == Self::Success
+    }
+}
+
+impl DemuxedOutput {
+    /// [`DemuxedOutput::stdout`] decoded in [`DemuxedOutput::codepage`].
+    pub fn stdout_text(&self) -> String {
+        self.codepage.decode(&self.stdout)
+    }
+
+    /// [`DemuxedOutput::stderr`] decoded in [`DemuxedOutput::codepage`].
+    pub fn stderr_text(&self) -> String {
+        self.codepage.decode(&self.stderr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::process::ExitStatusExt;
+
+    use crate::{Codepage, PrefixBuilder, WineExit, runner::RecordingRunner};
+
+    #[test]
+    fn output_demuxed_interprets_the_exit() {
+        let (prefix, _) =
+            RecordingRunner::attach(PrefixBuilder::new("/prefix").build());
+        let output = prefix.wine_command("game.exe").output_demuxed().unwrap();
+        assert_eq!(output.exit, WineExit::Success);
+        assert_eq!(output.codepage, Codepage::UTF8);
+
+        let failed = std::process::ExitStatus::from_raw(53 << 8);
+        assert_eq!(
+            WineExit::interpret(
+                failed,
+                "err:module:foo\nwine: failed to open \"game.exe\"\n"
+            ),
+            WineExit::LoadFailed("wine: failed to open \"game.exe\"".into())
+        );
+        assert_eq!(WineExit::interpret(failed, ""), WineExit::Failure(53));
+        assert_eq!(
+            WineExit::interpret(std::process::ExitStatus::from_raw(9), ""),
+            WineExit::Signaled(9)
+        );
+    }
+}
@@ -0,0 +1,560 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Reading the functions and line numbers of a Program Database, the debug
+//! information of MSVC and `lld-link`, as laid out in the LLVM documentation
+//! of the format.
+
+use std::io;
+
+const MSF_MAGIC: &[u8; 32] = b"Microsoft C/C++ MSF 7.00\r\n\x1aDS\0\0\0";
+
+const PDB_STREAM: usize = 1;
+const DBI_STREAM: usize = 3;
+const NIL_STREAM: u16 = 0xffff;
+
+/// The index of the stream of section headers in the optional debug header
+/// of the DBI stream.
+const SECTION_HEADERS_DEBUG_STREAM: usize = 5;
+
+const S_PUB32: u16 = 0x110e;
+const S_LPROC32: u16 = 0x110f;
+const S_GPROC32: u16 = 0x1110;
+const S_LPROC32_ID: u16 = 0x1146;
+const S_GPROC32_ID: u16 = 0x1147;
+/// Set on publics that are functions rather than data.
+const CVPSF_FUNCTION: u32 = 0x2;
+
+const DEBUG_S_LINES: u32 = 0xf2;
+const DEBUG_S_FILECHKSMS: u32 = 0xf4;
+/// Set on line subsections that also record columns.
+const CV_LINES_HAVE_COLUMNS: u16 = 0x1;
+/// Line numbers at and above this mark compiler-generated code.
+const HIDDEN_LINE: u32 = 0xf0_0000;
+
+pub(crate) struct Function {
+    pub(crate) rva: u32,
+    /// Unknown for publics.
+    pub(crate) size: Option<u32>,
+    pub(crate) name: String,
+}
+
+pub(crate) struct Line {
+    pub(crate) rva: u32,
+    /// An index into [`Symbols::files`].
+    pub(crate) file: usize,
+    pub(crate) line: u32,
+}
+
+#[derive(Default)]
+pub(crate) struct Symbols {
+    pub(crate) functions: Vec<Function>,
+    pub(crate) lines: Vec<Line>,
+    pub(crate) files: Vec<String>,
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        bytes.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn usize_at(bytes: &[u8], offset: usize) -> Option<usize> {
+    u32_at(bytes, offset).map(|value| value as usize)
+}
+
+/// The NUL-terminated string at `offset`, and where it ends.
+fn str_at(bytes: &[u8], offset: usize) -> Option<(String, usize)> {
+    let rest = bytes.get(offset..)?;
+    let length = rest.iter().position(|&byte| byte == 0)?;
+    Some((
+        String::from_utf8_lossy(&rest[..length]).into_owned(),
+        offset + length + 1,
+    ))
+}
+
+/// The Multi-Stream Format container, which splits a file into streams
+/// stored as lists of blocks.
+struct Msf<'a> {
+    data: &'a [u8],
+    block_size: usize,
+    streams: Vec<(usize, Vec<usize>)>,
+}
+
+impl<'a> Msf<'a> {
+    fn parse(data: &'a [u8]) -> Option<Self> {
+        if data.get(..MSF_MAGIC.len())? != MSF_MAGIC {
+            return None;
+        }
+        let block_size = usize_at(data, 32)?;
+        if !matches!(block_size, 512 | 1024 | 2048 | 4096) {
+            return None;
+        }
+        let directory_size = usize_at(data, 44)?;
+        let block_map = usize_at(data, 52)?.checked_mul(block_size)?;
+        let directory_blocks = (0..directory_size.div_ceil(block_size))
+            .map(|index| usize_at(data, block_map + 4 * index))
+            .collect::<Option<Vec<_>>>()?;
+
+        let mut msf = Self {
+            data,
+            block_size,
+            streams: Vec::new(),
+        };
+        let directory = msf.read(directory_size, &directory_blocks)?;
+        let stream_count = usize_at(&directory, 0)?;
+        let sizes = (0..stream_count)
+            .map(|index| u32_at(&directory, 4 + 4 * index))
+            .collect::<Option<Vec<_>>>()?;
+        let mut offset = 4 + 4 * stream_count;
+        for size in sizes {
+            // Deleted streams have no blocks.
+            let size = if size == u32::MAX { 0 } else { size as usize };
+            let blocks = (0..size.div_ceil(block_size))
+                .map(|index| usize_at(&directory, offset + 4 * index))
+                .collect::<Option<Vec<_>>>()?;
+            offset += 4 * blocks.len();
+            msf.streams.push((size, blocks));
+        }
+        Some(msf)
+    }
+
+    fn read(&self, size: usize, blocks: &[usize]) -> Option<Vec<u8>> {
+        let mut contents = Vec::with_capacity(size);
+        for &block in blocks {
+            let start = block.checked_mul(self.block_size)?;
+            let length = self.block_size.min(size - contents.len());
+            contents.extend_from_slice(self.data.get(start..start + length)?);
+        }
+        Some(contents)
+    }
+
+    fn stream(&self, index: usize) -> Option<Vec<u8>> {
+        let (size, blocks) = self.streams.get(index)?;
+        self.read(*size, blocks)
+    }
+}
+
+/// The contents of `/names`, the string table that file names point into.
+fn names_stream(msf: &Msf) -> Option<Vec<u8>> {
+    let info = msf.stream(PDB_STREAM)?;
+    // The version, signature, age, and GUID precede the map of named
+    // streams, which is a string buffer followed by a hash table.
+    let buffer_size = usize_at(&info, 28)?;
+    let buffer = info.get(32..32 + buffer_size)?;
+    let table = 32 + buffer_size;
+    let entry_count = usize_at(&info, table)?;
+    let present_words = usize_at(&info, table + 8)?;
+    let deleted_words_offset = table + 12 + 4 * present_words;
+    let deleted_words = usize_at(&info, deleted_words_offset)?;
+    let entries = deleted_words_offset + 4 + 4 * deleted_words;
+    let index = (0..entry_count).find_map(|index| {
+        let name = usize_at(&info, entries + 8 * index)?;
+        let (name, _) = str_at(buffer, name)?;
+        if name == "/names" {
+            usize_at(&info, entries + 8 * index + 4)
+        } else {
+            None
+        }
+    })?;
+    let names = msf.stream(index)?;
+    let size = usize_at(&names, 8)?;
+    names.get(12..12 + size).map(<[u8]>::to_vec)
+}
+
+/// The symbol records in `bytes`, as their kinds and contents.
+fn records(bytes: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        let length = usize::from(u16_at(bytes, offset)?);
+        let kind = u16_at(bytes, offset + 2)?;
+        let contents = bytes.get(offset + 4..offset + 2 + length)?;
+        offset += 2 + length;
+        Some((kind, contents))
+    })
+}
+
+struct Reader {
+    /// The virtual address of each section, by its number minus one.
+    sections: Vec<u32>,
+    names: Option<Vec<u8>>,
+    symbols: Symbols,
+}
+
+impl Reader {
+    fn rva(&self, segment: u16, offset: u32) -> Option<u32> {
+        let section =
+            self.sections.get(usize::from(segment).checked_sub(1)?)?;
+        section.checked_add(offset)
+    }
+
+    /// Reads the stream of a module, which holds a signature and symbols,
+    /// then line numbers in the old and the current format.
+    fn read_module(
+        &mut self,
+        module: &[u8],
+        [symbols_size, c11_size, c13_size]: [usize; 3],
+    ) -> Option<()> {
+        for (kind, record) in records(module.get(4..symbols_size)?) {
+            if matches!(
+                kind,
+                S_LPROC32 | S_GPROC32 | S_LPROC32_ID | S_GPROC32_ID
+            ) {
+                let Some(rva) =
+                    self.rva(u16_at(record, 32)?, u32_at(record, 28)?)
+                else {
+                    continue;
+                };
+                self.symbols.functions.push(Function {
+                    rva,
+                    size: u32_at(record, 12),
+                    name: str_at(record, 35)?.0,
+                });
+            }
+        }
+
+        let lines_start = symbols_size + c11_size;
+        let lines_data = module.get(lines_start..lines_start + c13_size)?;
+        let subsections = || {
+            let mut offset = 0;
+            std::iter::from_fn(move || {
+                let kind = u32_at(lines_data, offset)?;
+                let length = usize_at(lines_data, offset + 4)?;
+                let contents =
+                    lines_data.get(offset + 8..offset + 8 + length)?;
+                offset = (offset + 8 + length).next_multiple_of(4);
+                Some((kind, contents))
+            })
+        };
+        let checksums = subsections()
+            .find(|(kind, _)| *kind == DEBUG_S_FILECHKSMS)
+            .map(|(_, contents)| contents);
+        for (_, lines) in
+            subsections().filter(|(kind, _)| *kind == DEBUG_S_LINES)
+        {
+            self.read_lines(lines, checksums)?;
+        }
+        Some(())
+    }
+
+    fn read_lines(
+        &mut self,
+        lines: &[u8],
+        checksums: Option<&[u8]>,
+    ) -> Option<()> {
+        let Some(base) = self.rva(u16_at(lines, 4)?, u32_at(lines, 0)?) else {
+            return Some(());
+        };
+        let has_columns = u16_at(lines, 6)? & CV_LINES_HAVE_COLUMNS != 0;
+        let mut offset = 12;
+        while offset < lines.len() {
+            let checksum = usize_at(lines, offset)?;
+            let count = usize_at(lines, offset + 4)?;
+            let block_size = usize_at(lines, offset + 8)?;
+            let file = self.file(checksums, checksum);
+            for index in 0..count {
+                let entry = offset + 12 + 8 * index;
+                let line = u32_at(lines, entry + 4)? & 0xff_ffff;
+                if line == 0 || line >= HIDDEN_LINE {
+                    continue;
+                }
+                self.symbols.lines.push(Line {
+                    rva: base.checked_add(u32_at(lines, entry)?)?,
+                    file,
+                    line,
+                });
+            }
+            let expected = 12 + count * if has_columns { 12 } else { 8 };
+            offset += block_size.max(expected);
+        }
+        Some(())
+    }
+
+    /// The index of the file named by the checksum entry at `checksum`,
+    /// added to the files on first use.
+    fn file(&mut self, checksums: Option<&[u8]>, checksum: usize) -> usize {
+        let name = checksums
+            .and_then(|checksums| usize_at(checksums, checksum))
+            .zip(self.names.as_deref())
+            .and_then(|(offset, names)| str_at(names, offset))
+            .map_or_else(|| "<unknown>".to_owned(), |(name, _)| name);
+        match self.symbols.files.iter().position(|file| *file == name) {
+            Some(index) => index,
+            None => {
+                self.symbols.files.push(name);
+                self.symbols.files.len() - 1
+            }
+        }
+    }
+}
+
+fn read_symbols(data: &[u8]) -> Option<Symbols> {
+    let msf = Msf::parse(data)?;
+    let dbi = msf.stream(DBI_STREAM)?;
+    let substream_sizes = [24, 28, 32, 36, 40, 52]
+        .map(|offset| usize_at(&dbi, offset))
+        .into_iter()
+        .collect::<Option<Vec<_>>>()?;
+    let debug_header = 64 + substream_sizes.iter().sum::<usize>();
+    let sections =
+        match u16_at(&dbi, debug_header + 2 * SECTION_HEADERS_DEBUG_STREAM) {
+            Some(index) if index != NIL_STREAM => msf
+                .stream(usize::from(index))?
+                .chunks_exact(40)
+                .map(|header| u32_at(header, 12))
+                .collect::<Option<Vec<_>>>()?,
+            _ => Vec::new(),
+        };
+
+    let mut reader = Reader {
+        sections,
+        names: names_stream(&msf),
+        symbols: Symbols::default(),
+    };
+    let modules = dbi.get(64..64 + substream_sizes[0])?;
+    let mut offset = 0;
+    while offset + 64 <= modules.len() {
+        let stream = u16_at(modules, offset + 34)?;
+        let sizes = [36, 40, 44].map(|field| usize_at(modules, offset + field));
+        let (_, end) = str_at(modules, offset + 64)?;
+        let (_, end) = str_at(modules, end)?;
+        offset = end.next_multiple_of(4);
+        if stream == NIL_STREAM {
+            continue;
+        }
+        let [Some(symbols_size), Some(c11_size), Some(c13_size)] = sizes else {
+            return None;
+        };
+        let Some(module) = msf.stream(usize::from(stream)) else {
+            continue;
+        };
+        // A malformed module leaves the others readable.
+        let _ = reader.read_module(&module, [symbols_size, c11_size, c13_size]);
+    }
+
+    let symbol_records = u16_at(&dbi, 20)?;
+    if symbol_records != NIL_STREAM
+        && let Some(stream) = msf.stream(usize::from(symbol_records))
+    {
+        for (kind, record) in records(&stream) {
+            if kind == S_PUB32
+                && u32_at(record, 0)? & CVPSF_FUNCTION != 0
+                && let Some(rva) =
+                    reader.rva(u16_at(record, 8)?, u32_at(record, 4)?)
+            {
+                reader.symbols.functions.push(Function {
+                    rva,
+                    size: None,
+                    name: str_at(record, 10)?.0,
+                });
+            }
+        }
+    }
+    Some(reader.symbols)
+}
+
+/// Reads the PDB in `data`. Fails with [`io::ErrorKind::InvalidData`] if it
+/// is not one or is malformed.
+pub(crate) fn read(data: &[u8]) -> io::Result<Symbols> {
+    read_symbols(data).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "malformed PDB")
+    })
+}
+
+pub(crate) fn is_pdb(data: &[u8]) -> bool {
+    data.starts_with(MSF_MAGIC)
+}
+
+/// A minimal PDB built in memory for the tests of this module and of the
+/// symbolication of backtraces.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use super::{
+        CVPSF_FUNCTION, DEBUG_S_FILECHKSMS, DEBUG_S_LINES, MSF_MAGIC,
+        NIL_STREAM, S_GPROC32, S_PUB32,
+    };
+
+    const BLOCK_SIZE: usize = 512;
+
+    fn u16s(values: &[u16]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+
+    fn u32s(values: &[u32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+
+    fn record(kind: u16, contents: &[u8]) -> Vec<u8> {
+        let mut record = u16s(&[contents.len() as u16 + 2, kind]);
+        record.extend_from_slice(contents);
+        record
+    }
+
+    fn subsection(kind: u32, contents: &[u8]) -> Vec<u8> {
+        let mut subsection = u32s(&[kind, contents.len() as u32]);
+        subsection.extend_from_slice(contents);
+        subsection.resize(subsection.len().next_multiple_of(4), 0);
+        subsection
+    }
+
+    /// Lays `streams` out in blocks behind the superblock and the free block
+    /// maps, followed by the stream directory and the block that lists it.
+    fn msf(streams: &[Vec<u8>]) -> Vec<u8> {
+        let mut blocks: Vec<Vec<u8>> = vec![Vec::new(); 3];
+        let mut directory = u32s(&[streams.len() as u32]);
+        directory.extend(
+            streams
+                .iter()
+                .flat_map(|stream| (stream.len() as u32).to_le_bytes()),
+        );
+        for stream in streams {
+            for chunk in stream.chunks(BLOCK_SIZE) {
+                directory.extend((blocks.len() as u32).to_le_bytes());
+                blocks.push(chunk.to_vec());
+            }
+        }
+        let mut block_map = Vec::new();
+        for chunk in directory.chunks(BLOCK_SIZE) {
+            block_map.extend((blocks.len() as u32).to_le_bytes());
+            blocks.push(chunk.to_vec());
+        }
+        let block_map_index = blocks.len();
+        blocks.push(block_map);
+
+        let mut superblock = MSF_MAGIC.to_vec();
+        superblock.extend(u32s(&[
+            BLOCK_SIZE as u32,
+            1,
+            blocks.len() as u32,
+            directory.len() as u32,
+            0,
+            block_map_index as u32,
+        ]));
+        blocks[0] = superblock;
+        blocks
+            .into_iter()
+            .flat_map(|mut block| {
+                block.resize(BLOCK_SIZE, 0);
+                block
+            })
+            .collect()
+    }
+
+    /// A PDB of `main.obj`, compiled from `src\main.cpp`, whose `.text`
+    /// starts at 0x1000 and holds `main` at 0x1010, which is 0x20 bytes
+    /// long and has lines 3 and 5, and the public `helper` at 0x1040.
+    pub(crate) fn pdb() -> Vec<u8> {
+        let names = b"\0src\\main.cpp\0";
+        let mut names_stream = u32s(&[0xeffe_effe, 1, names.len() as u32]);
+        names_stream.extend_from_slice(names);
+
+        let mut info = u32s(&[20000404, 0, 1, 0, 0, 0, 0]);
+        info.extend(u32s(&[b"/names\0".len() as u32]));
+        info.extend_from_slice(b"/names\0");
+        // One present entry, mapping `/names` to stream 6.
+        info.extend(u32s(&[1, 1, 1, 1, 0, 0, 6]));
+
+        let mut text = vec![0; 40];
+        text[..5].copy_from_slice(b".text");
+        text[12..16].copy_from_slice(&0x1000u32.to_le_bytes());
+
+        let mut procedure = u32s(&[0, 0, 0, 0x20, 0, 0, 0, 0x10]);
+        procedure.extend(u16s(&[1]));
+        procedure.push(0);
+        procedure.extend_from_slice(b"main\0");
+        let mut module = u32s(&[4]);
+        module.extend(record(S_GPROC32, &procedure));
+        let symbols_size = module.len();
+        let checksums = subsection(DEBUG_S_FILECHKSMS, &u32s(&[1, 0]));
+        let mut lines = u32s(&[0x10]);
+        lines.extend(u16s(&[1, 0]));
+        lines.extend(u32s(&[0x20, 0, 2, 12 + 2 * 8]));
+        lines.extend(u32s(&[0, 0x8000_0003, 0x0c, 0x8000_0005]));
+        let lines = subsection(DEBUG_S_LINES, &lines);
+        let c13_size = checksums.len() + lines.len();
+        module.extend(checksums);
+        module.extend(lines);
+
+        let mut module_info = vec![0; 64];
+        module_info[34..36].copy_from_slice(&5u16.to_le_bytes());
+        module_info[36..48].copy_from_slice(&u32s(&[
+            symbols_size as u32,
+            0,
+            c13_size as u32,
+        ]));
+        module_info.extend_from_slice(b"main.obj\0main.obj\0");
+        module_info.resize(module_info.len().next_multiple_of(4), 0);
+
+        let mut public = u32s(&[CVPSF_FUNCTION, 0x40]);
+        public.extend(u16s(&[1]));
+        public.extend_from_slice(b"helper\0");
+        let publics = record(S_PUB32, &public);
+
+        let mut dbi = vec![0; 64];
+        dbi[20..22].copy_from_slice(&7u16.to_le_bytes());
+        dbi[24..28].copy_from_slice(&(module_info.len() as u32).to_le_bytes());
+        dbi.extend(module_info);
+        let mut debug_header = [NIL_STREAM; 11];
+        debug_header[super::SECTION_HEADERS_DEBUG_STREAM] = 4;
+        dbi.extend(u16s(&debug_header));
+
+        msf(&[
+            Vec::new(),
+            info,
+            Vec::new(),
+            dbi,
+            text,
+            module,
+            names_stream,
+            publics,
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn reads_functions_and_lines() {
+        let symbols = super::read(&super::fixtures::pdb()).unwrap();
+        let functions: Vec<_> = symbols
+            .functions
+            .iter()
+            .map(|function| {
+                (function.rva, function.size, function.name.as_str())
+            })
+            .collect();
+        assert_eq!(
+            functions,
+            [(0x1010, Some(0x20), "main"), (0x1040, None, "helper")]
+        );
+        let lines: Vec<_> = symbols
+            .lines
+            .iter()
+            .map(|line| {
+                (line.rva, symbols.files[line.file].as_str(), line.line)
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            [(0x1010, "src\\main.cpp", 3), (0x101c, "src\\main.cpp", 5)]
+        );
+
+        assert!(super::is_pdb(&super::fixtures::pdb()));
+        assert!(super::read(b"Microsoft C/C++ MSF 7.00\r\n").is_err());
+    }
+}
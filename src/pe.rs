@@ -0,0 +1,864 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Reading the headers of Windows executables, e.g., to tell why one cannot
+//! run before launching it, and their resources, e.g., to show what a
+//! program is without running it.
+
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use crate::Prefix;
+
+/// Set in the COFF characteristics of DLLs.
+const IMAGE_FILE_DLL: u16 = 0x2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Machine {
+    X86,
+    X64,
+    Arm,
+    Arm64,
+    Other(u16),
+}
+
+impl Machine {
+    fn from_raw(raw: u16) -> Self {
+        match raw {
+            0x014c => Self::X86,
+            0x8664 => Self::X64,
+            0x01c4 => Self::Arm,
+            0xaa64 => Self::Arm64,
+            raw => Self::Other(raw),
+        }
+    }
+
+    /// `None` for machines this crate does not know.
+    pub fn bits(&self) -> Option<u32> {
+        match self {
+            Self::X86 | Self::Arm => Some(32),
+            Self::X64 | Self::Arm64 => Some(64),
+            Self::Other(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Machine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::X86 => f.write_str("x86"),
+            Self::X64 => f.write_str("x64"),
+            Self::Arm => f.write_str("ARM"),
+            Self::Arm64 => f.write_str("ARM64"),
+            Self::Other(raw) => write!(f, "machine {raw:#06x}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Subsystem {
+    /// Opens windows and has no console.
+    Gui,
+    Console,
+    /// Drivers and other programs for the kernel.
+    Native,
+    Other(u16),
+}
+
+impl Subsystem {
+    fn from_raw(raw: u16) -> Self {
+        match raw {
+            1 => Self::Native,
+            2 => Self::Gui,
+            3 => Self::Console,
+            raw => Self::Other(raw),
+        }
+    }
+}
+
+/// A Windows version as recorded in PE headers, e.g., 6.1 for Windows 7.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OsVersion {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl fmt::Display for OsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PeInfo {
+    pub machine: Machine,
+    pub subsystem: Subsystem,
+    /// The oldest Windows the loader agrees to run the image on.
+    pub min_os_version: OsVersion,
+    pub is_dll: bool,
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_owned())
+}
+
+fn read_at<const N: usize>(
+    file: &mut fs::File,
+    offset: u64,
+) -> io::Result<[u8; N]> {
+    let mut buffer = [0; N];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut buffer).map_err(|error| {
+        if error.kind() == io::ErrorKind::UnexpectedEof {
+            invalid("truncated PE headers")
+        } else {
+            error
+        }
+    })?;
+    Ok(buffer)
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+/// Reads the headers of the executable or DLL at `path`.
+pub fn inspect(path: impl AsRef<Path>) -> io::Result<PeInfo> {
+    let mut file = fs::File::open(path)?;
+
+    let dos_header: [u8; 64] = read_at(&mut file, 0)?;
+    if &dos_header[..2] != b"MZ" {
+        return Err(invalid("not an executable (missing MZ signature)"));
+    }
+    let pe_offset =
+        u32::from_le_bytes(dos_header[0x3c..0x40].try_into().unwrap());
+
+    // The signature, the COFF header, and the optional header up to and
+    // including the subsystem.
+    let headers: [u8; 4 + 20 + 70] = read_at(&mut file, pe_offset.into())?;
+    if &headers[..4] != b"PE\0\0" {
+        return Err(invalid("not a PE executable (missing PE signature)"));
+    }
+    let coff = &headers[4..24];
+    let optional = &headers[24..];
+    if !matches!(u16_at(optional, 0), 0x10b | 0x20b) {
+        return Err(invalid("unknown PE optional header format"));
+    }
+
+    Ok(PeInfo {
+        machine: Machine::from_raw(u16_at(coff, 0)),
+        subsystem: Subsystem::from_raw(u16_at(optional, 68)),
+        min_os_version: OsVersion {
+            major: u16_at(optional, 48),
+            minor: u16_at(optional, 50),
+        },
+        is_dll: u16_at(coff, 18) & IMAGE_FILE_DLL != 0,
+    })
+}
+
+/// A section of an image, e.g., `.text` or `.rsrc`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Section {
+    /// Up to eight bytes, without the padding.
+    pub name: String,
+    pub virtual_address: u32,
+    pub virtual_size: u32,
+    /// Where the contents start in the file.
+    pub raw_offset: u32,
+    pub raw_size: u32,
+}
+
+impl Section {
+    /// The end of the contents in the file.
+    fn raw_end(&self) -> u64 {
+        u64::from(self.raw_offset) + u64::from(self.raw_size)
+    }
+}
+
+/// What identifies a resource, its type, or its language.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ResourceId {
+    Id(u16),
+    Name(String),
+}
+
+impl ResourceId {
+    pub const ICON: Self = Self::Id(3);
+    pub const RCDATA: Self = Self::Id(10);
+    pub const GROUP_ICON: Self = Self::Id(14);
+    pub const VERSION: Self = Self::Id(16);
+    pub const MANIFEST: Self = Self::Id(24);
+}
+
+/// A resource embedded in an image, e.g., an icon or the version information.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Resource {
+    pub kind: ResourceId,
+    pub name: ResourceId,
+    pub language: ResourceId,
+    pub data: Vec<u8>,
+}
+
+/// How far the section table and the resource directory can be trusted.
+const MAX_SECTIONS: u16 = 96;
+const MAX_RESOURCE_ENTRIES: u16 = 4096;
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+/// The sections of an image and the RVA and size of its resource directory.
+pub(crate) struct Layout {
+    pub(crate) sections: Vec<Section>,
+    resource_directory: Option<(u32, u32)>,
+}
+
+impl Layout {
+    pub(crate) fn read(file: &mut fs::File) -> io::Result<Self> {
+        let dos_header: [u8; 64] = read_at(file, 0)?;
+        if &dos_header[..2] != b"MZ" {
+            return Err(invalid("not an executable (missing MZ signature)"));
+        }
+        let pe_offset = u64::from(u32_at(&dos_header, 0x3c));
+        let headers: [u8; 4 + 20 + 2] = read_at(file, pe_offset)?;
+        if &headers[..4] != b"PE\0\0" {
+            return Err(invalid("not a PE executable (missing PE signature)"));
+        }
+        let coff = &headers[4..24];
+        let section_count = u16_at(coff, 2).min(MAX_SECTIONS);
+        let optional_size = u16_at(coff, 16);
+        let directories_offset = match u16_at(&headers, 24) {
+            0x10b => 96,
+            0x20b => 112,
+            _ => return Err(invalid("unknown PE optional header format")),
+        };
+
+        let optional_offset = pe_offset + 24;
+        let resource_directory = if usize::from(optional_size)
+            >= directories_offset + 3 * 8
+        {
+            let count: [u8; 4] =
+                read_at(file, optional_offset + directories_offset as u64 - 4)?;
+            let entry: [u8; 8] = read_at(
+                file,
+                optional_offset + directories_offset as u64 + 2 * 8,
+            )?;
+            let (rva, size) = (u32_at(&entry, 0), u32_at(&entry, 4));
+            (u32::from_le_bytes(count) > 2 && rva != 0 && size != 0)
+                .then_some((rva, size))
+        } else {
+            None
+        };
+
+        let mut sections = Vec::new();
+        let table = optional_offset + u64::from(optional_size);
+        for index in 0..u64::from(section_count) {
+            let header: [u8; 40] = read_at(file, table + index * 40)?;
+            let name = &header[..8];
+            let name = &name[..name.iter().position(|&b| b == 0).unwrap_or(8)];
+            sections.push(Section {
+                name: String::from_utf8_lossy(name).into_owned(),
+                virtual_size: u32_at(&header, 8),
+                virtual_address: u32_at(&header, 12),
+                raw_size: u32_at(&header, 16),
+                raw_offset: u32_at(&header, 20),
+            });
+        }
+        Ok(Self {
+            sections,
+            resource_directory,
+        })
+    }
+
+    /// Where data appended after the last section, e.g., the payload of an
+    /// installer, starts.
+    pub(crate) fn overlay_offset(&self) -> u64 {
+        self.sections
+            .iter()
+            .map(Section::raw_end)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The section holding the resource directory and its contents.
+    fn resource_section(
+        &self,
+        file: &mut fs::File,
+    ) -> io::Result<Option<(u32, Vec<u8>)>> {
+        let Some((rva, _)) = self.resource_directory else {
+            return Ok(None);
+        };
+        let mut found = None;
+        for section in &self.sections {
+            let end = section
+                .virtual_address
+                .checked_add(section.virtual_size.max(section.raw_size))
+                .ok_or_else(|| {
+                    invalid("section extends past the address space")
+                })?;
+            if (section.virtual_address..end).contains(&rva) {
+                found = Some(section);
+                break;
+            }
+        }
+        let Some(section) = found else {
+            return Ok(None);
+        };
+        let mut contents = Vec::new();
+        file.seek(SeekFrom::Start(section.raw_offset.into()))?;
+        file.take(section.raw_size.into())
+            .read_to_end(&mut contents)?;
+        Ok(Some((section.virtual_address, contents)))
+    }
+
+    pub(crate) fn resources(
+        &self,
+        file: &mut fs::File,
+    ) -> io::Result<Vec<Resource>> {
+        let Some((base, section)) = self.resource_section(file)? else {
+            return Ok(Vec::new());
+        };
+        let (rva, _) = self.resource_directory.unwrap_or_default();
+        let root = (rva - base) as usize;
+
+        let malformed = || invalid("malformed resource directory");
+        let mut resources = Vec::new();
+        resource_entries(&section, root, |kind, offset, is_directory| {
+            if !is_directory {
+                return Err(malformed());
+            }
+            resource_entries(&section, offset, |name, offset, is_directory| {
+                if !is_directory {
+                    return Err(malformed());
+                }
+                resource_entries(
+                    &section,
+                    offset,
+                    |language, offset, is_directory| {
+                        let entry = section
+                            .get(offset..offset + 8)
+                            .filter(|_| !is_directory)
+                            .ok_or_else(malformed)?;
+                        let start =
+                            u32_at(entry, 0).wrapping_sub(base) as usize;
+                        let size = u32_at(entry, 4) as usize;
+                        let data = section
+                            .get(start..start.saturating_add(size))
+                            .ok_or_else(|| {
+                                invalid("truncated resource data")
+                            })?;
+                        resources.push(Resource {
+                            kind: kind.clone(),
+                            name: name.clone(),
+                            language,
+                            data: data.to_vec(),
+                        });
+                        Ok(())
+                    },
+                )
+            })
+        })?;
+        Ok(resources)
+    }
+}
+
+/// Walks one level of the resource directory at `offset` in `section`,
+/// calling `visit` with the ID of each entry, the offset it leads to, and
+/// whether that is another directory.
+fn resource_entries(
+    section: &[u8],
+    offset: usize,
+    mut visit: impl FnMut(ResourceId, usize, bool) -> io::Result<()>,
+) -> io::Result<()> {
+    let truncated = || invalid("truncated resource directory");
+    let directory = section.get(offset..offset + 16).ok_or_else(truncated)?;
+    let count = u16_at(directory, 12)
+        .saturating_add(u16_at(directory, 14))
+        .min(MAX_RESOURCE_ENTRIES);
+    for index in 0..usize::from(count) {
+        let entry_offset = offset + 16 + index * 8;
+        let entry = section
+            .get(entry_offset..entry_offset + 8)
+            .ok_or_else(truncated)?;
+        let (name, target) = (u32_at(entry, 0), u32_at(entry, 4));
+        let id = if name & 0x8000_0000 != 0 {
+            let start = (name & 0x7fff_ffff) as usize;
+            let length = section
+                .get(start..start + 2)
+                .map(|length| usize::from(u16_at(length, 0)))
+                .ok_or_else(truncated)?;
+            let units: Vec<_> = section
+                .get(start + 2..start + 2 + 2 * length)
+                .ok_or_else(truncated)?
+                .chunks_exact(2)
+                .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                .collect();
+            ResourceId::Name(String::from_utf16_lossy(&units))
+        } else {
+            ResourceId::Id(name as u16)
+        };
+        visit(
+            id,
+            (target & 0x7fff_ffff) as usize,
+            target & 0x8000_0000 != 0,
+        )?;
+    }
+    Ok(())
+}
+
+/// Reads the resources of the executable or DLL at `path`, in the order of
+/// the resource directory, which sorts them by type, name, and language.
+pub fn resources(path: impl AsRef<Path>) -> io::Result<Vec<Resource>> {
+    let mut file = fs::File::open(path)?;
+    Layout::read(&mut file)?.resources(&mut file)
+}
+
+/// The signature of the fixed part of the version information.
+const FIXED_FILE_INFO_SIGNATURE: u32 = 0xfeef_04bd;
+
+/// US English, which the strings are read in when they are translated.
+const ENGLISH_STRING_TABLE: &str = "0409";
+
+/// A version as recorded in the version information, e.g., 1.2.0.345.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileVersion {
+    pub major: u16,
+    pub minor: u16,
+    pub build: u16,
+    pub revision: u16,
+}
+
+impl FileVersion {
+    fn from_raw(most_significant: u32, least_significant: u32) -> Self {
+        Self {
+            major: (most_significant >> 16) as u16,
+            minor: most_significant as u16,
+            build: (least_significant >> 16) as u16,
+            revision: least_significant as u16,
+        }
+    }
+}
+
+impl fmt::Display for FileVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}.{}",
+            self.major, self.minor, self.build, self.revision
+        )
+    }
+}
+
+/// The `VERSIONINFO` resource of an executable or DLL, i.e., what Explorer
+/// shows in the details of the file.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct VersionInfo {
+    pub file_version: Option<FileVersion>,
+    pub product_version: Option<FileVersion>,
+    /// The strings by their names, e.g., `ProductName` or `LegalCopyright`,
+    /// in US English if they are translated into it and otherwise in the
+    /// first language.
+    pub strings: BTreeMap<String, String>,
+}
+
+impl VersionInfo {
+    pub fn product_name(&self) -> Option<&str> {
+        self.string("ProductName")
+    }
+
+    pub fn company_name(&self) -> Option<&str> {
+        self.string("CompanyName")
+    }
+
+    pub fn file_description(&self) -> Option<&str> {
+        self.string("FileDescription")
+    }
+
+    /// The version as the vendor writes it, e.g., `2024 R2`, which need not
+    /// match [`VersionInfo::file_version`].
+    pub fn file_version_string(&self) -> Option<&str> {
+        self.string("FileVersion")
+    }
+
+    pub fn string(&self, name: &str) -> Option<&str> {
+        self.strings
+            .get(name)
+            .map(String::as_str)
+            .filter(|value| !value.is_empty())
+    }
+}
+
+/// A node of the version information: a key, a value, and more nodes.
+struct VersionBlock<'a> {
+    key: String,
+    value: &'a [u8],
+    children: &'a [u8],
+}
+
+fn utf16_until_nul(bytes: &[u8]) -> (String, usize) {
+    let units: Vec<_> = bytes
+        .chunks_exact(2)
+        .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+        .take_while(|&unit| unit != 0)
+        .collect();
+    (String::from_utf16_lossy(&units), 2 * units.len())
+}
+
+/// The sibling nodes that fill `bytes`, each aligned to four bytes.
+fn version_blocks(mut bytes: &[u8]) -> Vec<VersionBlock<'_>> {
+    let mut blocks = Vec::new();
+    while bytes.len() >= 6 {
+        let length = usize::from(u16_at(bytes, 0));
+        let Some(block) = bytes.get(..length).filter(|_| length >= 6) else {
+            break;
+        };
+        let (value_length, is_text) =
+            (usize::from(u16_at(block, 2)), u16_at(block, 4) == 1);
+        let (key, key_length) = utf16_until_nul(&block[6..]);
+        let value_start = (6 + key_length + 2).next_multiple_of(4);
+        // The length of text is in UTF-16 units.
+        let value_end =
+            value_start + value_length * if is_text { 2 } else { 1 };
+        let value = block.get(value_start..value_end).unwrap_or_default();
+        let children = block
+            .get(value_end.next_multiple_of(4)..)
+            .unwrap_or_default();
+        blocks.push(VersionBlock {
+            key,
+            value,
+            children,
+        });
+        bytes = bytes.get(length.next_multiple_of(4)..).unwrap_or_default();
+    }
+    blocks
+}
+
+impl VersionInfo {
+    fn parse(data: &[u8]) -> Option<Self> {
+        let root = version_blocks(data).into_iter().next()?;
+        if root.key != "VS_VERSION_INFO" {
+            return None;
+        }
+        let mut info = Self::default();
+        if root.value.len() >= 24
+            && u32_at(root.value, 0) == FIXED_FILE_INFO_SIGNATURE
+        {
+            info.file_version = Some(FileVersion::from_raw(
+                u32_at(root.value, 8),
+                u32_at(root.value, 12),
+            ));
+            info.product_version = Some(FileVersion::from_raw(
+                u32_at(root.value, 16),
+                u32_at(root.value, 20),
+            ));
+        }
+
+        let tables: Vec<_> = version_blocks(root.children)
+            .into_iter()
+            .filter(|block| block.key == "StringFileInfo")
+            .flat_map(|block| version_blocks(block.children))
+            .collect();
+        let table = tables
+            .iter()
+            .find(|table| {
+                table.key.get(..4).is_some_and(|language| {
+                    language.eq_ignore_ascii_case(ENGLISH_STRING_TABLE)
+                })
+            })
+            .or(tables.first());
+        if let Some(table) = table {
+            for string in version_blocks(table.children) {
+                let (value, _) = utf16_until_nul(string.value);
+                info.strings.insert(string.key, value.trim().to_owned());
+            }
+        }
+        Some(info)
+    }
+}
+
+/// Reads the version information of the executable or DLL at `path`, or
+/// `None` if it has none.
+pub fn version_info(path: impl AsRef<Path>) -> io::Result<Option<VersionInfo>> {
+    let path = path.as_ref();
+    let Some(resource) = resources(path)?
+        .into_iter()
+        .find(|resource| resource.kind == ResourceId::VERSION)
+    else {
+        return Ok(None);
+    };
+    VersionInfo::parse(&resource.data).map(Some).ok_or_else(|| {
+        invalid(&format!(
+            "{} has malformed version information",
+            path.display()
+        ))
+    })
+}
+
+impl Prefix {
+    /// Reads the version information of a program installed in the prefix,
+    /// e.g., `C:\Program Files\App\app.exe`, without running it. The path
+    /// is resolved like [`Prefix::resolve_guest_path`] does.
+    pub fn program_version_info(
+        &self,
+        windows_path: &str,
+    ) -> io::Result<Option<VersionInfo>> {
+        version_info(self.resolve_guest_path(windows_path)?)
+    }
+}
+
+/// Executables built in memory for the tests of this module and of those
+/// that read resources.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use std::{env, fs, path::PathBuf, process};
+
+    pub(crate) fn put_u16(bytes: &mut [u8], offset: usize, value: u16) {
+        bytes[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn put_u32(bytes: &mut [u8], offset: usize, value: u32) {
+        bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// A resource directory at `rva` holding each `(kind, name, data)` of
+    /// `resources` in US English.
+    pub(crate) fn resource_directory(
+        rva: u32,
+        resources: &[(u16, u16, &[u8])],
+    ) -> Vec<u8> {
+        let count = resources.len();
+        let root_length = 16 + 8 * count;
+        let mut section = vec![0; root_length + 64 * count];
+        put_u16(&mut section, 14, count as u16);
+        for (index, (kind, name, data)) in resources.iter().enumerate() {
+            let names = root_length + 64 * index;
+            let (languages, entry) = (names + 24, names + 48);
+            put_u32(&mut section, 16 + 8 * index, u32::from(*kind));
+            put_u32(&mut section, 20 + 8 * index, 0x8000_0000 | names as u32);
+            for (directory, id, target) in [
+                (names, u32::from(*name), 0x8000_0000 | languages as u32),
+                (languages, 0x409, entry as u32),
+            ] {
+                put_u16(&mut section, directory + 14, 1);
+                put_u32(&mut section, directory + 16, id);
+                put_u32(&mut section, directory + 20, target);
+            }
+            section.resize(section.len().next_multiple_of(4), 0);
+            let start = rva + section.len() as u32;
+            put_u32(&mut section, entry, start);
+            put_u32(&mut section, entry + 4, data.len() as u32);
+            section.extend_from_slice(data);
+        }
+        section
+    }
+
+    /// A 64-bit GUI executable whose only section, `.rsrc`, is mapped at
+    /// `virtual_address` with `virtual_size` and holds `contents`, which the
+    /// resource directory points to the start of.
+    pub(crate) fn image(
+        virtual_address: u32,
+        virtual_size: u32,
+        contents: &[u8],
+    ) -> Vec<u8> {
+        const RAW_OFFSET: usize = 0x200;
+        let mut image = vec![0; RAW_OFFSET];
+        image[..2].copy_from_slice(b"MZ");
+        put_u32(&mut image, 0x3c, 0x40);
+        image[0x40..0x44].copy_from_slice(b"PE\0\0");
+        let coff = 0x44;
+        put_u16(&mut image, coff, 0x8664);
+        put_u16(&mut image, coff + 2, 1);
+        put_u16(&mut image, coff + 16, 240);
+        let optional = coff + 20;
+        put_u16(&mut image, optional, 0x20b);
+        put_u16(&mut image, optional + 48, 6);
+        put_u16(&mut image, optional + 50, 1);
+        put_u16(&mut image, optional + 68, 2);
+        put_u32(&mut image, optional + 108, 16);
+        put_u32(&mut image, optional + 112 + 16, virtual_address);
+        put_u32(&mut image, optional + 112 + 20, contents.len() as u32);
+        let header = optional + 240;
+        image[header..header + 5].copy_from_slice(b".rsrc");
+        put_u32(&mut image, header + 8, virtual_size);
+        put_u32(&mut image, header + 12, virtual_address);
+        put_u32(&mut image, header + 16, contents.len() as u32);
+        put_u32(&mut image, header + 20, RAW_OFFSET as u32);
+        image.extend_from_slice(contents);
+        image
+    }
+
+    /// Writes an executable holding `resources` to a temporary file named
+    /// after `name`.
+    pub(crate) fn write_executable(
+        name: &str,
+        resources: &[(u16, u16, &[u8])],
+    ) -> PathBuf {
+        let contents = resource_directory(0x1000, resources);
+        write_image(name, &image(0x1000, contents.len() as u32, &contents))
+    }
+
+    pub(crate) fn write_image(name: &str, image: &[u8]) -> PathBuf {
+        let path = env::temp_dir()
+            .join(format!("wine-rs-pe-test-{}-{name}.exe", process::id()));
+        fs::write(&path, image).unwrap();
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io};
+
+    use super::{
+        FileVersion, Machine, ResourceId, Subsystem, VersionInfo,
+        fixtures::{
+            image, put_u16, put_u32, resource_directory, write_executable,
+            write_image,
+        },
+    };
+
+    fn utf16(text: &str) -> Vec<u8> {
+        text.encode_utf16()
+            .chain([0])
+            .flat_map(u16::to_le_bytes)
+            .collect()
+    }
+
+    /// A node of the version information, aligned like `rc` writes them.
+    fn version_block(
+        key: &str,
+        value: &[u8],
+        is_text: bool,
+        children: &[Vec<u8>],
+    ) -> Vec<u8> {
+        let mut block = vec![0; 6];
+        block.extend(utf16(key));
+        block.resize(block.len().next_multiple_of(4), 0);
+        block.extend_from_slice(value);
+        for child in children {
+            block.resize(block.len().next_multiple_of(4), 0);
+            block.extend_from_slice(child);
+        }
+        let length = block.len() as u16;
+        let value_length = if is_text {
+            value.len() / 2
+        } else {
+            value.len()
+        };
+        put_u16(&mut block, 0, length);
+        put_u16(&mut block, 2, value_length as u16);
+        put_u16(&mut block, 4, u16::from(is_text));
+        block
+    }
+
+    fn string_table(language: &str, strings: &[(&str, &str)]) -> Vec<u8> {
+        let strings: Vec<_> = strings
+            .iter()
+            .map(|(name, value)| version_block(name, &utf16(value), true, &[]))
+            .collect();
+        version_block(language, &[], true, &strings)
+    }
+
+    fn version_resource() -> Vec<u8> {
+        let mut fixed = vec![0; 52];
+        put_u32(&mut fixed, 0, super::FIXED_FILE_INFO_SIGNATURE);
+        put_u32(&mut fixed, 8, 1 << 16 | 2);
+        put_u32(&mut fixed, 12, 3 << 16 | 4);
+        put_u32(&mut fixed, 16, 2024 << 16);
+        let strings = version_block(
+            "StringFileInfo",
+            &[],
+            true,
+            &[
+                string_table("040704b0", &[("ProductName", "Beispiel")]),
+                string_table(
+                    "040904b0",
+                    &[
+                        ("CompanyName", ""),
+                        ("ProductName", "Example App "),
+                        ("FileVersion", "2024 R2"),
+                    ],
+                ),
+            ],
+        );
+        version_block("VS_VERSION_INFO", &fixed, false, &[strings])
+    }
+
+    #[test]
+    fn parses_version_information() {
+        let info = VersionInfo::parse(&version_resource()).unwrap();
+        assert_eq!(info.product_name(), Some("Example App"));
+        assert_eq!(info.file_version_string(), Some("2024 R2"));
+        assert_eq!(info.company_name(), None);
+        assert_eq!(info.file_version.unwrap().to_string(), "1.2.3.4");
+        assert_eq!(
+            info.product_version,
+            Some(FileVersion {
+                major: 2024,
+                minor: 0,
+                build: 0,
+                revision: 0,
+            })
+        );
+
+        assert!(
+            VersionInfo::parse(&version_block("Other", &[], false, &[]))
+                .is_none()
+        );
+
+        let path = write_executable("version", &[(16, 1, &version_resource())]);
+        assert_eq!(super::version_info(&path).unwrap(), Some(info));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn reads_headers_and_resources() {
+        let path = write_executable("resources", &[(10, 1, b"payload")]);
+
+        let info = super::inspect(&path).unwrap();
+        assert_eq!(info.machine, Machine::X64);
+        assert_eq!(info.subsystem, Subsystem::Gui);
+        assert_eq!(
+            (info.min_os_version.major, info.min_os_version.minor),
+            (6, 1)
+        );
+        assert!(!info.is_dll);
+
+        let resources = super::resources(&path).unwrap();
+        assert_eq!(resources.len(), 1);
+        assert_eq!(resources[0].kind, ResourceId::RCDATA);
+        assert_eq!(resources[0].name, ResourceId::Id(1));
+        assert_eq!(resources[0].language, ResourceId::Id(0x409));
+        assert_eq!(resources[0].data, b"payload");
+        assert!(super::version_info(&path).unwrap().is_none());
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn rejects_sections_past_the_address_space() {
+        let contents = resource_directory(0xffff_f000, &[(10, 1, b"payload")]);
+        let path =
+            write_image("overflow", &image(0xffff_f000, 0x2000, &contents));
+
+        let error = super::resources(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn rejects_non_executables() {
+        let path = write_image("text", b"#!/bin/sh\n");
+        let error = super::inspect(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        fs::remove_file(path).unwrap();
+    }
+}
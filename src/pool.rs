@@ -0,0 +1,157 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    fs, io,
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::{Condvar, Mutex},
+};
+
+use crate::{Prefix, tree};
+
+#[derive(Debug)]
+struct Slot {
+    prefix: Prefix,
+    /// Whether the prefix was used since it was last copied from the
+    /// template.
+    is_dirty: bool,
+}
+
+/// Copies of a template prefix handed out to one worker at a time, e.g., to
+/// run integration tests in parallel against isolated prefixes.
+///
+/// A prefix is reset to the template before it is handed out again, so the
+/// template should not be running anything while the pool is in use.
+#[derive(Debug)]
+pub struct PrefixPool {
+    template: Prefix,
+    idle: Mutex<Vec<Slot>>,
+    returned: Condvar,
+}
+
+/// A prefix checked out of a [`PrefixPool`], returned to it when dropped.
+#[derive(Debug)]
+pub struct PooledPrefix<'a> {
+    pool: &'a PrefixPool,
+    prefix: Option<Prefix>,
+}
+
+impl PrefixPool {
+    /// Copies `template` into `size` prefixes in `dir`, replacing any that
+    /// are left over from an earlier pool.
+    pub fn new(
+        template: &Prefix,
+        dir: impl AsRef<Path>,
+        size: usize,
+    ) -> io::Result<Self> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        let mut idle = Vec::with_capacity(size);
+        for index in 0..size {
+            template.report_progress(
+                &format!("copying prefix {} of {size}", index + 1),
+                index,
+                size,
+            );
+            let prefix = template.relocated(dir.join(index.to_string()));
+            tree::remove_tree(&prefix.path)?;
+            if let Err(error) = template
+                .check_cancelled()
+                .and_then(|()| tree::copy_tree(&template.path, &prefix.path))
+            {
+                for index in 0..=index {
+                    tree::remove_tree(&dir.join(index.to_string()))?;
+                }
+                return Err(error);
+            }
+            idle.push(Slot {
+                prefix,
+                is_dirty: false,
+            });
+        }
+        template.report_progress("copied the prefixes", size, size);
+        Ok(Self {
+            template: template.clone(),
+            idle: Mutex::new(idle),
+            returned: Condvar::new(),
+        })
+    }
+
+    /// Blocks until a prefix is free.
+    pub fn checkout(&self) -> io::Result<PooledPrefix<'_>> {
+        let mut idle = self.idle.lock().unwrap();
+        loop {
+            if let Some(slot) = idle.pop() {
+                drop(idle);
+                return self.hand_out(slot);
+            }
+            idle = self.returned.wait(idle).unwrap();
+        }
+    }
+
+    /// Returns `None` if every prefix is checked out.
+    pub fn try_checkout(&self) -> io::Result<Option<PooledPrefix<'_>>> {
+        let slot = self.idle.lock().unwrap().pop();
+        slot.map(|slot| self.hand_out(slot)).transpose()
+    }
+
+    fn hand_out(&self, slot: Slot) -> io::Result<PooledPrefix<'_>> {
+        if slot.is_dirty
+            && let Err(error) = self.reset(&slot.prefix)
+        {
+            // Try again on the next checkout.
+            self.give_back(slot);
+            return Err(error);
+        }
+        Ok(PooledPrefix {
+            pool: self,
+            prefix: Some(slot.prefix),
+        })
+    }
+
+    fn reset(&self, prefix: &Prefix) -> io::Result<()> {
+        if prefix.is_server_running() {
+            prefix.kill_all()?;
+        }
+        tree::remove_tree(&prefix.path)?;
+        tree::copy_tree(&self.template.path, &prefix.path)
+    }
+
+    fn give_back(&self, slot: Slot) {
+        self.idle.lock().unwrap().push(slot);
+        self.returned.notify_one();
+    }
+}
+
+impl Deref for PooledPrefix<'_> {
+    type Target = Prefix;
+
+    fn deref(&self) -> &Prefix {
+        self.prefix.as_ref().unwrap()
+    }
+}
+
+impl Drop for PooledPrefix<'_> {
+    fn drop(&mut self) {
+        if let Some(prefix) = self.prefix.take() {
+            self.pool.give_back(Slot {
+                prefix,
+                is_dirty: true,
+            });
+        }
+    }
+}
+
+impl Prefix {
+    /// This prefix with the same configuration, but at `path`.
+    pub(crate) fn relocated(&self, path: PathBuf) -> Prefix {
+        Prefix {
+            path,
+            ..self.clone()
+        }
+    }
+}
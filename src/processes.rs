@@ -0,0 +1,95 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    io, thread,
+    time::{Duration, Instant},
+};
+
+use crate::{Error, Prefix, serialize::serialize_fields};
+
+/// How often [`Prefix::wait_for_process`] lists the processes.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A Windows process running in a prefix.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GuestProcess {
+    /// The Windows process ID, not the host one.
+    pub pid: u32,
+    pub threads: u32,
+    /// The executable file name, e.g., `explorer.exe`.
+    pub image_name: String,
+}
+
+serialize_fields!(GuestProcess {
+    pid,
+    threads,
+    image_name
+});
+
+/// Parses the output of `winedbg --command "info proc"`, whose lines look like
+/// ` 00000038 7        \_ 'plugplay.exe'`.
+fn parse_info_proc(output: &str) -> Vec<GuestProcess> {
+    output
+        .lines()
+        .filter_map(|line| {
+            // The debugger marks itself and its target.
+            let line = line.trim_start_matches([' ', '>', '=']);
+            let (pid, rest) = line.split_once(' ')?;
+            let pid = u32::from_str_radix(pid, 16).ok()?;
+            let rest = rest.trim_start();
+            let (threads, rest) = rest.split_once(' ')?;
+            let threads = threads.parse().ok()?;
+            let (_, quoted) = rest.split_once('\'')?;
+            let image_name = quoted.strip_suffix('\'')?;
+            Some(GuestProcess {
+                pid,
+                threads,
+                image_name: image_name.to_owned(),
+            })
+        })
+        .collect()
+}
+
+impl Prefix {
+    /// Lists the Windows processes running in the prefix, which is empty
+    /// without starting the wineserver if it is not running.
+    pub fn processes(&self) -> io::Result<Vec<GuestProcess>> {
+        if !self.is_server_running() {
+            return Ok(Vec::new());
+        }
+        let output = self.run_builtin("winedbg", ["--command", "info proc"])?;
+        Ok(parse_info_proc(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Blocks until a process whose executable is `image_name`, compared
+    /// regardless of case, is running in the prefix, e.g., to launch a tool
+    /// only once the server it talks to has started. Fails with
+    /// [`Error::Timeout`] after `timeout`.
+    pub fn wait_for_process(
+        &self,
+        image_name: &str,
+        timeout: Duration,
+    ) -> io::Result<GuestProcess> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(process) =
+                self.processes()?.into_iter().find(|process| {
+                    process.image_name.eq_ignore_ascii_case(image_name)
+                })
+            {
+                return Ok(process);
+            }
+            if Instant::now() >= deadline {
+                return Err(Error::Timeout(format!(
+                    "waiting for `{image_name}` to start"
+                ))
+                .into());
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
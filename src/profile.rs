@@ -0,0 +1,242 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{fmt, fs, io, path::Path, str::FromStr};
+
+use crate::{
+    DebugRules, DllOverride, DllOverrideMode, Prefix, WineCommand,
+    settings::WindowsVersion,
+};
+
+/// Compatibility settings bundled under a name, e.g., `dx9-game`, so that
+/// they can be reused across prefixes.
+///
+/// Profiles are stored as `key = value` lines, where `#` starts a comment and
+/// `dll_override` and `env` may repeat:
+///
+/// ```text
+/// name = dx9-game
+/// windows_version = win7
+/// dll_override = d3d9=native
+/// env = DXVK_HUD=fps
+/// debug = -all,+err
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Profile {
+    pub name: String,
+    pub windows_version: Option<WindowsVersion>,
+    pub dll_overrides: Vec<DllOverride>,
+    pub env: Vec<(String, String)>,
+    /// A `WINEDEBUG` value.
+    pub debug: Option<String>,
+}
+
+impl Profile {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            windows_version: None,
+            dll_overrides: Vec::new(),
+            env: Vec::new(),
+            debug: None,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+}
+
+impl FromStr for Profile {
+    type Err = io::Error;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let invalid = |line_number: usize, message: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {line_number}: {message}"),
+            )
+        };
+        let mut profile = Profile::new("");
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.split_once('#').map_or(line, |(line, _)| line);
+            if line.trim().is_empty() {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(invalid(
+                    line_number,
+                    "expected `key = value`".into(),
+                ));
+            };
+            let value = value.trim();
+            match key.trim() {
+                "name" => profile.name = value.to_owned(),
+                "windows_version" => {
+                    profile.windows_version = Some(
+                        WindowsVersion::from_name(value).ok_or_else(|| {
+                            invalid(
+                                line_number,
+                                format!("unknown Windows version `{value}`"),
+                            )
+                        })?,
+                    );
+                }
+                "dll_override" => {
+                    let (dll, mode) =
+                        value.split_once('=').unwrap_or((value, ""));
+                    let mode =
+                        DllOverrideMode::from_name(mode).ok_or_else(|| {
+                            invalid(
+                                line_number,
+                                format!("unknown mode `{mode}`"),
+                            )
+                        })?;
+                    profile
+                        .dll_overrides
+                        .push(DllOverride::new(dll.trim(), mode));
+                }
+                "env" => {
+                    let Some((name, value)) = value.split_once('=') else {
+                        return Err(invalid(
+                            line_number,
+                            "expected `env = NAME=value`".into(),
+                        ));
+                    };
+                    profile
+                        .env
+                        .push((name.trim().to_owned(), value.to_owned()));
+                }
+                "debug" => {
+                    DebugRules::parse(value).map_err(|issue| {
+                        invalid(line_number, issue.to_string())
+                    })?;
+                    profile.debug = Some(value.to_owned());
+                }
+                key => {
+                    return Err(invalid(
+                        line_number,
+                        format!("unknown key `{key}`"),
+                    ));
+                }
+            }
+        }
+        if profile.name.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the profile has no name",
+            ));
+        }
+        Ok(profile)
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "name = {}", self.name)?;
+        if let Some(version) = self.windows_version {
+            writeln!(f, "windows_version = {}", version.as_str())?;
+        }
+        for dll_override in &self.dll_overrides {
+            writeln!(
+                f,
+                "dll_override = {}={}",
+                dll_override.dll,
+                dll_override.mode.as_registry_str()
+            )?;
+        }
+        for (name, value) in &self.env {
+            writeln!(f, "env = {name}={value}")?;
+        }
+        if let Some(debug) = &self.debug {
+            writeln!(f, "debug = {debug}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Prefix {
+    /// Persists the Windows version and DLL overrides of `profile` in the
+    /// registry of the prefix. Its variables and debug rules apply per launch
+    /// through [`WineCommand::profile`].
+    pub fn apply_profile(&self, profile: &Profile) -> io::Result<()> {
+        if let Some(version) = profile.windows_version {
+            self.set_windows_version(version)?;
+        }
+        for dll_override in &profile.dll_overrides {
+            self.set_dll_override(dll_override)?;
+        }
+        Ok(())
+    }
+}
+
+impl WineCommand<'_> {
+    /// Sets the variables and debug rules of `profile`, the latter replacing
+    /// any given through [`WineCommand::debug_rules`].
+    pub fn profile(&mut self, profile: &Profile) -> &mut Self {
+        self.envs(profile.env.iter().map(|(name, value)| (name, value)));
+        if let Some(debug) = &profile.debug {
+            self.env("WINEDEBUG", debug);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::Profile;
+    use crate::{DllOverride, DllOverrideMode, settings::WindowsVersion};
+
+    #[test]
+    fn parses_and_round_trips() {
+        let profile: Profile = "# Settings for old games.\n\
+                                name = dx9-game\n\
+                                windows_version = win7\n\
+                                dll_override = d3d9=native\n\
+                                dll_override = xinput1_3 # builtin\n\
+                                env = DXVK_HUD=fps=1\n\
+                                debug = -all,+err\n"
+            .parse()
+            .unwrap();
+        assert_eq!(profile.name, "dx9-game");
+        assert_eq!(profile.windows_version, Some(WindowsVersion::Windows7));
+        assert_eq!(
+            profile.dll_overrides,
+            [
+                DllOverride::new("d3d9", DllOverrideMode::Native),
+                DllOverride::new("xinput1_3", DllOverrideMode::Disabled),
+            ]
+        );
+        assert_eq!(profile.env, [("DXVK_HUD".to_owned(), "fps=1".to_owned())]);
+        assert_eq!(profile.debug.as_deref(), Some("-all,+err"));
+        assert_eq!(profile.to_string().parse::<Profile>().unwrap(), profile);
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        for source in [
+            "name = a\nwindows_version",
+            "name = a\nwindows_version = win1",
+            "name = a\ndll_override = d3d9=sometimes",
+            "name = a\nenv = DXVK_HUD",
+            "name = a\ndebug = bogus+heap",
+            "name = a\ncolor = blue",
+            "windows_version = win7",
+        ] {
+            let error = source.parse::<Profile>().unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{source}");
+        }
+        let error = "name = a\ncolor = blue".parse::<Profile>().unwrap_err();
+        assert!(error.to_string().starts_with("line 2:"));
+    }
+}
@@ -0,0 +1,70 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::sync::Arc;
+
+use crate::Prefix;
+
+/// How far a long operation, such as [`Prefix::initialize`] or
+/// [`Prefix::snapshot`], has come.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProgressUpdate<'a> {
+    /// What the operation is starting on, e.g., `saving drive_c`.
+    pub phase: &'a str,
+    /// The phases finished so far, out of [`ProgressUpdate::total`]. The last
+    /// update of an operation has every phase finished.
+    pub completed: usize,
+    pub total: usize,
+}
+
+impl ProgressUpdate<'_> {
+    /// From 0 to 1.
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.completed as f64 / self.total as f64
+        }
+    }
+}
+
+/// Receives the [`ProgressUpdate`]s of the long operations on a prefix, e.g.,
+/// to draw a progress bar rather than a spinner. Closures taking an update
+/// implement it.
+pub trait Progress: Send + Sync {
+    fn update(&self, update: &ProgressUpdate<'_>);
+}
+
+impl<F: Fn(&ProgressUpdate<'_>) + Send + Sync> Progress for F {
+    fn update(&self, update: &ProgressUpdate<'_>) {
+        self(update)
+    }
+}
+
+impl Prefix {
+    /// Reports the progress of long operations on this prefix to `progress`,
+    /// from whichever thread runs them.
+    pub fn with_progress(mut self, progress: impl Progress + 'static) -> Self {
+        self.hooks.progress.push(Arc::new(progress));
+        self
+    }
+
+    pub(crate) fn report_progress(
+        &self,
+        phase: &str,
+        completed: usize,
+        total: usize,
+    ) {
+        let update = ProgressUpdate {
+            phase,
+            completed,
+            total,
+        };
+        for progress in &self.hooks.progress {
+            progress.update(&update);
+        }
+    }
+}
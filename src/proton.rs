@@ -0,0 +1,185 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Launching through Valve's Proton builds outside of Steam.
+
+use std::{
+    env,
+    ffi::{OsStr, OsString},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{Prefix, PrefixBuilder, PrefixConfig, ResolvedCommand, WineFlavor};
+
+/// Steam installations, relative to the home directory.
+const STEAM_ROOTS: &[&str] = &[".steam/root", ".local/share/Steam"];
+
+/// The Steam installations of the current user that exist.
+pub fn steam_roots() -> Vec<PathBuf> {
+    let Some(home) = env::var_os("HOME").map(PathBuf::from) else {
+        return Vec::new();
+    };
+    let mut roots: Vec<PathBuf> = Vec::new();
+    for root in STEAM_ROOTS {
+        let root = home.join(root);
+        if let Ok(canonical) = fs::canonicalize(&root)
+            && !roots.contains(&canonical)
+        {
+            roots.push(canonical);
+        }
+    }
+    roots
+}
+
+/// A Proton build, either one shipped by Steam or a custom one in
+/// `compatibilitytools.d`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Proton {
+    /// The directory containing the `proton` script.
+    pub path: PathBuf,
+    pub name: String,
+}
+
+impl Proton {
+    /// Returns `None` unless `path` contains a `proton` script.
+    pub fn at(path: impl AsRef<Path>) -> Option<Self> {
+        let path = path.as_ref();
+        path.join("proton").is_file().then(|| Self {
+            path: path.to_path_buf(),
+            name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Finds the builds in `compatibilitytools.d` and `steamapps/common` of
+    /// every Steam installation.
+    pub fn discover() -> io::Result<Vec<Self>> {
+        let mut builds = Vec::new();
+        for root in steam_roots() {
+            for dir in ["compatibilitytools.d", "steamapps/common"] {
+                let entries = match fs::read_dir(root.join(dir)) {
+                    Ok(entries) => entries,
+                    Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                        continue;
+                    }
+                    Err(error) => return Err(error),
+                };
+                for entry in entries {
+                    builds.extend(Self::at(entry?.path()));
+                }
+            }
+        }
+        builds.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(builds)
+    }
+
+    /// The Wine build inside, which older releases keep in `dist/`.
+    pub fn wine_root(&self) -> PathBuf {
+        let files = self.path.join("files");
+        if files.is_dir() {
+            files
+        } else {
+            self.path.join("dist")
+        }
+    }
+
+    /// A prefix that runs this build's Wine directly against the `pfx`
+    /// directory of `compat_data`.
+    pub fn prefix(&self, compat_data: &CompatData) -> Prefix {
+        let wine_root = self.wine_root();
+        let mut config = PrefixConfig {
+            flavor: Some(WineFlavor::Proton),
+            ..PrefixConfig::default()
+        };
+        config.extra_env.extend(compat_data.env());
+        let mut builder = PrefixBuilder::new(compat_data.prefix_path());
+        builder
+            .wine(wine_root.join("bin/wine"))
+            .dynamic_library_paths([
+                wine_root.join("lib64"),
+                wine_root.join("lib"),
+            ])
+            .config(config);
+        builder.build()
+    }
+
+    /// A `proton run` invocation of `program`, which also sets up the prefix
+    /// on first use and applies Proton's own defaults.
+    pub fn run_command<I, S>(
+        &self,
+        compat_data: &CompatData,
+        program: impl AsRef<OsStr>,
+        args: I,
+    ) -> ResolvedCommand
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let mut command =
+            ResolvedCommand::new(self.path.join("proton"), &self.path);
+        self.prefix(compat_data)
+            .environment_policy()
+            .apply(&mut command);
+        command.args.push("run".into());
+        command.args.push(program.as_ref().to_os_string());
+        command
+            .args
+            .extend(args.into_iter().map(|arg| arg.as_ref().to_os_string()));
+        command.envs.extend(compat_data.env());
+        command
+    }
+}
+
+/// A `compatdata` directory, which holds a Wine prefix in `pfx/` alongside
+/// Proton's bookkeeping.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CompatData {
+    pub path: PathBuf,
+    /// The Steam installation Proton loads its client libraries from.
+    pub client_install_path: PathBuf,
+}
+
+impl CompatData {
+    pub fn new(
+        path: impl AsRef<Path>,
+        client_install_path: impl AsRef<Path>,
+    ) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            client_install_path: client_install_path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// The `compatdata` Steam would use for the app with the given ID.
+    pub fn for_app(steam_root: impl AsRef<Path>, app_id: u32) -> Self {
+        let steam_root = steam_root.as_ref();
+        Self::new(
+            steam_root.join(format!("steamapps/compatdata/{app_id}")),
+            steam_root,
+        )
+    }
+
+    pub fn prefix_path(&self) -> PathBuf {
+        self.path.join("pfx")
+    }
+
+    /// The variables Proton refuses to start without.
+    pub fn env(&self) -> [(OsString, OsString); 2] {
+        [
+            (
+                "STEAM_COMPAT_DATA_PATH".into(),
+                self.path.clone().into_os_string(),
+            ),
+            (
+                "STEAM_COMPAT_CLIENT_INSTALL_PATH".into(),
+                self.client_install_path.clone().into_os_string(),
+            ),
+        ]
+    }
+}
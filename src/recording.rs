@@ -0,0 +1,283 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    ffi::{OsStr, OsString},
+    fs, io,
+    os::unix::ffi::{OsStrExt, OsStringExt},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    MANIFEST_AREAS, Manifest, ResolvedCommand, WineCommand,
+    diagnostics::{self, Diagnostic},
+};
+
+const HEADER: &[u8] = b"wine-rs-recording 1\n";
+
+/// Everything about a launch made with [`WineCommand::record`], saved as a
+/// single file to attach to bug reports and replayed with `wine-rs replay`
+/// on another machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaunchRecording {
+    /// The prefix the launch ran in, for which a replay substitutes its own.
+    pub prefix: PathBuf,
+    /// As handed to the operating system, after every hook ran.
+    pub command: ResolvedCommand,
+    /// The `--version` output of the loader, if it gave one.
+    pub wine_version: Option<String>,
+    /// Of the [`MANIFEST_AREAS`] when the launch began.
+    pub manifest: Manifest,
+    /// `None` if the program was killed by a signal.
+    pub exit_code: Option<i32>,
+    pub stdout: Vec<u8>,
+    /// Including the diagnostics of Wine, see
+    /// [`LaunchRecording::diagnostics`].
+    pub stderr: Vec<u8>,
+    pub elapsed: Duration,
+}
+
+/// Replaces a leading `from` in `value` with `to`.
+fn substitute(value: &OsStr, from: &[u8], to: &[u8]) -> OsString {
+    let bytes = value.as_bytes();
+    match bytes.strip_prefix(from) {
+        Some(rest) if !from.is_empty() => {
+            OsString::from_vec([to, rest].concat())
+        }
+        _ => value.to_os_string(),
+    }
+}
+
+fn write_field(bundle: &mut Vec<u8>, name: &str, value: &[u8]) {
+    bundle.extend(format!("{name} {}\n", value.len()).as_bytes());
+    bundle.extend(value);
+    bundle.push(b'\n');
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Reads the fields of a bundle, each of which is a line of its name and
+/// length followed by that many bytes and a newline, so that values may hold
+/// anything.
+fn read_fields(mut bundle: &[u8]) -> io::Result<Vec<(&str, &[u8])>> {
+    bundle = bundle
+        .strip_prefix(HEADER)
+        .ok_or_else(|| invalid("not a wine-rs recording"))?;
+    let mut fields = Vec::new();
+    while !bundle.is_empty() {
+        let line_end = bundle
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .ok_or_else(|| invalid("truncated field header"))?;
+        let header = str::from_utf8(&bundle[..line_end])
+            .map_err(|_| invalid("malformed field header"))?;
+        let (name, length) = header
+            .split_once(' ')
+            .and_then(|(name, length)| Some((name, length.parse().ok()?)))
+            .ok_or_else(|| invalid(format!("malformed field `{header}`")))?;
+        let rest = &bundle[line_end + 1..];
+        let length: usize = length;
+        if rest.len() < length + 1 || rest[length] != b'\n' {
+            return Err(invalid(format!("truncated field `{name}`")));
+        }
+        fields.push((name, &rest[..length]));
+        bundle = &rest[length + 1..];
+    }
+    Ok(fields)
+}
+
+impl LaunchRecording {
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        diagnostics::demultiplex(&self.stderr).1
+    }
+
+    /// The recorded command with the recorded prefix replaced by `prefix` in
+    /// its program, arguments, directory, and variables.
+    pub fn command_for(&self, prefix: impl AsRef<Path>) -> ResolvedCommand {
+        let from = self.prefix.as_os_str().as_bytes();
+        let to = prefix.as_ref().as_os_str().as_bytes();
+        let mut command = self.command.clone();
+        command.program = substitute(&command.program, from, to);
+        for arg in &mut command.args {
+            *arg = substitute(arg, from, to);
+        }
+        command.current_dir =
+            substitute(command.current_dir.as_os_str(), from, to).into();
+        for (_, value) in &mut command.envs {
+            *value = substitute(value, from, to);
+        }
+        command
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bundle = HEADER.to_vec();
+        let command = &self.command;
+        write_field(&mut bundle, "prefix", self.prefix.as_os_str().as_bytes());
+        write_field(&mut bundle, "program", command.program.as_bytes());
+        for arg in &command.args {
+            write_field(&mut bundle, "arg", arg.as_bytes());
+        }
+        write_field(
+            &mut bundle,
+            "current_dir",
+            command.current_dir.as_os_str().as_bytes(),
+        );
+        if command.env_clear {
+            write_field(&mut bundle, "env_clear", b"");
+        }
+        for (name, value) in &command.envs {
+            write_field(
+                &mut bundle,
+                "env",
+                &[name.as_bytes(), b"=", value.as_bytes()].concat(),
+            );
+        }
+        for name in &command.unset_envs {
+            write_field(&mut bundle, "unset_env", name.as_bytes());
+        }
+        if let Some(version) = &self.wine_version {
+            write_field(&mut bundle, "wine_version", version.as_bytes());
+        }
+        write_field(
+            &mut bundle,
+            "manifest",
+            self.manifest.to_string().as_bytes(),
+        );
+        if let Some(exit_code) = self.exit_code {
+            write_field(
+                &mut bundle,
+                "exit_code",
+                exit_code.to_string().as_bytes(),
+            );
+        }
+        write_field(&mut bundle, "stdout", &self.stdout);
+        write_field(&mut bundle, "stderr", &self.stderr);
+        write_field(
+            &mut bundle,
+            "elapsed_ms",
+            self.elapsed.as_millis().to_string().as_bytes(),
+        );
+        bundle
+    }
+
+    pub fn from_bytes(bundle: &[u8]) -> io::Result<Self> {
+        let mut recording = Self {
+            prefix: PathBuf::new(),
+            command: ResolvedCommand::new("", ""),
+            wine_version: None,
+            manifest: Manifest::default(),
+            exit_code: None,
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+            elapsed: Duration::ZERO,
+        };
+        let text = |name: &str, value: &[u8]| {
+            str::from_utf8(value)
+                .map(str::to_owned)
+                .map_err(|_| invalid(format!("`{name}` is not UTF-8")))
+        };
+        for (name, value) in read_fields(bundle)? {
+            let os = || OsString::from_vec(value.to_vec());
+            let command = &mut recording.command;
+            match name {
+                "prefix" => recording.prefix = os().into(),
+                "program" => command.program = os(),
+                "arg" => command.args.push(os()),
+                "current_dir" => command.current_dir = os().into(),
+                "env_clear" => command.env_clear = true,
+                "env" => {
+                    let split = value
+                        .iter()
+                        .position(|&byte| byte == b'=')
+                        .ok_or_else(|| invalid("variable without `=`"))?;
+                    command.envs.push((
+                        OsString::from_vec(value[..split].to_vec()),
+                        OsString::from_vec(value[split + 1..].to_vec()),
+                    ));
+                }
+                "unset_env" => command.unset_envs.push(os()),
+                "wine_version" => {
+                    recording.wine_version = Some(text(name, value)?);
+                }
+                "manifest" => {
+                    recording.manifest = text(name, value)?.parse()?
+                }
+                "exit_code" => {
+                    recording.exit_code =
+                        Some(text(name, value)?.parse().map_err(|_| {
+                            invalid("the exit code is not a number")
+                        })?);
+                }
+                "stdout" => recording.stdout = value.to_vec(),
+                "stderr" => recording.stderr = value.to_vec(),
+                "elapsed_ms" => {
+                    recording.elapsed = Duration::from_millis(
+                        text(name, value)?.parse().map_err(|_| {
+                            invalid("the elapsed time is not a number")
+                        })?,
+                    );
+                }
+                // Fields of later versions.
+                _ => {}
+            }
+        }
+        Ok(recording)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+}
+
+impl WineCommand<'_> {
+    /// Runs the command to completion like [`WineCommand::output`], capturing
+    /// what is needed to reproduce the launch elsewhere. A program that fails
+    /// is recorded rather than reported as an error.
+    pub fn record(&self) -> io::Result<LaunchRecording> {
+        let prefix = self.prefix;
+        let manifest = Manifest::record(prefix, MANIFEST_AREAS)?;
+        let wine_version = prefix.version_output(&prefix.wine);
+
+        let launched = Arc::new(Mutex::new(None));
+        let mut command = self.clone();
+        command.post_exit_hook({
+            let launched = launched.clone();
+            move |command, _| {
+                *launched.lock().unwrap() = Some(command.clone());
+            }
+        });
+        let start = Instant::now();
+        let output = command.output()?;
+        let elapsed = start.elapsed();
+        let mut command = launched
+            .lock()
+            .unwrap()
+            .take()
+            .unwrap_or_else(|| self.resolve());
+        // How the recording collected the output is not part of the launch.
+        command.capture_output = false;
+        command.pipe_stdin = false;
+
+        Ok(LaunchRecording {
+            prefix: prefix.path.clone(),
+            command,
+            wine_version,
+            manifest,
+            exit_code: output.status.code(),
+            stdout: output.stdout,
+            stderr: output.stderr,
+            elapsed,
+        })
+    }
+}
@@ -0,0 +1,756 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Editing the registry of a prefix through Wine's builtin `reg.exe`, or
+//! through `regedit.exe` for many changes at once, and watching it for
+//! changes.
+
+use std::{ffi::OsStr, fmt, io, str::FromStr};
+
+use crate::{Error, Prefix, Script, trace};
+
+mod watch;
+
+pub use watch::{RegistryChange, RegistryWatcher};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Hive {
+    ClassesRoot,
+    CurrentUser,
+    LocalMachine,
+    Users,
+    CurrentConfig,
+}
+
+impl Hive {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ClassesRoot => "HKEY_CLASSES_ROOT",
+            Self::CurrentUser => "HKEY_CURRENT_USER",
+            Self::LocalMachine => "HKEY_LOCAL_MACHINE",
+            Self::Users => "HKEY_USERS",
+            Self::CurrentConfig => "HKEY_CURRENT_CONFIG",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RegistryKey {
+    hive: Hive,
+    path: String,
+}
+
+impl RegistryKey {
+    /// `path` is backslash-separated and relative to `hive`.
+    pub fn new(hive: Hive, path: impl Into<String>) -> Self {
+        Self {
+            hive,
+            path: path.into(),
+        }
+    }
+
+    pub fn current_user(path: impl Into<String>) -> Self {
+        Self::new(Hive::CurrentUser, path)
+    }
+
+    pub fn local_machine(path: impl Into<String>) -> Self {
+        Self::new(Hive::LocalMachine, path)
+    }
+
+    pub fn classes_root(path: impl Into<String>) -> Self {
+        Self::new(Hive::ClassesRoot, path)
+    }
+
+    pub fn hive(&self) -> Hive {
+        self.hive
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn join(&self, subkey: &str) -> Self {
+        let path = if self.path.is_empty() {
+            subkey.to_owned()
+        } else {
+            format!("{}\\{subkey}", self.path)
+        };
+        Self::new(self.hive, path)
+    }
+
+    /// The direct subkeys of this key in `prefix`.
+    pub fn subkeys(
+        &self,
+        prefix: &Prefix,
+    ) -> io::Result<impl Iterator<Item = RegistryKey>> {
+        let listing = prefix.registry_listing(self, false)?;
+        Ok(listing.into_iter().skip(1).map(|contents| contents.key))
+    }
+
+    /// The values of this key in `prefix`, named as in
+    /// [`Prefix::registry_value`].
+    pub fn values(
+        &self,
+        prefix: &Prefix,
+    ) -> io::Result<impl Iterator<Item = (Option<String>, RegistryValue)>> {
+        let listing = prefix.registry_listing(self, false)?;
+        Ok(listing
+            .into_iter()
+            .next()
+            .into_iter()
+            .flat_map(|contents| contents.values))
+    }
+
+    /// This key and every key below it in `prefix`, each before its subkeys,
+    /// read with a single `reg query /s`.
+    pub fn walk(
+        &self,
+        prefix: &Prefix,
+    ) -> io::Result<impl Iterator<Item = RegistryKeyContents>> {
+        Ok(prefix.registry_listing(self, true)?.into_iter())
+    }
+}
+
+impl FromStr for RegistryKey {
+    type Err = io::Error;
+
+    /// Accepts both full and abbreviated hive names, e.g.
+    /// `HKEY_CURRENT_USER\Software\Wine` and `HKCU\Software\Wine`.
+    fn from_str(key: &str) -> Result<Self, Self::Err> {
+        let (hive, path) = key.split_once('\\').unwrap_or((key, ""));
+        let hive = match hive.to_ascii_uppercase().as_str() {
+            "HKEY_CLASSES_ROOT" | "HKCR" => Hive::ClassesRoot,
+            "HKEY_CURRENT_USER" | "HKCU" => Hive::CurrentUser,
+            "HKEY_LOCAL_MACHINE" | "HKLM" => Hive::LocalMachine,
+            "HKEY_USERS" | "HKU" => Hive::Users,
+            "HKEY_CURRENT_CONFIG" | "HKCC" => Hive::CurrentConfig,
+            _ => {
+                return Err(Error::RegistryParse(format!(
+                    "unknown registry hive `{hive}`"
+                ))
+                .into());
+            }
+        };
+        Ok(Self::new(hive, path.trim_end_matches('\\')))
+    }
+}
+
+impl fmt::Display for RegistryKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.hive.as_str())?;
+        if !self.path.is_empty() {
+            write!(f, "\\{}", self.path)?;
+        }
+        Ok(())
+    }
+}
+
+/// As its full name, e.g., `HKEY_CURRENT_USER\Software\Wine`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for RegistryKey {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RegistryValue {
+    String(String),
+    /// A string in which `%VARIABLE%` references are expanded when it is
+    /// read, such as `%SystemRoot%\system32`.
+    ExpandString(String),
+    MultiString(Vec<String>),
+    Binary(Vec<u8>),
+    DWord(u32),
+    QWord(u64),
+}
+
+impl RegistryValue {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::String(_) => "REG_SZ",
+            Self::ExpandString(_) => "REG_EXPAND_SZ",
+            Self::MultiString(_) => "REG_MULTI_SZ",
+            Self::Binary(_) => "REG_BINARY",
+            Self::DWord(_) => "REG_DWORD",
+            Self::QWord(_) => "REG_QWORD",
+        }
+    }
+
+    /// The data as passed to `reg add /d`, which separates the strings of
+    /// multi-strings with `\0`.
+    fn reg_data(&self) -> String {
+        match self {
+            Self::String(string) | Self::ExpandString(string) => string.clone(),
+            Self::MultiString(strings) => strings.join("\\0"),
+            Self::Binary(bytes) => {
+                bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+            }
+            Self::DWord(dword) => dword.to_string(),
+            Self::QWord(qword) => qword.to_string(),
+        }
+    }
+
+    /// The right-hand side of the value in a `.reg` file.
+    fn reg_file_data(&self) -> String {
+        match self {
+            Self::String(string) => quote_reg_string(string),
+            Self::ExpandString(string) => {
+                format!("hex(2):{}", hex_bytes(&utf16_data([string])))
+            }
+            Self::MultiString(strings) => {
+                let mut data = utf16_data(strings);
+                data.extend_from_slice(&[0, 0]);
+                format!("hex(7):{}", hex_bytes(&data))
+            }
+            Self::Binary(bytes) => format!("hex:{}", hex_bytes(bytes)),
+            Self::DWord(dword) => format!("dword:{dword:08x}"),
+            Self::QWord(qword) => {
+                format!("hex(b):{}", hex_bytes(&qword.to_le_bytes()))
+            }
+        }
+    }
+}
+
+/// As a map of its `type`, e.g., `REG_SZ`, and its `data`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for RegistryValue {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        NamedValue {
+            name: None,
+            value: self,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// A value serialized with its name, if it has one, as a map whose `name` is
+/// `null` for the default value.
+#[cfg(feature = "serde")]
+struct NamedValue<'a> {
+    name: Option<&'a Option<String>>,
+    value: &'a RegistryValue,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for NamedValue<'_> {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct(
+            "RegistryValue",
+            2 + usize::from(self.name.is_some()),
+        )?;
+        if let Some(name) = self.name {
+            state.serialize_field("name", name)?;
+        }
+        state.serialize_field("type", self.value.type_name())?;
+        match self.value {
+            RegistryValue::String(string)
+            | RegistryValue::ExpandString(string) => {
+                state.serialize_field("data", string)?;
+            }
+            RegistryValue::MultiString(strings) => {
+                state.serialize_field("data", strings)?;
+            }
+            RegistryValue::Binary(bytes) => {
+                state.serialize_field("data", bytes)?;
+            }
+            RegistryValue::DWord(dword) => {
+                state.serialize_field("data", dword)?;
+            }
+            RegistryValue::QWord(qword) => {
+                state.serialize_field("data", qword)?;
+            }
+        }
+        state.end()
+    }
+}
+
+fn quote_reg_string(string: &str) -> String {
+    format!("\"{}\"", string.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Encodes each string as UTF-16LE with a null terminator, as they are stored
+/// in the registry.
+fn utf16_data<S: AsRef<str>>(strings: impl IntoIterator<Item = S>) -> Vec<u8> {
+    let mut data = Vec::new();
+    for string in strings {
+        for unit in string.as_ref().encode_utf16().chain([0]) {
+            data.extend_from_slice(&unit.to_le_bytes());
+        }
+    }
+    data
+}
+
+fn hex_bytes(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum BatchOperation {
+    Set(RegistryKey, Option<String>, RegistryValue),
+    DeleteValue(RegistryKey, Option<String>),
+    DeleteKey(RegistryKey),
+}
+
+/// Registry changes collected to be applied together with
+/// [`Prefix::apply_registry_batch`], which starts Wine once instead of once
+/// per change.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct RegistryBatch {
+    operations: Vec<BatchOperation>,
+}
+
+impl RegistryBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Like [`Prefix::set_registry_value`].
+    pub fn set_value(
+        &mut self,
+        key: &RegistryKey,
+        name: Option<&str>,
+        value: impl Into<RegistryValue>,
+    ) -> &mut Self {
+        self.operations.push(BatchOperation::Set(
+            key.clone(),
+            name.map(str::to_owned),
+            value.into(),
+        ));
+        self
+    }
+
+    /// Like [`Prefix::delete_registry_value`], except that a missing value
+    /// is not an error.
+    pub fn delete_value(
+        &mut self,
+        key: &RegistryKey,
+        name: Option<&str>,
+    ) -> &mut Self {
+        self.operations.push(BatchOperation::DeleteValue(
+            key.clone(),
+            name.map(str::to_owned),
+        ));
+        self
+    }
+
+    /// Deletes `key` with all of its subkeys, if it exists.
+    pub fn delete_key(&mut self, key: &RegistryKey) -> &mut Self {
+        self.operations.push(BatchOperation::DeleteKey(key.clone()));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// The batch as the contents of a `.reg` file, applying the operations in
+    /// the order they were added.
+    pub fn to_reg_file(&self) -> String {
+        let mut text = String::from("Windows Registry Editor Version 5.00\r\n");
+        let mut section: Option<&RegistryKey> = None;
+        for operation in &self.operations {
+            let (key, name, data) = match operation {
+                BatchOperation::Set(key, name, value) => {
+                    (key, name, value.reg_file_data())
+                }
+                BatchOperation::DeleteValue(key, name) => {
+                    (key, name, "-".to_owned())
+                }
+                BatchOperation::DeleteKey(key) => {
+                    text.push_str(&format!("\r\n[-{key}]\r\n"));
+                    section = None;
+                    continue;
+                }
+            };
+            if section != Some(key) {
+                text.push_str(&format!("\r\n[{key}]\r\n"));
+                section = Some(key);
+            }
+            let name = name.as_deref().map_or("@".to_owned(), quote_reg_string);
+            text.push_str(&format!("{name}={data}\r\n"));
+        }
+        text
+    }
+}
+
+impl From<&str> for RegistryValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_owned())
+    }
+}
+
+impl From<String> for RegistryValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<Vec<String>> for RegistryValue {
+    fn from(value: Vec<String>) -> Self {
+        Self::MultiString(value)
+    }
+}
+
+impl From<Vec<u8>> for RegistryValue {
+    fn from(value: Vec<u8>) -> Self {
+        Self::Binary(value)
+    }
+}
+
+impl From<&[u8]> for RegistryValue {
+    fn from(value: &[u8]) -> Self {
+        Self::Binary(value.to_vec())
+    }
+}
+
+impl From<u32> for RegistryValue {
+    fn from(value: u32) -> Self {
+        Self::DWord(value)
+    }
+}
+
+impl From<u64> for RegistryValue {
+    fn from(value: u64) -> Self {
+        Self::QWord(value)
+    }
+}
+
+/// The value types [`parse_reg_data`] understands.
+const SUPPORTED_TYPES: &[&str] = &[
+    "REG_SZ",
+    "REG_EXPAND_SZ",
+    "REG_MULTI_SZ",
+    "REG_BINARY",
+    "REG_DWORD",
+    "REG_QWORD",
+];
+
+/// Parses the data column of `reg query` output.
+fn parse_reg_data(type_name: &str, data: &str) -> io::Result<RegistryValue> {
+    let malformed = || {
+        io::Error::from(Error::RegistryParse(format!(
+            "malformed {type_name} data `{data}`"
+        )))
+    };
+    let parse_hex = |data: &str| {
+        u64::from_str_radix(data.trim_start_matches("0x"), 16)
+            .map_err(|_| malformed())
+    };
+    match type_name {
+        "REG_SZ" => Ok(RegistryValue::String(data.to_owned())),
+        "REG_EXPAND_SZ" => Ok(RegistryValue::ExpandString(data.to_owned())),
+        // reg.exe shows the null separators as `\0`.
+        "REG_MULTI_SZ" if data.is_empty() => {
+            Ok(RegistryValue::MultiString(Vec::new()))
+        }
+        "REG_MULTI_SZ" => Ok(RegistryValue::MultiString(
+            data.split("\\0").map(str::to_owned).collect(),
+        )),
+        "REG_BINARY" => {
+            if !data.len().is_multiple_of(2) || !data.is_ascii() {
+                return Err(malformed());
+            }
+            (0..data.len())
+                .step_by(2)
+                .map(|index| {
+                    u8::from_str_radix(&data[index..index + 2], 16)
+                        .map_err(|_| malformed())
+                })
+                .collect::<io::Result<_>>()
+                .map(RegistryValue::Binary)
+        }
+        "REG_DWORD" => Ok(RegistryValue::DWord(parse_hex(data)? as u32)),
+        "REG_QWORD" => Ok(RegistryValue::QWord(parse_hex(data)?)),
+        _ => Err(Error::RegistryParse(format!(
+            "unsupported registry value type {type_name}"
+        ))
+        .into()),
+    }
+}
+
+/// Parses a value line of `reg query` output, e.g.
+/// `    LogPixels    REG_DWORD    0x60`, whose name is `(Default)` for the
+/// default value.
+fn parse_value_line(line: &str) -> Option<(&str, &str, &str)> {
+    let line = line.strip_prefix("    ")?;
+    let name_end = line.find("    REG_")?;
+    let rest = &line[name_end + 4..];
+    let (type_name, data) = rest.split_once("    ").unwrap_or((rest, ""));
+    Some((&line[..name_end], type_name, data))
+}
+
+/// A key together with its values, as visited by [`RegistryKey::walk`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RegistryKeyContents {
+    pub key: RegistryKey,
+    /// Named as in [`Prefix::registry_value`]. Values of types this crate
+    /// cannot represent are left out.
+    pub values: Vec<(Option<String>, RegistryValue)>,
+}
+
+/// As a map of its `key` and its `values`, each of which serializes like a
+/// [`RegistryValue`] with a `name` added.
+#[cfg(feature = "serde")]
+impl serde::Serialize for RegistryKeyContents {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let values: Vec<_> = self
+            .values
+            .iter()
+            .map(|(name, value)| NamedValue {
+                name: Some(name),
+                value,
+            })
+            .collect();
+        let mut state =
+            serializer.serialize_struct("RegistryKeyContents", 2)?;
+        state.serialize_field("key", &self.key)?;
+        state.serialize_field("values", &values)?;
+        state.end()
+    }
+}
+
+/// Parses `reg query` output, in which every key is on a line of its own
+/// followed by its indented values.
+fn parse_listing(output: &str) -> io::Result<Vec<RegistryKeyContents>> {
+    let mut listing: Vec<RegistryKeyContents> = Vec::new();
+    for line in output.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if !line.starts_with(' ') {
+            listing.push(RegistryKeyContents {
+                key: line.trim_end().parse()?,
+                values: Vec::new(),
+            });
+        } else if let Some((name, type_name, data)) = parse_value_line(line)
+            && let Some(contents) = listing.last_mut()
+        {
+            if !SUPPORTED_TYPES.contains(&type_name) {
+                continue;
+            }
+            let value = parse_reg_data(type_name, data)?;
+            let name = (name != "(Default)").then(|| name.to_owned());
+            contents.values.push((name, value));
+        }
+    }
+    Ok(listing)
+}
+
+fn value_name_args(name: Option<&str>) -> Vec<&str> {
+    match name {
+        Some(name) => vec!["/v", name],
+        None => vec!["/ve"],
+    }
+}
+
+impl Prefix {
+    /// Sets the value `name` of `key`, or the key's default value when `name`
+    /// is `None`, creating the key if needed.
+    pub fn set_registry_value(
+        &self,
+        key: &RegistryKey,
+        name: Option<&str>,
+        value: impl Into<RegistryValue>,
+    ) -> io::Result<()> {
+        let value = value.into();
+        let key = key.to_string();
+        let data = value.reg_data();
+        let mut args = vec!["add", &key];
+        args.extend(value_name_args(name));
+        args.extend(["/t", value.type_name(), "/d", &data, "/f"]);
+        trace::operation("set_registry_value", &self.path, None, || {
+            let _serialized = self.serialize_mutation()?;
+            self.run_builtin("reg", args)?;
+            self.emit_registry_modified(&key);
+            Ok(())
+        })
+    }
+
+    /// Runs `reg query` with `args`, returning its output or `None` if the
+    /// key or value does not exist.
+    fn reg_query(&self, args: &[&str]) -> io::Result<Option<String>> {
+        let output = self
+            .builtin_command("reg")
+            .arg("query")
+            .args(args)
+            .output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.to_lowercase().contains("unable to find") {
+                return Ok(None);
+            }
+            return Err(Error::ProcessCrashed {
+                program: "reg query".into(),
+                status: output.status,
+                stderr: stderr.trim().to_owned(),
+            }
+            .into());
+        }
+        Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned()))
+    }
+
+    /// Reads the value `name` of `key`, or the key's default value when `name`
+    /// is `None`, returning `None` if it does not exist.
+    pub fn registry_value(
+        &self,
+        key: &RegistryKey,
+        name: Option<&str>,
+    ) -> io::Result<Option<RegistryValue>> {
+        let key = key.to_string();
+        let mut args = vec![key.as_str()];
+        args.extend(value_name_args(name));
+        let Some(stdout) = self.reg_query(&args)? else {
+            return Ok(None);
+        };
+        let expected_name = name.unwrap_or("(Default)");
+        for line in stdout.lines() {
+            if let Some((name, type_name, data)) = parse_value_line(line)
+                && name.eq_ignore_ascii_case(expected_name)
+            {
+                return parse_reg_data(type_name, data).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn registry_key_exists(&self, key: &RegistryKey) -> io::Result<bool> {
+        Ok(self.reg_query(&[&key.to_string()])?.is_some())
+    }
+
+    /// The keys listed by `reg query` on `key`, failing with
+    /// [`Error::RegistryKeyMissing`] if it does not exist.
+    fn registry_listing(
+        &self,
+        key: &RegistryKey,
+        recursive: bool,
+    ) -> io::Result<Vec<RegistryKeyContents>> {
+        let key_string = key.to_string();
+        let mut args = vec![key_string.as_str()];
+        if recursive {
+            args.push("/s");
+        }
+        let stdout = self
+            .reg_query(&args)?
+            .ok_or_else(|| Error::RegistryKeyMissing(key_string.clone()))?;
+        parse_listing(&stdout)
+    }
+
+    pub fn delete_registry_value(
+        &self,
+        key: &RegistryKey,
+        name: Option<&str>,
+    ) -> io::Result<()> {
+        let key = key.to_string();
+        let mut args = vec!["delete", &key];
+        args.extend(value_name_args(name));
+        args.push("/f");
+        trace::operation("delete_registry_value", &self.path, None, || {
+            let _serialized = self.serialize_mutation()?;
+            self.run_builtin("reg", args)?;
+            self.emit_registry_modified(&key);
+            Ok(())
+        })
+    }
+
+    pub fn delete_registry_key(&self, key: &RegistryKey) -> io::Result<()> {
+        trace::operation("delete_registry_key", &self.path, None, || {
+            let _serialized = self.serialize_mutation()?;
+            self.run_builtin("reg", ["delete", &key.to_string(), "/f"])?;
+            self.emit_registry_modified(&key);
+            Ok(())
+        })
+    }
+
+    /// Imports `batch` with a single `regedit /S`.
+    pub fn apply_registry_batch(
+        &self,
+        batch: &RegistryBatch,
+    ) -> io::Result<()> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        let script = Script::Inline(batch.to_reg_file());
+        trace::operation("apply_registry_batch", &self.path, None, || {
+            let _serialized = self.serialize_mutation()?;
+            // regedit only reads non-ASCII data from UTF-16 files.
+            let (path, _temp) = self.stage_script(&script, "reg", |text| {
+                let mut source = vec![0xff, 0xfe];
+                for unit in text.encode_utf16() {
+                    source.extend_from_slice(&unit.to_le_bytes());
+                }
+                source
+            })?;
+            self.run_builtin("regedit", [OsStr::new("/S"), &path])?;
+            let mut modified: Vec<&RegistryKey> = Vec::new();
+            for operation in &batch.operations {
+                let (BatchOperation::Set(key, ..)
+                | BatchOperation::DeleteValue(key, _)
+                | BatchOperation::DeleteKey(key)) = operation;
+                if !modified.contains(&key) {
+                    self.emit_registry_modified(key);
+                    modified.push(key);
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RegistryKey, RegistryValue, parse_listing};
+    use crate::Error;
+
+    #[test]
+    fn listing_skips_unsupported_types_and_rejects_malformed_data() {
+        let output = "HKEY_CURRENT_USER\\Software\\Wine\n    \
+                      Version    REG_SZ    win10\n    \
+                      Blob    REG_RESOURCE_LIST    00ff\n    \
+                      (Default)    REG_DWORD    0x10\n";
+        let listing = parse_listing(output).unwrap();
+        assert_eq!(listing.len(), 1);
+        assert_eq!(listing[0].key, RegistryKey::current_user("Software\\Wine"));
+        assert_eq!(
+            listing[0].values,
+            [
+                (
+                    Some("Version".to_owned()),
+                    RegistryValue::String("win10".to_owned())
+                ),
+                (None, RegistryValue::DWord(16)),
+            ]
+        );
+
+        let output = "HKCU\\Software\n    Data    REG_BINARY    0g\n";
+        let error = parse_listing(output).unwrap_err();
+        assert!(matches!(Error::from(error), Error::RegistryParse(_)));
+    }
+}
@@ -0,0 +1,331 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::PathBuf,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use super::{Hive, RegistryKey, RegistryValue};
+use crate::Prefix;
+
+/// A difference between two readings of the registry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RegistryChange {
+    /// The values of an added key are reported as [`RegistryChange::ValueSet`].
+    KeyAdded(RegistryKey),
+    /// The values of a removed key are not reported separately.
+    KeyRemoved(RegistryKey),
+    ValueSet {
+        key: RegistryKey,
+        name: Option<String>,
+        old: Option<RegistryValue>,
+        new: RegistryValue,
+    },
+    ValueRemoved {
+        key: RegistryKey,
+        name: Option<String>,
+        old: RegistryValue,
+    },
+}
+
+/// Values by lowercased name, since names are case-insensitive.
+type Values = BTreeMap<Option<String>, (Option<String>, RegistryValue)>;
+
+/// Keys by their full path, lowercased.
+type Snapshot = BTreeMap<String, (RegistryKey, Values)>;
+
+/// Watches the registry files of a prefix for changes, created with
+/// [`Prefix::watch_registry`].
+///
+/// wineserver keeps the registry in memory and only saves it every so often
+/// and when it exits, so changes show up with a delay.
+#[derive(Debug)]
+pub struct RegistryWatcher {
+    files: Vec<(PathBuf, RegistryKey, Option<SystemTime>)>,
+    snapshot: Snapshot,
+}
+
+impl Prefix {
+    /// Starts watching for registry changes, relative to the registry as it is
+    /// saved now.
+    pub fn watch_registry(&self) -> io::Result<RegistryWatcher> {
+        let files = [
+            ("system.reg", RegistryKey::new(Hive::LocalMachine, "")),
+            ("user.reg", RegistryKey::new(Hive::CurrentUser, "")),
+            ("userdef.reg", RegistryKey::new(Hive::Users, ".Default")),
+        ]
+        .into_iter()
+        .map(|(file, root)| (self.path.join(file), root, None))
+        .collect();
+        let mut watcher = RegistryWatcher {
+            files,
+            snapshot: Snapshot::new(),
+        };
+        watcher.poll()?;
+        Ok(watcher)
+    }
+}
+
+impl RegistryWatcher {
+    /// The changes since the last call, or since the watcher was created.
+    /// Returns no changes without reading the files unless they were saved
+    /// since.
+    pub fn poll(&mut self) -> io::Result<Vec<RegistryChange>> {
+        let mut modified = false;
+        for (path, _, last_modified) in &mut self.files {
+            let current = match fs::metadata(path) {
+                Ok(metadata) => Some(metadata.modified()?),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+                Err(error) => return Err(error),
+            };
+            modified |= current != *last_modified;
+            *last_modified = current;
+        }
+        if !modified {
+            return Ok(Vec::new());
+        }
+
+        let mut snapshot = Snapshot::new();
+        for (path, root, _) in &self.files {
+            match fs::read(path) {
+                Ok(bytes) => read_hive(
+                    &String::from_utf8_lossy(&bytes),
+                    root,
+                    &mut snapshot,
+                ),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+                Err(error) => return Err(error),
+            }
+        }
+        let changes = diff(&self.snapshot, &snapshot);
+        self.snapshot = snapshot;
+        Ok(changes)
+    }
+
+    /// Polls every `interval` until something changes.
+    pub fn wait(
+        &mut self,
+        interval: Duration,
+    ) -> io::Result<Vec<RegistryChange>> {
+        loop {
+            let changes = self.poll()?;
+            if !changes.is_empty() {
+                return Ok(changes);
+            }
+            thread::sleep(interval);
+        }
+    }
+}
+
+fn diff(old: &Snapshot, new: &Snapshot) -> Vec<RegistryChange> {
+    let mut changes = Vec::new();
+    for (path, (key, old_values)) in old {
+        match new.get(path) {
+            None => changes.push(RegistryChange::KeyRemoved(key.clone())),
+            Some((_, new_values)) => {
+                for (lowercase, (name, old)) in old_values {
+                    if !new_values.contains_key(lowercase) {
+                        changes.push(RegistryChange::ValueRemoved {
+                            key: key.clone(),
+                            name: name.clone(),
+                            old: old.clone(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    for (path, (key, new_values)) in new {
+        let old_values = old.get(path).map(|(_, values)| values);
+        if old_values.is_none() {
+            changes.push(RegistryChange::KeyAdded(key.clone()));
+        }
+        for (lowercase, (name, new)) in new_values {
+            let old = old_values
+                .and_then(|values| values.get(lowercase))
+                .map(|(_, old)| old);
+            if old != Some(new) {
+                changes.push(RegistryChange::ValueSet {
+                    key: key.clone(),
+                    name: name.clone(),
+                    old: old.cloned(),
+                    new: new.clone(),
+                });
+            }
+        }
+    }
+    changes
+}
+
+/// Reads a registry file as saved by wineserver into `snapshot`, with its
+/// keys relative to `root`.
+fn read_hive(text: &str, root: &RegistryKey, snapshot: &mut Snapshot) {
+    let mut current: Option<String> = None;
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        // Long hex data continues on the following lines.
+        let mut line = line.to_owned();
+        while line.ends_with('\\') {
+            line.pop();
+            match lines.next() {
+                Some(next) => line.push_str(next.trim_start()),
+                None => break,
+            }
+        }
+
+        if let Some(header) = line.strip_prefix('[') {
+            let Some(end) = header.rfind(']') else {
+                continue;
+            };
+            let key = root.join(&unescape(&header[..end]));
+            let path = key.to_string().to_lowercase();
+            snapshot.entry(path.clone()).or_insert((key, Values::new()));
+            current = Some(path);
+        } else if let Some(path) = &current
+            && let Some((name, value)) = parse_value(&line)
+            && let Some((_, values)) = snapshot.get_mut(path)
+        {
+            let lowercase = name.as_ref().map(|name| name.to_lowercase());
+            values.insert(lowercase, (name, value));
+        }
+    }
+}
+
+/// Reverses the escaping of strings in registry files, which write
+/// characters outside of printable ASCII as UTF-16 code units of up to four
+/// hex digits, e.g., `\x00e9`.
+fn unescape(text: &str) -> String {
+    let mut units = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buffer = [0; 2];
+            units.extend_from_slice(c.encode_utf16(&mut buffer));
+            continue;
+        }
+        match chars.next() {
+            Some('n') => units.push(u16::from(b'\n')),
+            Some('r') => units.push(u16::from(b'\r')),
+            Some('t') => units.push(u16::from(b'\t')),
+            Some('0') => units.push(0),
+            Some('x') => {
+                let mut unit = 0;
+                for _ in 0..4 {
+                    match chars.peek().and_then(|c| c.to_digit(16)) {
+                        Some(digit) => unit = unit * 16 + digit as u16,
+                        None => break,
+                    }
+                    chars.next();
+                }
+                units.push(unit);
+            }
+            Some(c) => {
+                let mut buffer = [0; 2];
+                units.extend_from_slice(c.encode_utf16(&mut buffer));
+            }
+            None => {}
+        }
+    }
+    String::from_utf16_lossy(&units)
+}
+
+/// Splits a string that starts with a quote at its closing quote, returning
+/// the unescaped string and what follows.
+fn quoted(text: &str) -> Option<(String, &str)> {
+    let text = text.strip_prefix('"')?;
+    let mut escaped = false;
+    for (index, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some((unescape(&text[..index]), &text[index + 1..])),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_value(line: &str) -> Option<(Option<String>, RegistryValue)> {
+    let (name, rest) = match line.strip_prefix('@') {
+        Some(rest) => (None, rest),
+        None => {
+            let (name, rest) = quoted(line)?;
+            (Some(name), rest)
+        }
+    };
+    let data = rest.strip_prefix('=')?;
+    Some((name, parse_data(data)?))
+}
+
+fn utf16_strings(bytes: &[u8]) -> Vec<String> {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+    let mut strings: Vec<String> = units
+        .split(|&unit| unit == 0)
+        .map(String::from_utf16_lossy)
+        .collect();
+    while strings.last().is_some_and(String::is_empty) {
+        strings.pop();
+    }
+    strings
+}
+
+fn parse_data(data: &str) -> Option<RegistryValue> {
+    if data.starts_with('"') {
+        return Some(RegistryValue::String(quoted(data)?.0));
+    }
+    if let Some(dword) = data.strip_prefix("dword:") {
+        return u32::from_str_radix(dword, 16)
+            .ok()
+            .map(RegistryValue::DWord);
+    }
+    if let Some(rest) = data.strip_prefix("str(") {
+        let (kind, string) = rest.split_once("):")?;
+        let string = quoted(string)?.0;
+        return Some(match kind {
+            "2" => RegistryValue::ExpandString(string),
+            "7" => RegistryValue::MultiString(utf16_strings(
+                &string
+                    .encode_utf16()
+                    .flat_map(u16::to_le_bytes)
+                    .collect::<Vec<_>>(),
+            )),
+            _ => RegistryValue::String(string),
+        });
+    }
+
+    let (kind, hex) = match data.strip_prefix("hex:") {
+        Some(hex) => ("3", hex),
+        None => data.strip_prefix("hex(")?.split_once("):")?,
+    };
+    let bytes = hex
+        .split(',')
+        .filter(|byte| !byte.is_empty())
+        .map(|byte| u8::from_str_radix(byte.trim(), 16).ok())
+        .collect::<Option<Vec<_>>>()?;
+    Some(match kind {
+        "1" => RegistryValue::String(
+            utf16_strings(&bytes).into_iter().next().unwrap_or_default(),
+        ),
+        "2" => RegistryValue::ExpandString(
+            utf16_strings(&bytes).into_iter().next().unwrap_or_default(),
+        ),
+        "4" if bytes.len() == 4 => {
+            RegistryValue::DWord(u32::from_le_bytes(bytes.try_into().ok()?))
+        }
+        "7" => RegistryValue::MultiString(utf16_strings(&bytes)),
+        "b" if bytes.len() == 8 => {
+            RegistryValue::QWord(u64::from_le_bytes(bytes.try_into().ok()?))
+        }
+        _ => RegistryValue::Binary(bytes),
+    })
+}
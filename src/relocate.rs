@@ -0,0 +1,311 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    env,
+    ffi::OsString,
+    fs, io,
+    os::unix::fs::symlink,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    Prefix, REGISTRY_FILES, Shortcut,
+    store::{dir_var, home_dir},
+    tree,
+};
+
+/// What [`Prefix::export`] writes in place of the path of the prefix and of
+/// the home directory, which [`Prefix::relocate`] replaces with the new ones.
+const EXPORTED_PREFIX: &str = "/wine-rs-exported-prefix";
+const EXPORTED_HOME: &str = "/wine-rs-exported-home";
+
+/// A host path and what to replace it with.
+type PathMapping = (String, String);
+
+fn path_string(path: &Path) -> io::Result<String> {
+    path.to_str().map(ToOwned::to_owned).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("`{}` is not valid UTF-8", path.display()),
+        )
+    })
+}
+
+/// Whether `char` can continue a file name, in which case a path ending
+/// right before it is not a whole path.
+fn is_name_char(char: char) -> bool {
+    char.is_alphanumeric() || "._-+~@".contains(char)
+}
+
+/// Replaces every whole occurrence of the path `from` in `text` with `to`.
+fn replace_path(text: &str, from: &str, to: &str) -> String {
+    let mut replaced = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(index) = rest.find(from) {
+        let after = &rest[index + from.len()..];
+        replaced.push_str(&rest[..index]);
+        if after.chars().next().is_some_and(is_name_char) {
+            replaced.push_str(from);
+        } else {
+            replaced.push_str(to);
+        }
+        rest = after;
+    }
+    replaced.push_str(rest);
+    replaced
+}
+
+/// Rewrites the host paths in `text`, both as they are and as seen through
+/// `Z:`, escaped the way `.reg` files escape backslashes if `escaped`.
+fn rewrite_text(text: &str, mappings: &[PathMapping], escaped: bool) -> String {
+    let separator = if escaped { "\\\\" } else { "\\" };
+    let mut text = text.to_owned();
+    for (from, to) in mappings {
+        text = replace_path(&text, from, to);
+        for drive in ["Z:", "z:"] {
+            let windows =
+                |path: &str| format!("{drive}{}", path.replace('/', separator));
+            text = replace_path(&text, &windows(from), &windows(to));
+        }
+    }
+    text
+}
+
+fn rewrite_target(target: &Path, mappings: &[PathMapping]) -> Option<PathBuf> {
+    let target = target.to_str()?;
+    let rewritten = mappings
+        .iter()
+        .fold(target.to_owned(), |target, (from, to)| {
+            replace_path(&target, from, to)
+        });
+    (rewritten != target).then(|| rewritten.into())
+}
+
+/// Writes `contents` to `path` if they changed, replacing the file rather
+/// than writing into it, since it may share its data with a snapshot.
+fn replace_file(path: &Path, old: &[u8], contents: &[u8]) -> io::Result<()> {
+    if old == contents {
+        return Ok(());
+    }
+    let permissions = fs::metadata(path)?.permissions();
+    fs::remove_file(path)?;
+    fs::write(path, contents)?;
+    fs::set_permissions(path, permissions)
+}
+
+/// Rewrites the host paths in the symlinks, registry files, and `.lnk` files
+/// under `dir`, which includes the drive mappings and snapshots of a prefix.
+fn rewrite_tree(dir: &Path, mappings: &[PathMapping]) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        if file_type.is_symlink() {
+            if let Some(target) =
+                rewrite_target(&fs::read_link(&path)?, mappings)
+            {
+                fs::remove_file(&path)?;
+                symlink(target, &path)?;
+            }
+        } else if file_type.is_dir() {
+            rewrite_tree(&path, mappings)?;
+        } else if !file_type.is_file() {
+            continue;
+        } else if entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| REGISTRY_FILES.contains(&name))
+        {
+            let old = fs::read(&path)?;
+            let text = String::from_utf8_lossy(&old);
+            replace_file(
+                &path,
+                &old,
+                rewrite_text(&text, mappings, true).as_bytes(),
+            )?;
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("lnk"))
+        {
+            let old = fs::read(&path)?;
+            // Shortcuts this cannot read are left as they are.
+            let Ok(mut shortcut) = Shortcut::from_bytes(&old) else {
+                continue;
+            };
+            let original = shortcut.clone();
+            let rewrite = |text: &mut String| {
+                *text = rewrite_text(text, mappings, false);
+            };
+            rewrite(&mut shortcut.target);
+            rewrite(&mut shortcut.arguments);
+            if let Some(working_dir) = &mut shortcut.working_dir {
+                rewrite(working_dir);
+            }
+            if let Some((icon, _)) = &mut shortcut.icon {
+                rewrite(icon);
+            }
+            if shortcut != original {
+                replace_file(&path, &old, &shortcut.to_bytes()?)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rewrites the menu entries that Wine created on the host for programs in
+/// the prefix, which launch them through its path.
+fn rewrite_menu_entries(mappings: &[PathMapping]) -> io::Result<()> {
+    fn walk(dir: &Path, mappings: &[PathMapping]) -> io::Result<()> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok(());
+            }
+            Err(error) => return Err(error),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                walk(&path, mappings)?;
+            } else if file_type.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|extension| extension == "desktop")
+            {
+                let old = fs::read(&path)?;
+                let text = String::from_utf8_lossy(&old);
+                replace_file(
+                    &path,
+                    &old,
+                    rewrite_text(&text, mappings, false).as_bytes(),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    let data_dir = match dir_var("XDG_DATA_HOME") {
+        Some(data_home) => data_home,
+        None => home_dir()?.join(".local/share"),
+    };
+    walk(&data_dir.join("applications/wine"), mappings)
+}
+
+impl Prefix {
+    fn exported_marker(&self) -> PathBuf {
+        self.metadata_dir().join("exported")
+    }
+
+    /// This prefix at `path`, with Wine binaries and libraries that were
+    /// inside it moved along.
+    fn moved_to(&self, path: &Path) -> Prefix {
+        let rebase = |binary: &OsString| -> OsString {
+            match Path::new(binary).strip_prefix(&self.path) {
+                Ok(relative) => path.join(relative).into(),
+                Err(_) => binary.clone(),
+            }
+        };
+        let dynamic_library_paths = env::join_paths(
+            env::split_paths(&self.dynamic_library_paths)
+                .map(|library_path| rebase(&library_path.into())),
+        )
+        .unwrap_or_else(|_| self.dynamic_library_paths.clone());
+        Prefix {
+            path: path.to_path_buf(),
+            dynamic_library_paths,
+            wine: rebase(&self.wine),
+            wine64: rebase(&self.wine64),
+            wineserver: rebase(&self.wineserver),
+            regedit: rebase(&self.regedit),
+            ..self.clone()
+        }
+    }
+
+    /// Moves the prefix to `new_path`, which must not exist, and rewrites the
+    /// absolute host paths that refer to its old location: drive mappings
+    /// and other symlinks, registry values, shortcuts, including those of
+    /// its snapshots, and the menu entries Wine created on the host. Returns
+    /// the prefix at its new location. Fails while the wineserver is
+    /// running.
+    ///
+    /// A copy made with [`Prefix::export`] is relocated from the machine it
+    /// was exported on instead, and may be relocated in place by passing
+    /// its own path. Paths with non-ASCII characters, which the registry
+    /// stores escaped, are not rewritten there.
+    pub fn relocate(&self, new_path: impl AsRef<Path>) -> io::Result<Prefix> {
+        self.check_exists()?;
+        let new_path = std::path::absolute(new_path)?;
+        let _lock = self.lock()?;
+        self.check_stopped("relocate the prefix")?;
+        if new_path != self.path {
+            if fs::symlink_metadata(&new_path).is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("`{}` already exists", new_path.display()),
+                ));
+            }
+            if let Some(parent) = new_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            match fs::rename(&self.path, &new_path) {
+                Err(error) if error.kind() == io::ErrorKind::CrossesDevices => {
+                    tree::copy_tree(&self.path, &new_path)?;
+                    tree::remove_tree(&self.path)?;
+                }
+                result => result?,
+            }
+        }
+
+        let prefix = self.moved_to(&new_path);
+        let marker = prefix.exported_marker();
+        let new = path_string(&new_path)?;
+        if marker.is_file() {
+            let mappings = [
+                (EXPORTED_PREFIX.to_owned(), new),
+                (EXPORTED_HOME.to_owned(), path_string(&home_dir()?)?),
+            ];
+            rewrite_tree(&new_path, &mappings)?;
+            fs::remove_file(marker)?;
+        } else if new_path != self.path {
+            let mappings = [(path_string(&self.path)?, new)];
+            rewrite_tree(&new_path, &mappings)?;
+            rewrite_menu_entries(&mappings)?;
+        }
+        Ok(prefix)
+    }
+
+    /// Copies the prefix to `destination`, which must not exist, with the
+    /// host paths that refer to the prefix or to the home directory replaced
+    /// by placeholders, so that it can be moved to another account or
+    /// machine and set up there with [`Prefix::relocate`]. Fails while the
+    /// wineserver is running.
+    pub fn export(&self, destination: impl AsRef<Path>) -> io::Result<()> {
+        let destination = destination.as_ref();
+        self.check_exists()?;
+        let _lock = self.lock()?;
+        self.check_stopped("export the prefix")?;
+        let mut mappings =
+            vec![(path_string(&self.path)?, EXPORTED_PREFIX.to_owned())];
+        if let Ok(home) = home_dir() {
+            mappings.push((path_string(&home)?, EXPORTED_HOME.to_owned()));
+        }
+
+        tree::clone_tree(&self.path, destination)?;
+        let exported = self.moved_to(destination);
+        let result = rewrite_tree(destination, &mappings).and_then(|()| {
+            fs::create_dir_all(exported.metadata_dir())?;
+            fs::write(exported.exported_marker(), "")
+        });
+        if let Err(error) = result {
+            tree::remove_tree(destination)?;
+            return Err(error);
+        }
+        Ok(())
+    }
+}
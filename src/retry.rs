@@ -0,0 +1,101 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{io, process, thread, time::Duration};
+
+use crate::Prefix;
+
+/// What Wine prints when a launch loses the race against a wineserver that is
+/// starting up or shutting down.
+const TRANSIENT_MESSAGES: &[&str] = &[
+    "a wine server seems to be running, but I cannot connect to it",
+    "could not connect to wineserver",
+    "the wineserver is shutting down",
+];
+
+/// How often to retry launches that fail for reasons that go away on their
+/// own, doubling the delay after each attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RetryPolicy {
+    /// Including the first attempt.
+    pub max_attempts: u32,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(4),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Calls `attempt` until it succeeds, fails for a non-transient reason, or
+    /// runs out of attempts, returning the last result.
+    pub(crate) fn run<T>(
+        &self,
+        mut attempt: impl FnMut() -> io::Result<T>,
+        is_transient: impl Fn(&T) -> bool,
+    ) -> io::Result<T> {
+        let mut delay = self.initial_delay;
+        let mut attempts = 1;
+        loop {
+            let result = attempt();
+            let should_retry = match &result {
+                Ok(value) => is_transient(value),
+                Err(error) => is_transient_error(error),
+            };
+            if !should_retry || attempts >= self.max_attempts {
+                return result;
+            }
+            #[cfg(feature = "tracing")]
+            tracing::debug!(attempts, ?delay, "retrying transient failure");
+            thread::sleep(delay);
+            delay = (delay * 2).min(self.max_delay);
+            attempts += 1;
+        }
+    }
+}
+
+/// Spawn errors that come from racing a Wine update replacing binaries or
+/// from momentary resource exhaustion.
+fn is_transient_error(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::ExecutableFileBusy
+            | io::ErrorKind::ResourceBusy
+            | io::ErrorKind::WouldBlock
+            | io::ErrorKind::Interrupted
+    )
+}
+
+pub(crate) fn is_transient_output(output: &process::Output) -> bool {
+    if output.status.success() {
+        return false;
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    TRANSIENT_MESSAGES
+        .iter()
+        .any(|message| stderr.contains(message))
+}
+
+impl Prefix {
+    /// Applies [`crate::PrefixConfig::retry`], if any, to `attempt`.
+    pub(crate) fn with_retry<T>(
+        &self,
+        mut attempt: impl FnMut() -> io::Result<T>,
+        is_transient: impl Fn(&T) -> bool,
+    ) -> io::Result<T> {
+        match &self.config.retry {
+            Some(policy) => policy.run(attempt, is_transient),
+            None => attempt(),
+        }
+    }
+}
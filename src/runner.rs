@@ -0,0 +1,151 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    ffi::OsStr,
+    fmt,
+    hash::{Hash, Hasher},
+    io,
+    path::Path,
+    process::{self, ExitStatus},
+    sync::Arc,
+};
+
+use crate::{Error, ResolvedCommand};
+
+/// Executes the processes that [`crate::Prefix`] operations launch.
+///
+/// Replacing the default [`SystemRunner`] through
+/// [`crate::Prefix::with_runner`] lets code that drives Wine be exercised
+/// without Wine installed, e.g., by recording the commands instead.
+pub trait CommandRunner: Send + Sync {
+    fn spawn(&self, command: &ResolvedCommand) -> io::Result<process::Child>;
+
+    fn status(&self, command: &ResolvedCommand) -> io::Result<ExitStatus>;
+
+    fn output(&self, command: &ResolvedCommand) -> io::Result<process::Output>;
+}
+
+/// Runs commands as real child processes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SystemRunner;
+
+impl CommandRunner for SystemRunner {
+    fn spawn(&self, command: &ResolvedCommand) -> io::Result<process::Child> {
+        command
+            .to_command()
+            .spawn()
+            .map_err(|error| spawn_error(&command.program, error))
+    }
+
+    fn status(&self, command: &ResolvedCommand) -> io::Result<ExitStatus> {
+        command
+            .to_command()
+            .status()
+            .map_err(|error| spawn_error(&command.program, error))
+    }
+
+    fn output(&self, command: &ResolvedCommand) -> io::Result<process::Output> {
+        command
+            .to_command()
+            .output()
+            .map_err(|error| spawn_error(&command.program, error))
+    }
+}
+
+/// Names the binary that could not be found, which the bare OS error does
+/// not.
+pub(crate) fn spawn_error(program: &OsStr, error: io::Error) -> io::Error {
+    let path = Path::new(program);
+    if error.kind() == io::ErrorKind::NotFound
+        && path.components().count() > 1
+        && !path.exists()
+    {
+        Error::WineNotFound(path.to_path_buf()).into()
+    } else {
+        error
+    }
+}
+
+/// Shared handle to a runner, compared by identity so that [`crate::Prefix`]
+/// keeps its derives.
+#[derive(Clone)]
+pub(crate) struct Runner(pub(crate) Arc<dyn CommandRunner>);
+
+impl Default for Runner {
+    fn default() -> Self {
+        Self(Arc::new(SystemRunner))
+    }
+}
+
+impl fmt::Debug for Runner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Runner(..)")
+    }
+}
+
+impl PartialEq for Runner {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Runner {}
+
+impl Hash for Runner {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).cast::<()>().hash(state);
+    }
+}
+
+/// Records the commands it is given instead of running them, reporting
+/// success.
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct RecordingRunner {
+    pub(crate) commands: std::sync::Mutex<Vec<ResolvedCommand>>,
+}
+
+#[cfg(test)]
+impl RecordingRunner {
+    /// Makes `prefix` record its launches in the returned runner.
+    pub(crate) fn attach(
+        mut prefix: crate::Prefix,
+    ) -> (crate::Prefix, Arc<Self>) {
+        let runner = Arc::new(Self::default());
+        prefix.runner = Runner(runner.clone());
+        (prefix, runner)
+    }
+
+    pub(crate) fn take(&self) -> Vec<ResolvedCommand> {
+        std::mem::take(&mut self.commands.lock().unwrap())
+    }
+
+    fn record(&self, command: &ResolvedCommand) {
+        self.commands.lock().unwrap().push(command.clone());
+    }
+}
+
+#[cfg(test)]
+impl CommandRunner for RecordingRunner {
+    fn spawn(&self, command: &ResolvedCommand) -> io::Result<process::Child> {
+        self.record(command);
+        process::Command::new("true").spawn()
+    }
+
+    fn status(&self, command: &ResolvedCommand) -> io::Result<ExitStatus> {
+        self.record(command);
+        Ok(std::os::unix::process::ExitStatusExt::from_raw(0))
+    }
+
+    fn output(&self, command: &ResolvedCommand) -> io::Result<process::Output> {
+        Ok(process::Output {
+            status: self.status(command)?,
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        })
+    }
+}
@@ -0,0 +1,399 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Installing redistributable runtimes, e.g., the Visual C++ libraries, into a
+//! prefix without shelling out to winetricks.
+//!
+//! Installers are downloaded once into a [`Cache`] with `curl` and checked
+//! against their SHA-256 before every run. Microsoft replaces some of them in
+//! place, so the checksums are given by the caller rather than pinned here.
+
+use std::{
+    env,
+    ffi::OsStr,
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    CancelHandle, DllOverride, DllOverrideMode, Error, Prefix,
+    registry::{RegistryKey, RegistryValue},
+    sha256, store,
+};
+
+/// How often a download checks whether it was cancelled.
+const DOWNLOAD_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Exit code of installers that succeeded but want a reboot, which Wine does
+/// not need.
+const REBOOT_REQUIRED: i32 = 3010;
+
+/// A registry value that the installer of a runtime writes, through which
+/// installations made by other tools are recognized too.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Detection {
+    /// Under `HKEY_LOCAL_MACHINE\Software`.
+    pub key: String,
+    /// The value holding the installed version.
+    pub value: String,
+    /// Whether the key is written by a 32-bit installer, which lands under
+    /// `Wow6432Node` in 64-bit prefixes.
+    pub redirected: bool,
+}
+
+impl Detection {
+    fn registry_key(&self, prefix: &Prefix) -> RegistryKey {
+        let software = if self.redirected && prefix.is_64_bit() {
+            "Software\\Wow6432Node"
+        } else {
+            "Software"
+        };
+        RegistryKey::local_machine(format!("{software}\\{}", self.key))
+    }
+}
+
+/// A redistributable and how to install it silently.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Runtime {
+    /// The winetricks verb for the runtime, e.g., `vcrun2022`.
+    pub name: String,
+    pub url: String,
+    /// The SHA-256 of the installer, in hex.
+    pub sha256: String,
+    pub args: Vec<String>,
+    /// For redistributables that only extract themselves through `args`, the
+    /// Windows path of the setup they extracted and its arguments.
+    pub setup: Option<(String, Vec<String>)>,
+    /// `None` if only installations through [`install`] are recognized.
+    pub detection: Option<Detection>,
+    /// Libraries that Wine implements itself, which are loaded from the
+    /// runtime instead once it is installed.
+    pub dll_overrides: Vec<String>,
+}
+
+impl Runtime {
+    pub fn new(
+        name: impl Into<String>,
+        url: impl Into<String>,
+        sha256: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            url: url.into(),
+            sha256: sha256.into(),
+            args: Vec::new(),
+            setup: None,
+            detection: None,
+            dll_overrides: Vec::new(),
+        }
+    }
+
+    fn visual_cpp(arch: &str, sha256: &str) -> Self {
+        let mut dll_overrides = vec![
+            "concrt140",
+            "msvcp140",
+            "msvcp140_1",
+            "msvcp140_2",
+            "vcamp140",
+            "vcomp140",
+            "vcruntime140",
+        ];
+        if arch == "x64" {
+            dll_overrides.push("vcruntime140_1");
+        }
+        Self {
+            args: vec!["/install".into(), "/quiet".into(), "/norestart".into()],
+            detection: Some(Detection {
+                key: format!(
+                    "Microsoft\\VisualStudio\\14.0\\VC\\Runtimes\\{arch}"
+                ),
+                value: "Version".into(),
+                redirected: true,
+            }),
+            dll_overrides: dll_overrides.into_iter().map(Into::into).collect(),
+            ..Self::new(
+                "vcrun2022",
+                format!("https://aka.ms/vs/17/release/vc_redist.{arch}.exe"),
+                sha256,
+            )
+        }
+    }
+
+    /// The Visual C++ 2015-2022 libraries for 64-bit programs.
+    pub fn vcrun2022_x64(sha256: &str) -> Self {
+        Self::visual_cpp("x64", sha256)
+    }
+
+    /// The Visual C++ 2015-2022 libraries for 32-bit programs.
+    pub fn vcrun2022_x86(sha256: &str) -> Self {
+        Self {
+            name: "vcrun2022_x86".into(),
+            ..Self::visual_cpp("x86", sha256)
+        }
+    }
+
+    /// The .NET Framework 4.8, which replaces wine-mono for the prefix.
+    pub fn dotnet48(sha256: &str) -> Self {
+        Self {
+            args: vec!["/q".into(), "/norestart".into()],
+            detection: Some(Detection {
+                key: "Microsoft\\NET Framework Setup\\NDP\\v4\\Full".into(),
+                value: "Version".into(),
+                redirected: false,
+            }),
+            dll_overrides: vec!["mscoree".into()],
+            ..Self::new(
+                "dotnet48",
+                "https://download.visualstudio.microsoft.com/download/pr/\
+                 7afca223-55d2-470a-8edc-6a1739ae3252/\
+                 abd170b4b0ec15ad0222a809b761a036/\
+                 ndp48-x86-x64-allos-enu.exe",
+                sha256,
+            )
+        }
+    }
+
+    /// The June 2010 DirectX end-user runtime, whose D3DX, XAudio, and
+    /// XInput libraries some older games need.
+    pub fn directx_jun2010(sha256: &str) -> Self {
+        let extracted = "C:\\windows\\temp\\directx_Jun2010";
+        Self {
+            args: vec!["/Q".into(), format!("/T:{extracted}")],
+            setup: Some((
+                format!("{extracted}\\DXSETUP.exe"),
+                vec!["/silent".into()],
+            )),
+            dll_overrides: (24..=43)
+                .map(|version| format!("d3dx9_{version}"))
+                .chain(["d3dcompiler_43".into(), "xinput1_3".into()])
+                .collect(),
+            ..Self::new(
+                "directx_jun2010",
+                "https://download.microsoft.com/download/8/4/A/\
+                 84A35BF1-DAFE-4AE8-82AF-AD2AE20B6B14/\
+                 directx_Jun2010_redist.exe",
+                sha256,
+            )
+        }
+    }
+
+    fn file_name(&self) -> &str {
+        let path = self.url.split(['?', '#']).next().unwrap_or_default();
+        match path.rsplit('/').next() {
+            Some(file_name) if !file_name.is_empty() => file_name,
+            _ => "installer.exe",
+        }
+    }
+}
+
+/// Where downloaded installers are kept, one directory per runtime.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub fn at(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// `$XDG_CACHE_HOME/wine-rs/runtimes`, falling back to `~/.cache`.
+    pub fn user() -> io::Result<Self> {
+        let cache_home = match env::var_os("XDG_CACHE_HOME") {
+            Some(cache_home) if !cache_home.is_empty() => {
+                PathBuf::from(cache_home)
+            }
+            _ => store::home_dir()?.join(".cache"),
+        };
+        Ok(Self::at(cache_home.join("wine-rs/runtimes")))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The installer of `runtime`, downloaded unless a copy with the right
+    /// checksum is already cached.
+    pub fn fetch(&self, runtime: &Runtime) -> io::Result<PathBuf> {
+        self.fetch_cancellable(runtime, None)
+    }
+
+    /// Like [`Cache::fetch`], but stops the download once `cancel` is
+    /// cancelled.
+    fn fetch_cancellable(
+        &self,
+        runtime: &Runtime,
+        cancel: Option<&CancelHandle>,
+    ) -> io::Result<PathBuf> {
+        let dir = self.dir.join(&runtime.name);
+        let installer = dir.join(runtime.file_name());
+        if installer.is_file() {
+            if sha256::file_hex(&installer)?
+                .eq_ignore_ascii_case(&runtime.sha256)
+            {
+                return Ok(installer);
+            }
+            fs::remove_file(&installer)?;
+        }
+
+        fs::create_dir_all(&dir)?;
+        let partial = dir.join(format!("{}.part", runtime.file_name()));
+        let mut curl = Command::new("curl")
+            .args(["--fail", "--location", "--silent", "--show-error"])
+            .arg("--output")
+            .arg(&partial)
+            .arg(&runtime.url)
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let status = loop {
+            if let Some(status) = curl.try_wait()? {
+                break status;
+            }
+            if let Some(cancel) = cancel
+                && let Err(error) = cancel.check()
+            {
+                let _ = curl.kill();
+                let _ = curl.wait();
+                let _ = fs::remove_file(&partial);
+                return Err(error);
+            }
+            thread::sleep(DOWNLOAD_POLL_INTERVAL);
+        };
+        if !status.success() {
+            let _ = fs::remove_file(&partial);
+            let mut stderr = String::new();
+            if let Some(mut pipe) = curl.stderr.take() {
+                let _ = pipe.read_to_string(&mut stderr);
+            }
+            return Err(Error::ProcessCrashed {
+                program: "curl".into(),
+                status,
+                stderr: stderr.trim().to_owned(),
+            }
+            .into());
+        }
+
+        let sha256 = sha256::file_hex(&partial)?;
+        if !sha256.eq_ignore_ascii_case(&runtime.sha256) {
+            fs::remove_file(&partial)?;
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "the installer of {} has SHA-256 {sha256}, expected {}",
+                    runtime.name, runtime.sha256
+                ),
+            ));
+        }
+        fs::rename(&partial, &installer)?;
+        Ok(installer)
+    }
+}
+
+fn marker(prefix: &Prefix, runtime: &Runtime) -> PathBuf {
+    prefix.metadata_dir().join("runtimes").join(&runtime.name)
+}
+
+/// The installed version of `runtime`, which is the checksum of its installer
+/// if it was installed through [`install`] without a [`Detection`], or `None`
+/// if it is not installed.
+pub fn installed_version(
+    prefix: &Prefix,
+    runtime: &Runtime,
+) -> io::Result<Option<String>> {
+    if let Some(detection) = &runtime.detection {
+        let version = prefix
+            .registry_value(
+                &detection.registry_key(prefix),
+                Some(&detection.value),
+            )?
+            .and_then(|value| match value {
+                RegistryValue::String(version)
+                | RegistryValue::ExpandString(version) => Some(version),
+                RegistryValue::DWord(version) => Some(version.to_string()),
+                RegistryValue::QWord(version) => Some(version.to_string()),
+                _ => None,
+            });
+        if version.is_some() {
+            return Ok(version);
+        }
+    }
+    match fs::read_to_string(marker(prefix, runtime)) {
+        Ok(sha256) => Ok(Some(sha256.trim().to_owned())),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Runs a silent installer, tolerating requests for a reboot.
+fn run_installer(
+    prefix: &Prefix,
+    program: impl AsRef<OsStr>,
+    args: &[String],
+) -> io::Result<()> {
+    let mut command = prefix.builtin_command(&program);
+    command.args(args);
+    let output = command.output()?;
+    if output.status.success() || output.status.code() == Some(REBOOT_REQUIRED)
+    {
+        return Ok(());
+    }
+    Err(Error::ProcessCrashed {
+        program: program.as_ref().to_os_string(),
+        status: output.status,
+        stderr: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+    }
+    .into())
+}
+
+/// Downloads `runtime` into `cache` if needed and installs it, doing nothing
+/// if it is already installed.
+pub fn install(
+    prefix: &Prefix,
+    runtime: &Runtime,
+    cache: &Cache,
+) -> io::Result<()> {
+    if installed_version(prefix, runtime)?.is_some() {
+        return Ok(());
+    }
+    let name = &runtime.name;
+    let total = if runtime.setup.is_some() { 4 } else { 3 };
+    prefix.report_progress(&format!("downloading {name}"), 0, total);
+    let installer = cache.fetch_cancellable(runtime, prefix.cancel())?;
+    prefix.check_cancelled()?;
+    prefix.report_progress(&format!("installing {name}"), 1, total);
+    run_installer(prefix, &installer, &runtime.args)?;
+    if let Some((setup, args)) = &runtime.setup {
+        prefix.check_cancelled()?;
+        prefix.report_progress(
+            &format!("running the setup of {name}"),
+            2,
+            total,
+        );
+        run_installer(prefix, setup, args)?;
+    }
+    prefix.check_cancelled()?;
+    prefix.report_progress(&format!("configuring {name}"), total - 1, total);
+
+    for dll in &runtime.dll_overrides {
+        prefix.set_dll_override(&DllOverride::new(
+            dll.as_str(),
+            DllOverrideMode::NativeThenBuiltin,
+        ))?;
+    }
+
+    let marker = marker(prefix, runtime);
+    fs::create_dir_all(marker.parent().expect("joined above"))?;
+    fs::write(marker, &runtime.sha256)?;
+    prefix.report_progress(&format!("installed {name}"), total, total);
+    Ok(())
+}
@@ -0,0 +1,80 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{Prefix, registry::RegistryKey};
+
+/// The shell namespace extension that shows the host filesystem in Explorer.
+const UNIXFS_NAMESPACE_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\Desktop\\Namespace\\{9D20AAE8-0625-44B0-9CA7-71889C2254D9}";
+
+/// Replaces each symlink in `dir` that leads outside `prefix_path` with an
+/// empty directory.
+fn unlink_host_folders(dir: &Path, prefix_path: &Path) -> io::Result<()> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(error) => return Err(error),
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if !path.is_symlink() {
+            continue;
+        }
+        let is_inside_prefix = fs::canonicalize(&path)
+            .is_ok_and(|target| target.starts_with(prefix_path));
+        if !is_inside_prefix {
+            fs::remove_file(&path)?;
+            fs::create_dir(&path)?;
+        }
+    }
+    Ok(())
+}
+
+impl Prefix {
+    /// Removes the `Z:` drive and replaces the user folders linked into the
+    /// host home directory with real folders, like `winetricks sandbox`.
+    ///
+    /// This also disables automatic prefix updates, which would otherwise
+    /// restore the `Z:` drive. It is not a security boundary: Windows programs
+    /// can still make host system calls.
+    pub fn sandbox(&self) -> io::Result<()> {
+        let _lock = self.lock()?;
+        let prefix_path = fs::canonicalize(&self.path)?;
+
+        let root_drive = self.path.join("dosdevices").join("z:");
+        if root_drive.is_symlink() {
+            fs::remove_file(root_drive)?;
+        }
+
+        if let Ok(users) = fs::read_dir(self.drive_c().join("users")) {
+            for user in users {
+                unlink_host_folders(&user?.path(), &prefix_path)?;
+            }
+        }
+
+        let unixfs = RegistryKey::local_machine(UNIXFS_NAMESPACE_KEY);
+        if self.registry_key_exists(&unixfs)? {
+            self.delete_registry_key(&unixfs)?;
+        }
+
+        fs::write(self.path.join(".update-timestamp"), "disable\n")?;
+        fs::create_dir_all(self.metadata_dir())?;
+        fs::write(self.sandbox_marker(), "")
+    }
+
+    /// Whether [`Prefix::sandbox`] was applied to this prefix.
+    pub fn is_sandboxed(&self) -> bool {
+        self.sandbox_marker().is_file()
+    }
+
+    fn sandbox_marker(&self) -> PathBuf {
+        self.metadata_dir().join("sandboxed")
+    }
+}
@@ -0,0 +1,69 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{ffi::OsStr, io, path::Path};
+
+use crate::{Error, Prefix, WineWindow, window::host_tool};
+
+/// Captures the X11 window `id`, or the whole screen for `root`, with
+/// ImageMagick, which picks the format from the extension of `output_path`.
+fn import(window: &str, output_path: &Path) -> io::Result<()> {
+    host_tool(
+        "import",
+        "ImageMagick",
+        [
+            OsStr::new("-window"),
+            OsStr::new(window),
+            output_path.as_os_str(),
+        ],
+    )
+    .map(drop)
+}
+
+impl Prefix {
+    /// Saves a picture of the virtual desktop of the prefix to `output_path`,
+    /// e.g., to attach to the results of an automated test, or of the whole
+    /// screen if it shows no virtual desktop. The format follows the
+    /// extension, e.g., `.png`.
+    ///
+    /// This uses ImageMagick under X11 and `screencapture` on macOS, which
+    /// always captures the whole screen.
+    pub fn screenshot(&self, output_path: impl AsRef<Path>) -> io::Result<()> {
+        let output_path = output_path.as_ref();
+        if cfg!(target_os = "macos") {
+            return host_tool(
+                "screencapture",
+                "macOS",
+                [OsStr::new("-x"), output_path.as_os_str()],
+            )
+            .map(drop);
+        }
+        match self
+            .windows()?
+            .into_iter()
+            .find(WineWindow::is_virtual_desktop)
+        {
+            Some(desktop) => self.screenshot_window(&desktop, output_path),
+            None => import("root", output_path),
+        }
+    }
+
+    /// Saves a picture of `window`, e.g., from [`Prefix::find_window`], to
+    /// `output_path` like [`Prefix::screenshot`]. Only X11 is supported.
+    pub fn screenshot_window(
+        &self,
+        window: &WineWindow,
+        output_path: impl AsRef<Path>,
+    ) -> io::Result<()> {
+        if cfg!(target_os = "macos") {
+            return Err(Error::Unsupported(
+                "windows can only be captured under X11".into(),
+            )
+            .into());
+        }
+        import(&format!("{:#x}", window.id), output_path.as_ref())
+    }
+}
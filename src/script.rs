@@ -0,0 +1,163 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    ffi::OsString,
+    fs, io,
+    path::{self, Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{Error, Prefix};
+
+/// A script to run inside the prefix.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Script {
+    /// A script on the host, run where it is so that it can find files next
+    /// to it. It must be inside `drive_c` in a sandboxed prefix.
+    File(PathBuf),
+    /// Source code, written to the guest temporary directory to run it.
+    Inline(String),
+}
+
+/// A script written to `C:\windows\temp`, removed once it has run.
+pub(crate) struct TempScript {
+    path: PathBuf,
+}
+
+impl Drop for TempScript {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Embedded in the stand-in files Wine puts in `system32` for the programs it
+/// implements itself.
+const WINE_FAKE_MARKERS: &[&[u8]] =
+    &[b"Wine builtin DLL", b"Wine placeholder DLL"];
+
+/// Distinguishes the temporary scripts of concurrent runs.
+static NEXT_SCRIPT_ID: AtomicUsize = AtomicUsize::new(0);
+
+impl Prefix {
+    /// The path through which Windows programs see `path`, which is on `C:`
+    /// inside `drive_c` and on the `Z:` drive everywhere else.
+    pub fn windows_path(&self, path: impl AsRef<Path>) -> OsString {
+        let path = path.as_ref();
+        let absolute =
+            |path: &Path| path::absolute(path).unwrap_or(path.to_path_buf());
+        match absolute(path).strip_prefix(absolute(&self.drive_c())) {
+            Ok(relative) => {
+                let mut windows_path = OsString::from("C:");
+                for component in relative.components() {
+                    windows_path.push("\\");
+                    windows_path.push(component.as_os_str());
+                }
+                if relative.as_os_str().is_empty() {
+                    windows_path.push("\\");
+                }
+                windows_path
+            }
+            Err(_) => crate::host_to_windows_path(path),
+        }
+    }
+
+    /// Returns the Windows path of `script`, writing it to the guest
+    /// temporary directory with `extension` first if it is inline.
+    pub(crate) fn stage_script(
+        &self,
+        script: &Script,
+        extension: &str,
+        source: impl FnOnce(&str) -> Vec<u8>,
+    ) -> io::Result<(OsString, Option<TempScript>)> {
+        match script {
+            Script::File(path) => Ok((self.windows_path(path), None)),
+            Script::Inline(code) => {
+                let temp_dir = self.windows_dir().join("temp");
+                fs::create_dir_all(&temp_dir)?;
+                let path = temp_dir.join(format!(
+                    "wine-rs-{}-{}.{extension}",
+                    process::id(),
+                    NEXT_SCRIPT_ID.fetch_add(1, Ordering::Relaxed)
+                ));
+                fs::write(&path, source(code))?;
+                Ok((self.windows_path(&path), Some(TempScript { path })))
+            }
+        }
+    }
+
+    /// Runs a `.bat` or `.cmd` script with `cmd /c`, capturing its output. The
+    /// exit status is that of the script.
+    pub fn run_batch(&self, script: &Script) -> io::Result<process::Output> {
+        let (path, _temp) = self.stage_script(script, "bat", |code| {
+            // cmd expects DOS line endings.
+            code.lines()
+                .flat_map(|line| [line, "\r\n"])
+                .collect::<String>()
+                .into_bytes()
+        })?;
+        self.builtin_command("cmd").arg("/c").arg(path).output()
+    }
+
+    /// The PowerShell installed in the prefix, preferring PowerShell 7 over
+    /// Windows PowerShell. Wine's own stub `powershell.exe` does not count.
+    pub fn powershell(&self) -> Option<PathBuf> {
+        let drive_c = self.drive_c();
+        let mut candidates = Vec::new();
+        if let Ok(versions) =
+            fs::read_dir(drive_c.join("Program Files/PowerShell"))
+        {
+            let mut versions: Vec<_> =
+                versions.flatten().map(|entry| entry.path()).collect();
+            versions.sort();
+            candidates.extend(
+                versions.into_iter().rev().map(|dir| dir.join("pwsh.exe")),
+            );
+        }
+        candidates
+            .push(drive_c.join(
+                "windows/system32/WindowsPowerShell/v1.0/powershell.exe",
+            ));
+        candidates.into_iter().find(|candidate| {
+            fs::read(candidate).is_ok_and(|image| {
+                !WINE_FAKE_MARKERS.iter().any(|marker| {
+                    image.windows(marker.len()).any(|window| window == *marker)
+                })
+            })
+        })
+    }
+
+    /// Runs a PowerShell script with [`Prefix::powershell`], capturing its
+    /// output, or fails with [`Error::NotInstalled`] if none is installed.
+    pub fn run_powershell(
+        &self,
+        script: &Script,
+    ) -> io::Result<process::Output> {
+        let powershell = self.powershell().ok_or_else(|| {
+            Error::NotInstalled("PowerShell in this prefix".to_owned())
+        })?;
+        let (path, _temp) = self.stage_script(script, "ps1", |code| {
+            // Windows PowerShell reads scripts without a BOM as ANSI.
+            let mut source = "\u{feff}".as_bytes().to_vec();
+            for line in code.lines() {
+                source.extend_from_slice(line.as_bytes());
+                source.extend_from_slice(b"\r\n");
+            }
+            source
+        })?;
+        self.builtin_command(self.windows_path(powershell))
+            .args([
+                "-NoProfile",
+                "-NonInteractive",
+                "-ExecutionPolicy",
+                "Bypass",
+            ])
+            .arg("-File")
+            .arg(path)
+            .output()
+    }
+}
@@ -0,0 +1,72 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+/// Implements [`serde::Serialize`] with the `serde` feature for a struct, as
+/// a map of the listed fields.
+macro_rules! serialize_fields {
+    ($type:ident { $($field:ident),* $(,)? }) => {
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $type {
+            fn serialize<S: serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                use serde::ser::SerializeStruct;
+
+                let mut state = serializer.serialize_struct(
+                    stringify!($type),
+                    [$(stringify!($field)),*].len(),
+                )?;
+                $(state.serialize_field(stringify!($field), &self.$field)?;)*
+                state.end()
+            }
+        }
+    };
+}
+
+/// Implements [`serde::Serialize`] with the `serde` feature for a type, as
+/// the string its `$method` returns.
+macro_rules! serialize_as_str {
+    ($type:ident, $method:ident) => {
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $type {
+            fn serialize<S: serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.$method())
+            }
+        }
+    };
+}
+
+pub(crate) use serialize_as_str;
+pub(crate) use serialize_fields;
+
+#[cfg(feature = "json")]
+/// Renders the results of the query APIs, e.g., [`crate::Prefix::processes`]
+/// or [`crate::Prefix::verify`], as JSON for external tooling.
+///
+/// Structs serialize as maps of their fields, and enums with data as maps
+/// whose `type` field is the variant name in snake case, like
+/// [`crate::PrefixEvent`].
+pub trait ToJson {
+    fn to_json(&self) -> std::io::Result<String>;
+
+    /// Indented, for people to read.
+    fn to_json_pretty(&self) -> std::io::Result<String>;
+}
+
+#[cfg(feature = "json")]
+impl<T: serde::Serialize + ?Sized> ToJson for T {
+    fn to_json(&self) -> std::io::Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    fn to_json_pretty(&self) -> std::io::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
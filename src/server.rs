@@ -0,0 +1,344 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    fs,
+    io::{self, BufRead, BufReader},
+    os::unix::{fs::MetadataExt, net::UnixStream},
+    path::PathBuf,
+    process::{self, ExitStatus},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{Error, Prefix, installation, trace, tree};
+
+/// Where a wineserver started by [`Prefix::start_server`] writes its debug
+/// log.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub enum ServerLog {
+    /// The stderr of the current process.
+    #[default]
+    Inherit,
+    File(PathBuf),
+    /// Lines sent to [`ServerProcess::log`].
+    Channel,
+}
+
+/// How long a wineserver keeps running after the last process in its prefix
+/// exits, instead of the default few seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Persistence {
+    Forever,
+    For(Duration),
+}
+
+impl Persistence {
+    fn to_arg(self) -> String {
+        match self {
+            Self::Forever => "-p".to_owned(),
+            Self::For(duration) => format!("-p{}", duration.as_secs()),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct ServerOptions {
+    /// Passed as `-d<level>`, where higher levels log more of the requests
+    /// the server handles.
+    pub debug_level: Option<u8>,
+    pub log: ServerLog,
+    pub persistence: Option<Persistence>,
+}
+
+/// How far [`Prefix::stop`] had to go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StopStage {
+    /// No wineserver was running.
+    AlreadyStopped,
+    /// Every program exited after being asked to end the session.
+    EndedSession,
+    /// The wineserver was still running after the grace period, or the
+    /// session could not be ended, and was killed along with any programs
+    /// left.
+    Killed,
+}
+
+impl trace::Outcome for StopStage {}
+
+/// How often [`Prefix::stop`] checks whether `wineserver -w` returned.
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A wineserver running in the foreground as a child of this process.
+#[derive(Debug)]
+pub struct ServerProcess {
+    child: process::Child,
+    log: Option<mpsc::Receiver<String>>,
+}
+
+impl ServerProcess {
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// The log lines captured with [`ServerLog::Channel`].
+    pub fn log(&self) -> Option<&mpsc::Receiver<String>> {
+        self.log.as_ref()
+    }
+
+    /// Waits for the server to exit, which it does once the last process in
+    /// the prefix has.
+    pub fn wait(&mut self) -> io::Result<ExitStatus> {
+        self.child.wait()
+    }
+
+    pub fn kill(&mut self) -> io::Result<()> {
+        self.child.kill()
+    }
+}
+
+impl Prefix {
+    /// The directory where the wineserver for this prefix puts its socket,
+    /// which Wine derives from the device and inode of the prefix.
+    pub(crate) fn server_dir(&self) -> io::Result<PathBuf> {
+        let metadata = fs::metadata(&self.path)?;
+        Ok(PathBuf::from(format!(
+            "/tmp/.wine-{}/server-{:x}-{:x}",
+            metadata.uid(),
+            metadata.dev(),
+            metadata.ino()
+        )))
+    }
+
+    /// Whether a wineserver is serving this prefix, and hence whether Windows
+    /// processes may be running in it.
+    pub fn is_server_running(&self) -> bool {
+        self.server_dir()
+            .is_ok_and(|dir| UnixStream::connect(dir.join("socket")).is_ok())
+    }
+
+    /// The `/proc` entry of the wineserver serving this prefix, which runs
+    /// from [`Prefix::server_dir`]. Only Linux exposes this.
+    fn server_process(&self) -> Option<PathBuf> {
+        let server_dir = self.server_dir().ok()?;
+        fs::read_dir("/proc")
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path())
+            .find(|process| {
+                fs::read_link(process.join("cwd"))
+                    .is_ok_and(|cwd| cwd == server_dir)
+            })
+    }
+
+    /// The binary of the wineserver serving this prefix. Only Linux exposes
+    /// this.
+    fn running_server_binary(&self) -> Option<PathBuf> {
+        fs::read_link(self.server_process()?.join("exe")).ok()
+    }
+
+    /// Removes [`Prefix::server_dir`] if a wineserver that crashed or was
+    /// killed left it behind, which otherwise leads to confusing errors about
+    /// the server protocol. The directory only counts as stale when nothing
+    /// listens on its socket and, on Linux, no process runs from it. Returns
+    /// whether it was removed.
+    pub fn cleanup_stale_server_state(&self) -> io::Result<bool> {
+        let _lock = self.lock()?;
+        let server_dir = self.server_dir()?;
+        if !server_dir.is_dir()
+            || self.is_server_running()
+            || self.server_process().is_some()
+        {
+            return Ok(false);
+        }
+        tree::remove_tree(&server_dir)?;
+        Ok(true)
+    }
+
+    /// Fails unless the prefix is either not running or served by the Wine
+    /// build this [`Prefix`] is configured with, which destructive operations
+    /// check so that two builds never work on the prefix at once. When the
+    /// running server cannot be inspected, a prefix whose `.update-timestamp`
+    /// names another build counts as served by it.
+    pub fn check_ownership(&self) -> io::Result<()> {
+        if !self.is_server_running() {
+            return Ok(());
+        }
+        match self.running_server_binary() {
+            Some(binary) => {
+                if fs::canonicalize(&binary).ok()
+                    == fs::canonicalize(&self.wineserver).ok()
+                {
+                    return Ok(());
+                }
+                installation::check_versions(
+                    self.version_output(&binary).as_deref(),
+                    self.version_output(&self.wineserver).as_deref(),
+                )
+                .map_err(|mismatch| Error::VersionMismatch(mismatch).into())
+            }
+            None if self.needs_update().unwrap_or(false) => {
+                Err(Error::PrefixBusy(
+                    "work on a prefix last updated by another Wine build"
+                        .to_owned(),
+                )
+                .into())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Starts the wineserver for this prefix in the foreground so its debug
+    /// log, which never appears in `WINEDEBUG` output, can be captured.
+    pub fn start_server(
+        &self,
+        options: &ServerOptions,
+    ) -> io::Result<ServerProcess> {
+        if self.is_server_running() {
+            return Err(Error::PrefixBusy(
+                "start another wineserver".to_owned(),
+            )
+            .into());
+        }
+
+        let mut command = self.wineserver_command("-f");
+        if let Some(level) = options.debug_level {
+            command.args.push(format!("-d{level}").into());
+        }
+        if let Some(persistence) = options.persistence {
+            command.args.push(persistence.to_arg().into());
+        }
+        let log_file = match &options.log {
+            ServerLog::Inherit => None,
+            ServerLog::File(path) => Some(fs::File::create(path)?),
+            ServerLog::Channel => None,
+        };
+        command.capture_stderr = options.log != ServerLog::Inherit;
+
+        let mut child = trace::operation(
+            "start_server",
+            &self.path,
+            Some(&self.wineserver),
+            || self.runner.0.spawn(&command),
+        )?;
+        let stderr = child.stderr.take();
+        let log = match (stderr, log_file) {
+            (Some(mut stderr), Some(mut file)) => {
+                thread::spawn(move || io::copy(&mut stderr, &mut file));
+                None
+            }
+            (Some(stderr), None) => {
+                let (sender, receiver) = mpsc::channel();
+                thread::spawn(move || {
+                    for line in BufReader::new(stderr).lines() {
+                        let Ok(line) = line else { break };
+                        if sender.send(line).is_err() {
+                            break;
+                        }
+                    }
+                });
+                Some(receiver)
+            }
+            (None, _) => None,
+        };
+        Ok(ServerProcess { child, log })
+    }
+
+    /// Shuts the prefix down like Windows would, giving programs up to `grace`
+    /// to save their data and exit before killing whatever is left, unlike
+    /// [`Prefix::kill_all`].
+    ///
+    /// A wineserver started with [`Persistence`] does not exit on its own, so
+    /// it is always killed once its programs are gone.
+    pub fn stop(&self, grace: Duration) -> io::Result<StopStage> {
+        trace::operation("stop", &self.path, None, || {
+            if !self.is_server_running() {
+                return Ok(StopStage::AlreadyStopped);
+            }
+            let deadline = Instant::now() + grace;
+            // A session that could not be ended is escalated like one that
+            // takes too long to end.
+            if self
+                .run_builtin("wineboot", ["--end-session", "--shutdown"])
+                .is_ok()
+                && self.wait_for_server_exit(deadline)
+            {
+                return Ok(StopStage::EndedSession);
+            }
+            self.kill_all()?;
+            Ok(StopStage::Killed)
+        })
+    }
+
+    /// Whether the wineserver exits before `deadline`, as told by
+    /// `wineserver -w`, which is killed otherwise.
+    fn wait_for_server_exit(&self, deadline: Instant) -> bool {
+        let mut wait = self.wineserver_command("-w");
+        wait.capture_output = true;
+        let Ok(mut waiter) = self.runner.0.spawn(&wait) else {
+            return false;
+        };
+        loop {
+            match waiter.try_wait() {
+                Ok(Some(_)) => return true,
+                Ok(None) if Instant::now() < deadline => {
+                    thread::sleep(STOP_POLL_INTERVAL);
+                }
+                _ => break,
+            }
+        }
+        let _ = waiter.kill();
+        let _ = waiter.wait();
+        false
+    }
+
+    /// Starts the wineserver and the loader ahead of time so the first real
+    /// launch does not absorb their startup latency. The server is kept alive
+    /// according to `persistence`, unless one was already running.
+    pub fn warm_up(&self, persistence: Persistence) -> io::Result<()> {
+        trace::operation("warm_up", &self.path, Some(&self.wineserver), || {
+            if !self.is_server_running() {
+                let command = self.wineserver_command(&persistence.to_arg());
+                let status = self.runner.0.status(&command)?;
+                if !status.success() {
+                    return Err(Error::ServerStartFailed(status).into());
+                }
+            }
+            self.run_builtin("cmd", ["/c", "exit"])?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsString;
+
+    use crate::{PrefixBuilder, runner::RecordingRunner};
+
+    use super::{Persistence, ServerOptions};
+
+    #[test]
+    fn start_server_goes_through_the_runner() {
+        let (prefix, runner) =
+            RecordingRunner::attach(PrefixBuilder::new("/prefix").build());
+
+        let mut server = prefix
+            .start_server(&ServerOptions {
+                debug_level: Some(2),
+                persistence: Some(Persistence::Forever),
+                ..ServerOptions::default()
+            })
+            .unwrap();
+        server.child.wait().unwrap();
+
+        let commands = runner.take();
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].program, prefix.wineserver);
+        assert_eq!(commands[0].args, ["-f", "-d2", "-p"].map(OsString::from));
+    }
+}
@@ -0,0 +1,207 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::io;
+
+use crate::{
+    Error, Prefix,
+    registry::{RegistryKey, RegistryValue},
+    serialize::{serialize_as_str, serialize_fields},
+};
+
+/// The `Type` bits of a service that runs in a process of its own or shares
+/// one with other services, as opposed to a driver.
+const SERVICE_WIN32: u32 = 0x10 | 0x20;
+
+/// When the service control manager starts a service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ServiceStartType {
+    /// With the system, which only drivers use.
+    Boot,
+    System,
+    /// When the wineserver starts, i.e., with the first program launched in
+    /// the prefix.
+    Automatic,
+    /// Only when a program or [`Prefix::start_service`] asks for it.
+    Manual,
+    Disabled,
+}
+
+impl ServiceStartType {
+    /// The `Start` value in the registry.
+    pub fn to_u32(&self) -> u32 {
+        match self {
+            Self::Boot => 0,
+            Self::System => 1,
+            Self::Automatic => 2,
+            Self::Manual => 3,
+            Self::Disabled => 4,
+        }
+    }
+
+    pub fn from_u32(value: u32) -> Option<Self> {
+        Some(match value {
+            0 => Self::Boot,
+            1 => Self::System,
+            2 => Self::Automatic,
+            3 => Self::Manual,
+            4 => Self::Disabled,
+            _ => return None,
+        })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Boot => "boot",
+            Self::System => "system",
+            Self::Automatic => "automatic",
+            Self::Manual => "manual",
+            Self::Disabled => "disabled",
+        }
+    }
+}
+
+serialize_as_str!(ServiceStartType, as_str);
+
+/// A Windows service registered in a prefix, e.g., the updater of an
+/// application.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GuestService {
+    /// The key name, by which the service is started and stopped.
+    pub name: String,
+    pub display_name: Option<String>,
+    /// The command line of the service, e.g.,
+    /// `C:\Program Files\App\updater.exe --service`.
+    pub image_path: Option<String>,
+    pub start_type: ServiceStartType,
+    pub is_running: bool,
+}
+
+serialize_fields!(GuestService {
+    name,
+    display_name,
+    image_path,
+    start_type,
+    is_running
+});
+
+impl GuestService {
+    /// The name Windows shows for the service.
+    pub fn display_name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.name)
+    }
+}
+
+fn services_key() -> RegistryKey {
+    RegistryKey::local_machine("System\\CurrentControlSet\\Services")
+}
+
+/// Parses the output of `net start`, which lists the display names of the
+/// running services indented under a header.
+fn parse_net_start(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter(|line| line.starts_with([' ', '\t']))
+        .map(|line| line.trim().to_owned())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+impl Prefix {
+    /// The services registered in the prefix, sorted by name, leaving out
+    /// drivers. This starts the wineserver, and with it the services set to
+    /// start automatically, to tell which are running.
+    pub fn services(&self) -> io::Result<Vec<GuestService>> {
+        let running = self.run_builtin("net", ["start"])?;
+        let running =
+            parse_net_start(&String::from_utf8_lossy(&running.stdout));
+        let services_key = services_key();
+        let mut services = Vec::new();
+        for contents in services_key.walk(self)? {
+            let Some(name) = contents
+                .key
+                .path()
+                .strip_prefix(services_key.path())
+                .and_then(|rest| rest.strip_prefix('\\'))
+                .filter(|name| !name.contains('\\'))
+            else {
+                continue;
+            };
+            let (mut kind, mut start_type) = (None, None);
+            let (mut display_name, mut image_path) = (None, None);
+            for (value_name, value) in contents.values {
+                let Some(value_name) = value_name else {
+                    continue;
+                };
+                match (value_name.to_ascii_lowercase().as_str(), value) {
+                    ("type", RegistryValue::DWord(value)) => kind = Some(value),
+                    ("start", RegistryValue::DWord(value)) => {
+                        start_type = ServiceStartType::from_u32(value);
+                    }
+                    ("displayname", RegistryValue::String(value)) => {
+                        display_name = Some(value);
+                    }
+                    (
+                        "imagepath",
+                        RegistryValue::String(value)
+                        | RegistryValue::ExpandString(value),
+                    ) => image_path = Some(value),
+                    _ => {}
+                }
+            }
+            let (Some(kind), Some(start_type)) = (kind, start_type) else {
+                continue;
+            };
+            if kind & SERVICE_WIN32 == 0 {
+                continue;
+            }
+            let mut service = GuestService {
+                name: name.to_owned(),
+                display_name,
+                image_path,
+                start_type,
+                is_running: false,
+            };
+            service.is_running = running.iter().any(|running| {
+                running.eq_ignore_ascii_case(service.display_name())
+            });
+            services.push(service);
+        }
+        services.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(services)
+    }
+
+    /// Starts the service `name`, waiting until it runs, with `net start`.
+    pub fn start_service(&self, name: &str) -> io::Result<()> {
+        self.run_builtin("net", ["start", name]).map(drop)
+    }
+
+    /// Stops the service `name` and the services that depend on it with
+    /// `net stop`.
+    pub fn stop_service(&self, name: &str) -> io::Result<()> {
+        self.run_builtin("net", ["stop", name]).map(drop)
+    }
+
+    /// Unregisters the service `name` with `sc delete`, once it has stopped.
+    pub fn delete_service(&self, name: &str) -> io::Result<()> {
+        self.run_builtin("sc", ["delete", name]).map(drop)
+    }
+
+    /// Changes when the service `name` starts, e.g., to keep one from
+    /// starting with every launch, which takes effect the next time the
+    /// wineserver starts.
+    pub fn set_service_start_type(
+        &self,
+        name: &str,
+        start_type: ServiceStartType,
+    ) -> io::Result<()> {
+        let key = services_key().join(name);
+        if !self.registry_key_exists(&key)? {
+            return Err(Error::RegistryKeyMissing(key.to_string()).into());
+        }
+        self.set_registry_value(&key, Some("Start"), start_type.to_u32())
+    }
+}
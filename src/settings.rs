@@ -0,0 +1,527 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! The options of `winecfg`, set through the registry instead of its GUI.
+
+use std::io;
+
+use crate::{
+    DllOverride, DllOverrideMode, Error, Prefix, dxvk,
+    registry::{RegistryKey, RegistryValue},
+    serialize::serialize_as_str,
+};
+
+/// The DPI Windows assumes when `LogPixels` is unset.
+pub const DEFAULT_DPI: u32 = 96;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WindowsVersion {
+    Windows11,
+    Windows10,
+    Windows81,
+    Windows8,
+    Windows7,
+    Vista,
+    Xp,
+    Windows2000,
+    Windows98,
+}
+
+impl WindowsVersion {
+    /// The spelling used by Wine's `Version` registry values.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Windows11 => "win11",
+            Self::Windows10 => "win10",
+            Self::Windows81 => "win81",
+            Self::Windows8 => "win8",
+            Self::Windows7 => "win7",
+            Self::Vista => "vista",
+            Self::Xp => "winxp",
+            Self::Windows2000 => "win2k",
+            Self::Windows98 => "win98",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            Self::Windows11,
+            Self::Windows10,
+            Self::Windows81,
+            Self::Windows8,
+            Self::Windows7,
+            Self::Vista,
+            Self::Xp,
+            Self::Windows2000,
+            Self::Windows98,
+        ]
+        .into_iter()
+        .find(|version| version.as_str().eq_ignore_ascii_case(name))
+    }
+}
+
+serialize_as_str!(WindowsVersion, as_str);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AudioDriver {
+    Pulse,
+    Alsa,
+    Oss,
+    CoreAudio,
+    /// Disables audio entirely, which avoids errors on hosts without a sound
+    /// server such as containers.
+    None,
+}
+
+impl AudioDriver {
+    /// The spelling used by the `Drivers` registry key.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pulse => "pulse",
+            Self::Alsa => "alsa",
+            Self::Oss => "oss",
+            Self::CoreAudio => "coreaudio",
+            Self::None => "",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "pulse" => Some(Self::Pulse),
+            "alsa" => Some(Self::Alsa),
+            "oss" => Some(Self::Oss),
+            "coreaudio" => Some(Self::CoreAudio),
+            "" => Some(Self::None),
+            _ => None,
+        }
+    }
+}
+
+serialize_as_str!(AudioDriver, as_str);
+
+/// A backend of Wine's windowing, which Wine tries in the order listed by
+/// [`Prefix::set_graphics_drivers`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GraphicsDriver {
+    X11,
+    /// Experimental, and only tried if listed.
+    Wayland,
+    /// The native macOS driver.
+    Mac,
+    /// No display at all.
+    Null,
+}
+
+impl GraphicsDriver {
+    /// The spelling used by the `Drivers` registry key.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::X11 => "x11",
+            Self::Wayland => "wayland",
+            Self::Mac => "mac",
+            Self::Null => "null",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "x11" => Some(Self::X11),
+            "wayland" => Some(Self::Wayland),
+            "mac" => Some(Self::Mac),
+            "null" => Some(Self::Null),
+            _ => None,
+        }
+    }
+}
+
+serialize_as_str!(GraphicsDriver, as_str);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Theme {
+    /// The classic look without visual styles.
+    None,
+    /// The visual style that ships with Wine.
+    Light,
+    /// A `.msstyles` file, given as a Windows path.
+    Custom(String),
+}
+
+/// The backend that implements Direct3D.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direct3DRenderer {
+    /// wined3d on top of OpenGL.
+    OpenGl,
+    /// wined3d on top of Vulkan.
+    Vulkan,
+    /// wined3d without hardware acceleration.
+    NoAcceleration,
+    /// The DLLs installed by [`dxvk::install`].
+    Dxvk,
+}
+
+impl Direct3DRenderer {
+    /// The spelling used by the `renderer` registry value, which does not
+    /// apply to [`Direct3DRenderer::Dxvk`].
+    pub fn as_str(&self) -> Option<&'static str> {
+        match self {
+            Self::OpenGl => Some("gl"),
+            Self::Vulkan => Some("vulkan"),
+            Self::NoAcceleration => Some("no3d"),
+            Self::Dxvk => None,
+        }
+    }
+}
+
+fn wine_key() -> RegistryKey {
+    RegistryKey::current_user("Software\\Wine")
+}
+
+/// The settings that apply only to programs whose executable is named `exe`,
+/// e.g., `setup.exe`, overriding the ones of the whole prefix.
+pub(crate) fn app_defaults_key(exe: &str) -> RegistryKey {
+    wine_key().join("AppDefaults").join(exe)
+}
+
+fn direct3d_key() -> RegistryKey {
+    wine_key().join("Direct3D")
+}
+
+fn drivers_key() -> RegistryKey {
+    wine_key().join("Drivers")
+}
+
+fn x11_driver_key() -> RegistryKey {
+    wine_key().join("X11 Driver")
+}
+
+fn desktop_key() -> RegistryKey {
+    RegistryKey::current_user("Control Panel\\Desktop")
+}
+
+/// Deletes `name` from `key` unless it is already absent, since `reg delete`
+/// fails on missing values.
+fn delete_if_present(
+    prefix: &Prefix,
+    key: &RegistryKey,
+    name: &str,
+) -> io::Result<()> {
+    if prefix.registry_value(key, Some(name))?.is_some() {
+        prefix.delete_registry_value(key, Some(name))?;
+    }
+    Ok(())
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value { "Y" } else { "N" }
+}
+
+impl Prefix {
+    /// The Windows version reported to every program in the prefix.
+    pub fn set_windows_version(
+        &self,
+        version: WindowsVersion,
+    ) -> io::Result<()> {
+        self.set_registry_value(&wine_key(), Some("Version"), version.as_str())
+    }
+
+    /// The Windows version reported to programs whose executable is named
+    /// `exe`, e.g., Windows XP to a legacy tool in a Windows 10 prefix.
+    pub fn set_app_windows_version(
+        &self,
+        exe: &str,
+        version: WindowsVersion,
+    ) -> io::Result<()> {
+        self.set_registry_value(
+            &app_defaults_key(exe),
+            Some("Version"),
+            version.as_str(),
+        )
+    }
+
+    /// The version set with [`Prefix::set_app_windows_version`], or `None`
+    /// if `exe` sees the version of the prefix.
+    pub fn app_windows_version(
+        &self,
+        exe: &str,
+    ) -> io::Result<Option<WindowsVersion>> {
+        match self.registry_value(&app_defaults_key(exe), Some("Version"))? {
+            Some(RegistryValue::String(name)) => {
+                WindowsVersion::from_name(&name).map(Some).ok_or_else(|| {
+                    Error::RegistryParse(format!(
+                        "unknown Windows version `{name}`"
+                    ))
+                    .into()
+                })
+            }
+            Some(_) => {
+                Err(Error::RegistryParse("Version is not a REG_SZ".to_owned())
+                    .into())
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Makes `exe` see the Windows version of the prefix again.
+    pub fn remove_app_windows_version(&self, exe: &str) -> io::Result<()> {
+        if !self.registry_key_exists(&app_defaults_key(exe))? {
+            return Ok(());
+        }
+        delete_if_present(self, &app_defaults_key(exe), "Version")
+    }
+
+    /// Every executable with a version set through
+    /// [`Prefix::set_app_windows_version`], with the version, skipping ones
+    /// Wine would not understand either.
+    pub fn app_windows_versions(
+        &self,
+    ) -> io::Result<Vec<(String, WindowsVersion)>> {
+        let app_defaults = wine_key().join("AppDefaults");
+        if !self.registry_key_exists(&app_defaults)? {
+            return Ok(Vec::new());
+        }
+        let mut versions = Vec::new();
+        for contents in app_defaults.walk(self)?.skip(1) {
+            let Some((parent, exe)) = contents.key.path().rsplit_once('\\')
+            else {
+                continue;
+            };
+            if !parent.eq_ignore_ascii_case(app_defaults.path()) {
+                continue;
+            }
+            let version =
+                contents.values.iter().find_map(|(name, value)| {
+                    match (name.as_deref(), value) {
+                        (Some(name), RegistryValue::String(version))
+                            if name.eq_ignore_ascii_case("Version") =>
+                        {
+                            WindowsVersion::from_name(version)
+                        }
+                        _ => None,
+                    }
+                });
+            if let Some(version) = version {
+                versions.push((exe.to_owned(), version));
+            }
+        }
+        Ok(versions)
+    }
+
+    pub fn set_audio_driver(&self, driver: AudioDriver) -> io::Result<()> {
+        self.set_registry_value(&drivers_key(), Some("Audio"), driver.as_str())
+    }
+
+    /// Makes Wine try `drivers` in order, e.g., Wayland when there is no X
+    /// server for [`GraphicsDriver::X11`] to connect to. Empty restores the
+    /// default order of the build.
+    pub fn set_graphics_drivers(
+        &self,
+        drivers: &[GraphicsDriver],
+    ) -> io::Result<()> {
+        if drivers.is_empty() {
+            return delete_if_present(self, &drivers_key(), "Graphics");
+        }
+        let drivers = drivers
+            .iter()
+            .map(GraphicsDriver::as_str)
+            .collect::<Vec<_>>()
+            .join(",");
+        self.set_registry_value(&drivers_key(), Some("Graphics"), drivers)
+    }
+
+    /// The drivers listed by [`Prefix::set_graphics_drivers`], which is empty
+    /// if Wine uses its default order.
+    pub fn graphics_drivers(&self) -> io::Result<Vec<GraphicsDriver>> {
+        match self.registry_value(&drivers_key(), Some("Graphics"))? {
+            Some(RegistryValue::String(drivers)) => drivers
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(|name| {
+                    GraphicsDriver::from_name(name).ok_or_else(|| {
+                        Error::RegistryParse(format!(
+                            "unknown graphics driver `{name}`"
+                        ))
+                        .into()
+                    })
+                })
+                .collect(),
+            Some(_) => {
+                Err(Error::RegistryParse("Graphics is not a REG_SZ".to_owned())
+                    .into())
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// The configured audio driver, or `None` if Wine picks one itself. When
+    /// several drivers are listed, the first one Wine tries is returned.
+    pub fn audio_driver(&self) -> io::Result<Option<AudioDriver>> {
+        match self.registry_value(&drivers_key(), Some("Audio"))? {
+            Some(RegistryValue::String(drivers)) => {
+                let first = drivers.split(',').next().unwrap_or_default();
+                AudioDriver::from_name(first.trim()).map(Some).ok_or_else(
+                    || {
+                        Error::RegistryParse(format!(
+                            "unknown audio driver `{first}`"
+                        ))
+                        .into()
+                    },
+                )
+            }
+            Some(_) => {
+                Err(Error::RegistryParse("Audio is not a REG_SZ".to_owned())
+                    .into())
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Whether full-screen windows capture the mouse.
+    pub fn set_mouse_capture(&self, is_enabled: bool) -> io::Result<()> {
+        self.set_registry_value(
+            &x11_driver_key(),
+            Some("GrabFullscreen"),
+            yes_no(is_enabled),
+        )
+    }
+
+    /// Whether the window manager draws title bars and borders.
+    pub fn set_window_decorations(&self, is_enabled: bool) -> io::Result<()> {
+        self.set_registry_value(
+            &x11_driver_key(),
+            Some("Decorated"),
+            yes_no(is_enabled),
+        )
+    }
+
+    /// Whether the window manager controls the windows.
+    pub fn set_window_manager_control(
+        &self,
+        is_enabled: bool,
+    ) -> io::Result<()> {
+        self.set_registry_value(
+            &x11_driver_key(),
+            Some("Managed"),
+            yes_no(is_enabled),
+        )
+    }
+
+    /// Confines every program to a virtual desktop of the given size, or lets
+    /// windows appear on the host desktop when `size` is `None`.
+    pub fn set_virtual_desktop(
+        &self,
+        size: Option<(u32, u32)>,
+    ) -> io::Result<()> {
+        let explorer = wine_key().join("Explorer");
+        match size {
+            Some((width, height)) => {
+                self.set_registry_value(&explorer, Some("Desktop"), "Default")?;
+                self.set_registry_value(
+                    &explorer.join("Desktops"),
+                    Some("Default"),
+                    format!("{width}x{height}"),
+                )
+            }
+            None => delete_if_present(self, &explorer, "Desktop"),
+        }
+    }
+
+    pub fn set_theme(&self, theme: &Theme) -> io::Result<()> {
+        let key = RegistryKey::current_user(
+            "Software\\Microsoft\\Windows\\CurrentVersion\\ThemeManager",
+        );
+        let dll_name = match theme {
+            Theme::None => {
+                return self.set_registry_value(&key, Some("ThemeActive"), "0");
+            }
+            Theme::Light => {
+                "%SystemRoot%\\resources\\themes\\light\\light.msstyles"
+            }
+            Theme::Custom(path) => path,
+        };
+        self.set_registry_value(&key, Some("DllName"), dll_name)?;
+        self.set_registry_value(&key, Some("ColorName"), "NormalColor")?;
+        self.set_registry_value(&key, Some("SizeName"), "NormalSize")?;
+        self.set_registry_value(&key, Some("ThemeActive"), "1")
+    }
+
+    /// Scales fonts and DPI-aware programs, where 96 is 100%.
+    pub fn set_dpi(&self, dpi: u32) -> io::Result<()> {
+        self.set_registry_value(&desktop_key(), Some("LogPixels"), dpi)?;
+        // Older Wine releases and some programs read the hardware profile.
+        self.set_registry_value(
+            &RegistryKey::local_machine(
+                "System\\CurrentControlSet\\Hardware Profiles\\Current\\Software\\Fonts",
+            ),
+            Some("LogPixels"),
+            dpi,
+        )
+    }
+
+    pub fn dpi(&self) -> io::Result<u32> {
+        match self.registry_value(&desktop_key(), Some("LogPixels"))? {
+            Some(RegistryValue::DWord(dpi)) => Ok(dpi),
+            Some(_) => Err(Error::RegistryParse(
+                "LogPixels is not a REG_DWORD".to_owned(),
+            )
+            .into()),
+            None => Ok(DEFAULT_DPI),
+        }
+    }
+
+    /// Selects the Direct3D backend. Choosing wined3d in a prefix with DXVK
+    /// installed overrides the DXVK DLLs back to the builtin ones, and
+    /// choosing DXVK fails unless it is installed.
+    pub fn set_direct3d_renderer(
+        &self,
+        renderer: Direct3DRenderer,
+    ) -> io::Result<()> {
+        let has_dxvk = dxvk::installed(self)?.is_some();
+        let mode = match renderer.as_str() {
+            Some(name) => {
+                self.set_registry_value(
+                    &direct3d_key(),
+                    Some("renderer"),
+                    name,
+                )?;
+                if !has_dxvk {
+                    return Ok(());
+                }
+                DllOverrideMode::Builtin
+            }
+            None => {
+                if !has_dxvk {
+                    return Err(Error::NotInstalled(
+                        "DXVK in this prefix".to_owned(),
+                    )
+                    .into());
+                }
+                delete_if_present(self, &direct3d_key(), "renderer")?;
+                DllOverrideMode::Native
+            }
+        };
+        for dll in dxvk::DLLS {
+            self.set_dll_override(&DllOverride::new(*dll, mode))?;
+        }
+        Ok(())
+    }
+
+    /// Caps the OpenGL version wined3d requests, or lifts the cap when
+    /// `version` is `None`.
+    pub fn set_max_gl_version(
+        &self,
+        version: Option<(u16, u16)>,
+    ) -> io::Result<()> {
+        match version {
+            Some((major, minor)) => self.set_registry_value(
+                &direct3d_key(),
+                Some("MaxVersionGL"),
+                (u32::from(major) << 16) | u32::from(minor),
+            ),
+            None => delete_if_present(self, &direct3d_key(), "MaxVersionGL"),
+        }
+    }
+}
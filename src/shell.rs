@@ -0,0 +1,192 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{ffi::OsString, io, path::Path, process};
+
+use crate::{
+    Prefix,
+    registry::{RegistryKey, RegistryValue},
+};
+
+/// The file type that an extension is registered to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FileAssociation {
+    /// The programmatic identifier, e.g., `txtfile`.
+    pub prog_id: String,
+    pub description: Option<String>,
+    /// The command line of the `open` verb, where `%1` stands for the
+    /// document, e.g., `"C:\Program Files\App\app.exe" "%1"`.
+    pub open_command: Option<String>,
+}
+
+/// Adds the leading dot that registry keys for extensions have.
+fn normalize_extension(extension: &str) -> String {
+    let extension = extension.to_ascii_lowercase();
+    if extension.starts_with('.') {
+        extension
+    } else {
+        format!(".{extension}")
+    }
+}
+
+fn extension_of(path: &Path) -> io::Result<String> {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| format!(".{}", extension.to_ascii_lowercase()))
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} has no file extension", path.display()),
+            )
+        })
+}
+
+/// Substitutes the document into a registered command line, which refers to
+/// it as `%1` or `%L` and to any further arguments as `%*`.
+fn expand_command(command: &str, document: &str) -> String {
+    let mentions_document = ["%1", "%L", "%l"]
+        .iter()
+        .any(|placeholder| command.contains(placeholder));
+    let mut expanded = command
+        .replace("%1", document)
+        .replace("%L", document)
+        .replace("%l", document)
+        .replace("%*", "");
+    if !mentions_document {
+        expanded.push_str(&format!(" \"{document}\""));
+    }
+    expanded
+}
+
+impl Prefix {
+    /// The command line registered for `verb`, e.g., `open` or `print`, on
+    /// files with the extension `extension` (including the dot).
+    pub(crate) fn shell_command(
+        &self,
+        extension: &str,
+        verb: &str,
+    ) -> io::Result<Option<String>> {
+        let Some(RegistryValue::String(prog_id)) =
+            self.registry_value(&RegistryKey::classes_root(extension), None)?
+        else {
+            return Ok(None);
+        };
+        let command_key = RegistryKey::classes_root(format!(
+            "{prog_id}\\shell\\{verb}\\command"
+        ));
+        match self.registry_value(&command_key, None)? {
+            Some(RegistryValue::String(command)) if !command.is_empty() => {
+                Ok(Some(command))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// The file type registered for `extension`, with or without its dot.
+    pub fn file_association(
+        &self,
+        extension: &str,
+    ) -> io::Result<Option<FileAssociation>> {
+        let extension = normalize_extension(extension);
+        let Some(RegistryValue::String(prog_id)) =
+            self.registry_value(&RegistryKey::classes_root(&extension), None)?
+        else {
+            return Ok(None);
+        };
+        let description = match self
+            .registry_value(&RegistryKey::classes_root(&prog_id), None)?
+        {
+            Some(RegistryValue::String(description))
+                if !description.is_empty() =>
+            {
+                Some(description)
+            }
+            _ => None,
+        };
+        Ok(Some(FileAssociation {
+            open_command: self.shell_command(&extension, "open")?,
+            prog_id,
+            description,
+        }))
+    }
+
+    /// Registers `extension`, with or without its dot, as a file of type
+    /// `association`, so that [`Prefix::open`] and Windows programs open it
+    /// with `association.open_command`.
+    pub fn register_file_association(
+        &self,
+        extension: &str,
+        association: &FileAssociation,
+    ) -> io::Result<()> {
+        let extension = normalize_extension(extension);
+        let prog_id = &association.prog_id;
+        self.set_registry_value(
+            &RegistryKey::classes_root(extension),
+            None,
+            prog_id.as_str(),
+        )?;
+        if let Some(description) = &association.description {
+            self.set_registry_value(
+                &RegistryKey::classes_root(prog_id),
+                None,
+                description.as_str(),
+            )?;
+        }
+        if let Some(command) = &association.open_command {
+            self.set_registry_value(
+                &RegistryKey::classes_root(format!(
+                    "{prog_id}\\shell\\open\\command"
+                )),
+                None,
+                command.as_str(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Opens a host file in the Windows program associated with its type,
+    /// like double-clicking it in Explorer.
+    pub fn open(
+        &self,
+        document: impl AsRef<Path>,
+    ) -> io::Result<process::Child> {
+        self.builtin_command("start")
+            .arg("/unix")
+            .arg(document.as_ref())
+            .spawn()
+    }
+
+    /// Performs `verb` on a host file with the program registered for it.
+    ///
+    /// `start.exe` only ever issues the `open` verb, so any other verb runs
+    /// the command line registered under the file type's `shell` key.
+    pub fn shell_execute(
+        &self,
+        document: impl AsRef<Path>,
+        verb: &str,
+    ) -> io::Result<process::Child> {
+        let document = document.as_ref();
+        if verb.eq_ignore_ascii_case("open") {
+            return self.open(document);
+        }
+        let extension = extension_of(document)?;
+        let command =
+            self.shell_command(&extension, verb)?.ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!(
+                        "no `{verb}` command is registered for {extension}"
+                    ),
+                )
+            })?;
+        let windows_path = self.windows_path(document);
+        let command = expand_command(&command, &windows_path.to_string_lossy());
+        self.builtin_command("cmd")
+            .arg("/c")
+            .arg(OsString::from(command))
+            .spawn()
+    }
+}
@@ -0,0 +1,328 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    env, fs, io,
+    os::unix,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    Prefix,
+    registry::{RegistryKey, RegistryValue},
+};
+
+const CURRENT_VERSION: &str = "Software\\Microsoft\\Windows\\CurrentVersion";
+
+const SHELL_FOLDERS: &str =
+    "Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\Shell Folders";
+
+/// A user folder that Wine links into the host home directory by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShellFolder {
+    Desktop,
+    Documents,
+    Downloads,
+    Music,
+    Pictures,
+    Videos,
+    Templates,
+}
+
+impl ShellFolder {
+    pub const ALL: &[Self] = &[
+        Self::Desktop,
+        Self::Documents,
+        Self::Downloads,
+        Self::Music,
+        Self::Pictures,
+        Self::Videos,
+        Self::Templates,
+    ];
+
+    /// The folder's name inside the user profile.
+    pub fn dir_name(&self) -> &'static str {
+        match self {
+            Self::Desktop => "Desktop",
+            Self::Documents => "Documents",
+            Self::Downloads => "Downloads",
+            Self::Music => "Music",
+            Self::Pictures => "Pictures",
+            Self::Videos => "Videos",
+            Self::Templates => "Templates",
+        }
+    }
+}
+
+/// A system or user folder whose location Windows records in the registry,
+/// in the language of the locale that the prefix was created under, e.g.,
+/// `C:\Programme` for [`KnownFolder::ProgramFiles`] with a German locale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KnownFolder {
+    Windows,
+    ProgramFiles,
+    /// Only 64-bit prefixes have it.
+    ProgramFilesX86,
+    CommonFiles,
+    ProgramData,
+    AppData,
+    LocalAppData,
+    Public,
+    Shell(ShellFolder),
+}
+
+impl KnownFolder {
+    /// The registry value that holds the folder's Windows path.
+    fn registry_value(&self) -> (RegistryKey, &'static str) {
+        let current_version = || RegistryKey::local_machine(CURRENT_VERSION);
+        let user_folders = || RegistryKey::current_user(SHELL_FOLDERS);
+        match self {
+            Self::Windows => (
+                RegistryKey::local_machine(
+                    "Software\\Microsoft\\Windows NT\\CurrentVersion",
+                ),
+                "SystemRoot",
+            ),
+            Self::ProgramFiles => (current_version(), "ProgramFilesDir"),
+            Self::ProgramFilesX86 => {
+                (current_version(), "ProgramFilesDir (x86)")
+            }
+            Self::CommonFiles => (current_version(), "CommonFilesDir"),
+            Self::ProgramData => {
+                (RegistryKey::local_machine(SHELL_FOLDERS), "Common AppData")
+            }
+            Self::AppData => (user_folders(), "AppData"),
+            Self::LocalAppData => (user_folders(), "Local AppData"),
+            Self::Public => (
+                RegistryKey::local_machine(
+                    "Software\\Microsoft\\Windows NT\\CurrentVersion\\\
+                     ProfileList",
+                ),
+                "Public",
+            ),
+            Self::Shell(folder) => (
+                user_folders(),
+                match folder {
+                    ShellFolder::Desktop => "Desktop",
+                    ShellFolder::Documents => "Personal",
+                    ShellFolder::Downloads => {
+                        "{374DE290-123F-4565-9164-39C4925E467B}"
+                    }
+                    ShellFolder::Music => "My Music",
+                    ShellFolder::Pictures => "My Pictures",
+                    ShellFolder::Videos => "My Videos",
+                    ShellFolder::Templates => "Templates",
+                },
+            ),
+        }
+    }
+}
+
+/// Expands the `%SystemDrive%` that Windows writes into some folder paths,
+/// leaving paths with other variables unresolved.
+fn expand_system_drive(path: &str) -> Option<String> {
+    let expanded = match path.get(..13) {
+        Some(variable) if variable.eq_ignore_ascii_case("%SystemDrive%") => {
+            format!("C:{}", &path[13..])
+        }
+        _ => path.to_owned(),
+    };
+    (!expanded.contains('%')).then_some(expanded)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ShellFolderTarget {
+    /// A symlink to a host directory.
+    Host(PathBuf),
+    /// A real directory inside the prefix.
+    InPrefix,
+}
+
+fn remove_folder(path: &Path) -> io::Result<()> {
+    if path.is_symlink() {
+        fs::remove_file(path)
+    } else if path.is_dir() {
+        // Refuses to discard anything a program saved there.
+        fs::remove_dir(path)
+    } else {
+        Ok(())
+    }
+}
+
+impl Prefix {
+    /// The profile directory under `C:\users` of the host user, which Wine
+    /// names after the login name.
+    pub fn user_profile(&self) -> io::Result<PathBuf> {
+        let users = self.drive_c().join("users");
+        if let Some(user) = env::var_os("USER") {
+            let profile = users.join(user);
+            if profile.is_dir() {
+                return Ok(profile);
+            }
+        }
+        let mut profiles = Vec::new();
+        for entry in fs::read_dir(&users)? {
+            let entry = entry?;
+            if entry.file_name() != "Public" && entry.path().is_dir() {
+                profiles.push(entry.path());
+            }
+        }
+        match <[PathBuf; 1]>::try_from(profiles) {
+            Ok([profile]) => Ok(profile),
+            Err(_) => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "could not determine the user profile in {}",
+                    users.display()
+                ),
+            )),
+        }
+    }
+
+    /// `C:\users\<user>\AppData\Roaming`, or `%APPDATA%`.
+    pub fn appdata(&self) -> io::Result<PathBuf> {
+        Ok(self.user_profile()?.join("AppData").join("Roaming"))
+    }
+
+    /// `C:\users\<user>\AppData\Local`, or `%LOCALAPPDATA%`.
+    pub fn local_appdata(&self) -> io::Result<PathBuf> {
+        Ok(self.user_profile()?.join("AppData").join("Local"))
+    }
+
+    /// `%TEMP%` of the host user.
+    pub fn temp_dir(&self) -> io::Result<PathBuf> {
+        Ok(self.local_appdata()?.join("Temp"))
+    }
+
+    /// `C:\users\Public`.
+    pub fn public_profile(&self) -> PathBuf {
+        self.drive_c().join("users").join("Public")
+    }
+
+    /// `C:\ProgramData`, or `%ProgramData%`.
+    pub fn program_data(&self) -> PathBuf {
+        self.drive_c().join("ProgramData")
+    }
+
+    /// `C:\windows`.
+    pub fn windows_dir(&self) -> PathBuf {
+        self.drive_c().join("windows")
+    }
+
+    /// Where native-bitness system libraries live, which is 64-bit on 64-bit
+    /// prefixes.
+    pub fn system32(&self) -> PathBuf {
+        self.windows_dir().join("system32")
+    }
+
+    /// Where 32-bit system libraries live on 64-bit prefixes.
+    pub fn syswow64(&self) -> Option<PathBuf> {
+        self.is_64_bit()
+            .then(|| self.windows_dir().join("syswow64"))
+    }
+
+    /// `C:\Program Files`, which holds 64-bit programs on 64-bit prefixes.
+    pub fn program_files(&self) -> PathBuf {
+        self.drive_c().join("Program Files")
+    }
+
+    /// `C:\Program Files (x86)`, which only 64-bit prefixes have.
+    pub fn program_files_x86(&self) -> Option<PathBuf> {
+        self.is_64_bit()
+            .then(|| self.drive_c().join("Program Files (x86)"))
+    }
+
+    pub fn shell_folder_path(
+        &self,
+        folder: ShellFolder,
+    ) -> io::Result<PathBuf> {
+        Ok(self.user_profile()?.join(folder.dir_name()))
+    }
+
+    /// Where `folder` lives on the host according to the registry of the
+    /// prefix, which names it in the language the prefix was created in,
+    /// unlike [`Prefix::program_files`] and the other helpers that assume
+    /// the English names. A folder the registry does not record, e.g., in a
+    /// prefix that has not finished booting, falls back to its English name.
+    ///
+    /// This queries the registry with `reg`, which starts the wineserver.
+    pub fn known_folder(&self, folder: KnownFolder) -> io::Result<PathBuf> {
+        let (key, name) = folder.registry_value();
+        let recorded = match self.registry_value(&key, Some(name))? {
+            Some(
+                RegistryValue::String(path) | RegistryValue::ExpandString(path),
+            ) => expand_system_drive(&path),
+            _ => None,
+        };
+        if let Some(path) = recorded {
+            return self.resolve_guest_path(&path);
+        }
+        match folder {
+            KnownFolder::Windows => Ok(self.windows_dir()),
+            KnownFolder::ProgramFiles => Ok(self.program_files()),
+            KnownFolder::ProgramFilesX86 => {
+                self.program_files_x86().ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        "32-bit prefixes have no `Program Files (x86)`",
+                    )
+                })
+            }
+            KnownFolder::CommonFiles => {
+                Ok(self.program_files().join("Common Files"))
+            }
+            KnownFolder::ProgramData => Ok(self.program_data()),
+            KnownFolder::AppData => self.appdata(),
+            KnownFolder::LocalAppData => self.local_appdata(),
+            KnownFolder::Public => Ok(self.public_profile()),
+            KnownFolder::Shell(folder) => self.shell_folder_path(folder),
+        }
+    }
+
+    /// Where `folder` currently leads, or `None` if it does not exist.
+    pub fn shell_folder_target(
+        &self,
+        folder: ShellFolder,
+    ) -> io::Result<Option<ShellFolderTarget>> {
+        let path = self.shell_folder_path(folder)?;
+        if path.is_symlink() {
+            Ok(Some(ShellFolderTarget::Host(fs::read_link(path)?)))
+        } else if path.is_dir() {
+            Ok(Some(ShellFolderTarget::InPrefix))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Points `folder` at `target`. Replacing an in-prefix folder fails unless
+    /// it is empty.
+    pub fn redirect_shell_folder(
+        &self,
+        folder: ShellFolder,
+        target: &ShellFolderTarget,
+    ) -> io::Result<()> {
+        let path = self.shell_folder_path(folder)?;
+        match target {
+            ShellFolderTarget::Host(host_path) => {
+                if !host_path.is_dir() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("{} is not a directory", host_path.display()),
+                    ));
+                }
+                remove_folder(&path)?;
+                unix::fs::symlink(host_path, path)
+            }
+            ShellFolderTarget::InPrefix => {
+                if !path.is_symlink() && path.is_dir() {
+                    return Ok(());
+                }
+                remove_folder(&path)?;
+                fs::create_dir(path)
+            }
+        }
+    }
+}
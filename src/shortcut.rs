@@ -0,0 +1,438 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{Prefix, ShellFolder};
+
+/// `00021401-0000-0000-C000-000000000046` in its on-disk byte order.
+const LINK_CLSID: [u8; 16] = [
+    0x01, 0x14, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0xc0, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x46,
+];
+
+const HAS_LINK_TARGET_ID_LIST: u32 = 0x1;
+const HAS_LINK_INFO: u32 = 0x2;
+const HAS_NAME: u32 = 0x4;
+const HAS_RELATIVE_PATH: u32 = 0x8;
+const HAS_WORKING_DIR: u32 = 0x10;
+const HAS_ARGUMENTS: u32 = 0x20;
+const HAS_ICON_LOCATION: u32 = 0x40;
+const IS_UNICODE: u32 = 0x80;
+
+const SW_SHOWNORMAL: u32 = 1;
+const DRIVE_FIXED: u32 = 3;
+
+/// A Windows shortcut, as written to a `.lnk` file by [`Shortcut::save`] or
+/// [`Prefix::create_shortcut`]. Paths are Windows paths, e.g., from
+/// [`Prefix::windows_path`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    /// E.g., `C:\Program Files\App\app.exe`.
+    pub target: String,
+    /// The command line passed to the target.
+    pub arguments: String,
+    pub working_dir: Option<String>,
+    /// A file holding the icon and the index of the icon in it, e.g., the
+    /// target itself and `0`. Without one, the icon of the target is shown.
+    pub icon: Option<(String, i32)>,
+    /// Shown as the tooltip of the shortcut.
+    pub description: Option<String>,
+}
+
+/// Where [`Prefix::create_shortcut`] places a shortcut.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShortcutLocation {
+    /// The Start Menu programs of the user.
+    StartMenu,
+    /// The Start Menu programs of every user, where installers usually put
+    /// their entries.
+    CommonStartMenu,
+    /// The desktop of the user, which Wine links to the host desktop by
+    /// default, see [`Prefix::redirect_shell_folder`].
+    Desktop,
+    /// `C:\users\Public\Desktop`.
+    CommonDesktop,
+}
+
+fn push_u16(link: &mut Vec<u8>, value: u16) {
+    link.extend(value.to_le_bytes());
+}
+
+fn push_u32(link: &mut Vec<u8>, value: u32) {
+    link.extend(value.to_le_bytes());
+}
+
+fn utf16(string: &str) -> Vec<u16> {
+    string.encode_utf16().collect()
+}
+
+/// A `StringData` entry: a count of UTF-16 units followed by them, without a
+/// terminator.
+fn push_string_data(link: &mut Vec<u8>, string: &str) -> io::Result<()> {
+    let units = utf16(string);
+    let count = u16::try_from(units.len()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("`{string}` is too long for a shortcut"),
+        )
+    })?;
+    push_u16(link, count);
+    for unit in units {
+        push_u16(link, unit);
+    }
+    Ok(())
+}
+
+/// A `LinkInfo` structure locating `target` on a local fixed drive, with the
+/// path in both the ANSI form older readers use and UTF-16.
+fn link_info(target: &str) -> Vec<u8> {
+    const HEADER_SIZE: u32 = 0x24;
+    let mut volume_id = Vec::new();
+    push_u32(&mut volume_id, 0x11);
+    push_u32(&mut volume_id, DRIVE_FIXED);
+    push_u32(&mut volume_id, 0);
+    // The label follows the four fields, and is empty.
+    push_u32(&mut volume_id, 0x10);
+    volume_id.push(0);
+
+    let mut ansi_path: Vec<u8> = target
+        .chars()
+        .map(|char| if char.is_ascii() { char as u8 } else { b'?' })
+        .collect();
+    ansi_path.push(0);
+    let mut unicode_path = Vec::new();
+    for unit in utf16(target).into_iter().chain([0]) {
+        push_u16(&mut unicode_path, unit);
+    }
+
+    let volume_id_offset = HEADER_SIZE;
+    let local_base_path_offset = volume_id_offset + volume_id.len() as u32;
+    let common_path_suffix_offset =
+        local_base_path_offset + ansi_path.len() as u32;
+    let unicode_path_offset = common_path_suffix_offset + 1;
+    let unicode_suffix_offset = unicode_path_offset + unicode_path.len() as u32;
+    let size = unicode_suffix_offset + 2;
+
+    let mut info = Vec::new();
+    push_u32(&mut info, size);
+    push_u32(&mut info, HEADER_SIZE);
+    // `VolumeIDAndLocalBasePath`.
+    push_u32(&mut info, 1);
+    push_u32(&mut info, volume_id_offset);
+    push_u32(&mut info, local_base_path_offset);
+    push_u32(&mut info, 0);
+    push_u32(&mut info, common_path_suffix_offset);
+    push_u32(&mut info, unicode_path_offset);
+    push_u32(&mut info, unicode_suffix_offset);
+    info.extend(volume_id);
+    info.extend(ansi_path);
+    info.push(0);
+    info.extend(unicode_path);
+    push_u16(&mut info, 0);
+    info
+}
+
+/// The NUL-terminated string at `offset` of `data`, in UTF-16 if `wide` and
+/// otherwise in an ANSI codepage, which is read as Latin-1.
+fn string_at(data: &[u8], offset: usize, wide: bool) -> Option<String> {
+    let data = data.get(offset..)?;
+    if wide {
+        let units: Vec<u16> = data
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .take_while(|&unit| unit != 0)
+            .collect();
+        Some(String::from_utf16_lossy(&units))
+    } else {
+        Some(
+            data.iter()
+                .take_while(|&&byte| byte != 0)
+                .map(|&byte| char::from(byte))
+                .collect(),
+        )
+    }
+}
+
+/// The path that a `LinkInfo` structure locates, if it names one on a local
+/// volume.
+fn link_info_path(info: &[u8]) -> Option<String> {
+    let u32_at = |offset: usize| -> Option<usize> {
+        let bytes = info.get(offset..offset + 4)?;
+        Some(u32::from_le_bytes(bytes.try_into().unwrap()) as usize)
+    };
+    let header_size = u32_at(4)?;
+    if u32_at(8)? & 1 == 0 {
+        return None;
+    }
+    // Newer writers add the path in UTF-16 after the ANSI one.
+    let (base, suffix) = if header_size >= 0x24 {
+        (
+            string_at(info, u32_at(28)?, true)?,
+            string_at(info, u32_at(32)?, true)?,
+        )
+    } else {
+        (
+            string_at(info, u32_at(16)?, false)?,
+            string_at(info, u32_at(24)?, false)?,
+        )
+    };
+    Some(base + &suffix)
+}
+
+impl Shortcut {
+    pub fn new(target: impl Into<String>) -> Self {
+        Self {
+            target: target.into(),
+            arguments: String::new(),
+            working_dir: None,
+            icon: None,
+            description: None,
+        }
+    }
+
+    /// The contents of the `.lnk` file, in the Shell Link format of
+    /// `[MS-SHLLINK]`. Fails if a string is longer than the format allows.
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut flags = HAS_LINK_INFO | IS_UNICODE;
+        let strings = [
+            (HAS_NAME, self.description.as_deref()),
+            (HAS_WORKING_DIR, self.working_dir.as_deref()),
+            (
+                HAS_ARGUMENTS,
+                Some(self.arguments.as_str()).filter(|args| !args.is_empty()),
+            ),
+            (
+                HAS_ICON_LOCATION,
+                self.icon.as_ref().map(|(path, _)| path.as_str()),
+            ),
+        ];
+        for (flag, string) in strings {
+            if string.is_some() {
+                flags |= flag;
+            }
+        }
+
+        let mut link = Vec::new();
+        push_u32(&mut link, 0x4c);
+        link.extend(LINK_CLSID);
+        push_u32(&mut link, flags);
+        // The file attributes, times, and size of the target, which readers
+        // look up themselves when they are zero.
+        link.extend([0; 4 + 3 * 8 + 4]);
+        link.extend(
+            self.icon
+                .as_ref()
+                .map_or(0, |(_, index)| *index)
+                .to_le_bytes(),
+        );
+        push_u32(&mut link, SW_SHOWNORMAL);
+        // No hotkey, then reserved fields.
+        link.extend([0; 2 + 2 + 4 + 4]);
+
+        link.extend(link_info(&self.target));
+        for string in strings.into_iter().filter_map(|(_, string)| string) {
+            push_string_data(&mut link, string)?;
+        }
+        // The terminal block of the extra data.
+        push_u32(&mut link, 0);
+        Ok(link)
+    }
+
+    /// Reads the contents of a `.lnk` file, keeping only what [`Shortcut`]
+    /// describes, e.g., not the item ID list that Windows locates the target
+    /// by before falling back to its path.
+    pub fn from_bytes(link: &[u8]) -> io::Result<Self> {
+        let invalid =
+            || io::Error::new(io::ErrorKind::InvalidData, "malformed shortcut");
+        let u16_at = |offset: usize| -> io::Result<usize> {
+            let bytes = link.get(offset..offset + 2).ok_or_else(invalid)?;
+            Ok(usize::from(u16::from_le_bytes(bytes.try_into().unwrap())))
+        };
+        let u32_at = |offset: usize| -> io::Result<u32> {
+            let bytes = link.get(offset..offset + 4).ok_or_else(invalid)?;
+            Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+        };
+        if u32_at(0)? != 0x4c || link.get(4..20) != Some(&LINK_CLSID) {
+            return Err(invalid());
+        }
+        let flags = u32_at(20)?;
+        let icon_index = u32_at(56)? as i32;
+
+        let mut offset = 0x4c;
+        if flags & HAS_LINK_TARGET_ID_LIST != 0 {
+            offset += 2 + u16_at(offset)?;
+        }
+        let mut target = None;
+        if flags & HAS_LINK_INFO != 0 {
+            let size = u32_at(offset)? as usize;
+            let info = link.get(offset..offset + size).ok_or_else(invalid)?;
+            target = link_info_path(info);
+            offset += size;
+        }
+        let mut strings = [None, None, None, None, None];
+        for (flag, string) in [
+            HAS_NAME,
+            HAS_RELATIVE_PATH,
+            HAS_WORKING_DIR,
+            HAS_ARGUMENTS,
+            HAS_ICON_LOCATION,
+        ]
+        .into_iter()
+        .zip(&mut strings)
+        {
+            if flags & flag == 0 {
+                continue;
+            }
+            let count = u16_at(offset)?;
+            let wide = flags & IS_UNICODE != 0;
+            let size = if wide { 2 * count } else { count };
+            let data = link
+                .get(offset + 2..offset + 2 + size)
+                .ok_or_else(invalid)?;
+            let mut data = data.to_vec();
+            data.extend([0, 0]);
+            *string = string_at(&data, 0, wide);
+            offset += 2 + size;
+        }
+        let [description, relative_path, working_dir, arguments, icon] =
+            strings;
+        Ok(Self {
+            target: target.or(relative_path).ok_or_else(invalid)?,
+            arguments: arguments.unwrap_or_default(),
+            working_dir,
+            icon: icon.map(|path| (path, icon_index)),
+            description,
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_bytes()?)
+    }
+}
+
+impl Prefix {
+    /// The host directory of `location`.
+    pub fn shortcut_dir(
+        &self,
+        location: ShortcutLocation,
+    ) -> io::Result<PathBuf> {
+        let start_menu = Path::new("Microsoft/Windows/Start Menu/Programs");
+        Ok(match location {
+            ShortcutLocation::StartMenu => self.appdata()?.join(start_menu),
+            ShortcutLocation::CommonStartMenu => {
+                self.program_data().join(start_menu)
+            }
+            ShortcutLocation::Desktop => {
+                self.shell_folder_path(ShellFolder::Desktop)?
+            }
+            ShortcutLocation::CommonDesktop => {
+                self.public_profile().join("Desktop")
+            }
+        })
+    }
+
+    /// Writes `shortcut` to `name` with `.lnk` appended under `location`,
+    /// where `name` may include folders, e.g., `App/App`, which are created.
+    /// Returns the host path of the new file.
+    ///
+    /// The shortcut is written directly rather than through the shell, so
+    /// Wine does not add it to the menus of the host.
+    pub fn create_shortcut(
+        &self,
+        location: ShortcutLocation,
+        name: impl AsRef<Path>,
+        shortcut: &Shortcut,
+    ) -> io::Result<PathBuf> {
+        let mut file_name = name.as_ref().as_os_str().to_os_string();
+        file_name.push(".lnk");
+        let path = self.shortcut_dir(location)?.join(file_name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        shortcut.save(&path)?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        HAS_ARGUMENTS, HAS_LINK_INFO, HAS_LINK_TARGET_ID_LIST, LINK_CLSID,
+        Shortcut,
+    };
+
+    #[test]
+    fn round_trips_shortcuts() {
+        let shortcut = Shortcut {
+            target: r"C:\Program Files\Café\app.exe".into(),
+            arguments: "--fullscreen -w 1920".into(),
+            working_dir: Some(r"C:\Program Files\Café".into()),
+            icon: Some((r"C:\Program Files\Café\app.ico".into(), -101)),
+            description: Some("Launches the app".into()),
+        };
+        let link = shortcut.to_bytes().unwrap();
+        assert_eq!(Shortcut::from_bytes(&link).unwrap(), shortcut);
+
+        let bare = Shortcut::new(r"C:\windows\notepad.exe");
+        let link = bare.to_bytes().unwrap();
+        assert_eq!(Shortcut::from_bytes(&link).unwrap(), bare);
+        assert!(Shortcut::from_bytes(&link[..0x4c]).is_err());
+        assert!(Shortcut::from_bytes(b"not a shortcut").is_err());
+    }
+
+    /// A shortcut from an older writer, with an item ID list, an ANSI
+    /// `LinkInfo` whose path is split into a base and a common suffix, and
+    /// ANSI strings.
+    #[test]
+    fn reads_ansi_link_info() {
+        let mut info = Vec::new();
+        let volume_id = [&0x10u32.to_le_bytes()[..], &[0; 12]].concat();
+        let base = b"C:\\Games\\\0";
+        let suffix = b"app.exe\0";
+        let volume_id_offset = 0x1cu32;
+        let base_offset = volume_id_offset + volume_id.len() as u32;
+        let suffix_offset = base_offset + base.len() as u32;
+        let size = suffix_offset + suffix.len() as u32;
+        for field in [
+            size,
+            0x1c,
+            1,
+            volume_id_offset,
+            base_offset,
+            0,
+            suffix_offset,
+        ] {
+            info.extend(field.to_le_bytes());
+        }
+        info.extend(volume_id);
+        info.extend(base);
+        info.extend(suffix);
+
+        let mut link = 0x4cu32.to_le_bytes().to_vec();
+        link.extend(LINK_CLSID);
+        link.extend(
+            (HAS_LINK_TARGET_ID_LIST | HAS_LINK_INFO | HAS_ARGUMENTS)
+                .to_le_bytes(),
+        );
+        link.extend([0; 0x4c - 24]);
+        // An ID list of just its terminator.
+        link.extend([2, 0, 0, 0]);
+        link.extend(info);
+        link.extend(4u16.to_le_bytes());
+        link.extend(b"-dx9");
+        link.extend(0u32.to_le_bytes());
+
+        let shortcut = Shortcut::from_bytes(&link).unwrap();
+        assert_eq!(shortcut.target, r"C:\Games\app.exe");
+        assert_eq!(shortcut.arguments, "-dx9");
+        assert_eq!(shortcut.working_dir, None);
+        assert_eq!(shortcut.icon, None);
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{Error, Prefix, REGISTRY_FILES, tree};
+
+/// What a snapshot captures besides [`REGISTRY_FILES`].
+const SNAPSHOT_DIRS: &[&str] = &["drive_c", "dosdevices"];
+
+fn check_name(name: &str) -> io::Result<()> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\0']) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid snapshot name `{name}`"),
+        ));
+    }
+    Ok(())
+}
+
+impl Prefix {
+    fn snapshots_dir(&self) -> PathBuf {
+        self.metadata_dir().join("snapshots")
+    }
+
+    pub(crate) fn check_stopped(&self, action: &str) -> io::Result<()> {
+        if self.is_server_running() {
+            return Err(Error::PrefixBusy(action.to_owned()).into());
+        }
+        Ok(())
+    }
+
+    /// Saves `drive_c`, the drive mappings, and the registry under `name`, to
+    /// be restored with [`Prefix::rollback`]. Files are shared copy-on-write
+    /// where the filesystem supports it, which makes this nearly free.
+    ///
+    /// Fails while the wineserver is running, since it may not have saved the
+    /// registry yet.
+    pub fn snapshot(&self, name: &str) -> io::Result<()> {
+        check_name(name)?;
+        // Taking the lock would create the prefix directory.
+        self.check_exists()?;
+        let _lock = self.lock()?;
+        self.check_stopped("take a snapshot")?;
+        let snapshot_dir = self.snapshots_dir().join(name);
+        if snapshot_dir.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("snapshot `{name}` already exists"),
+            ));
+        }
+
+        // A snapshot missing some files must never be restored.
+        let partial_dir = self.snapshots_dir().join(format!(".{name}.partial"));
+        tree::remove_tree(&partial_dir)?;
+        fs::create_dir_all(&partial_dir)?;
+        if let Err(error) = self.save_snapshot_items(&partial_dir) {
+            tree::remove_tree(&partial_dir)?;
+            return Err(error);
+        }
+        fs::rename(&partial_dir, &snapshot_dir)?;
+        let total = SNAPSHOT_DIRS.len() + REGISTRY_FILES.len();
+        self.report_progress("saved the snapshot", total, total);
+        Ok(())
+    }
+
+    fn save_snapshot_items(&self, partial_dir: &Path) -> io::Result<()> {
+        let total = SNAPSHOT_DIRS.len() + REGISTRY_FILES.len();
+        for (index, item) in
+            SNAPSHOT_DIRS.iter().chain(REGISTRY_FILES).enumerate()
+        {
+            self.check_cancelled()?;
+            self.report_progress(&format!("saving {item}"), index, total);
+            let source = self.path.join(item);
+            if fs::symlink_metadata(&source).is_ok() {
+                tree::clone_tree(&source, &partial_dir.join(item))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Restores the state saved by [`Prefix::snapshot`], which is kept for
+    /// later rollbacks. Fails while the wineserver is running.
+    pub fn rollback(&self, name: &str) -> io::Result<()> {
+        check_name(name)?;
+        let _lock = self.lock()?;
+        self.check_stopped("roll back")?;
+        let snapshot_dir = self.snapshots_dir().join(name);
+        if !snapshot_dir.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("snapshot `{name}` does not exist"),
+            ));
+        }
+
+        let total = SNAPSHOT_DIRS.len() + REGISTRY_FILES.len();
+        for (index, item) in
+            SNAPSHOT_DIRS.iter().chain(REGISTRY_FILES).enumerate()
+        {
+            self.report_progress(&format!("restoring {item}"), index, total);
+            let destination = self.path.join(item);
+            tree::remove_tree(&destination)?;
+            let source = snapshot_dir.join(item);
+            if fs::symlink_metadata(&source).is_ok() {
+                tree::clone_tree(&source, &destination)?;
+            }
+        }
+        self.report_progress("restored the snapshot", total, total);
+        Ok(())
+    }
+
+    /// The names of the snapshots of this prefix, sorted.
+    pub fn snapshots(&self) -> io::Result<Vec<String>> {
+        let entries = match fs::read_dir(self.snapshots_dir()) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok(Vec::new());
+            }
+            Err(error) => return Err(error),
+        };
+        let mut names = Vec::new();
+        for entry in entries {
+            if let Some(name) = entry?.file_name().to_str()
+                && !name.starts_with('.')
+            {
+                names.push(name.to_owned());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    pub fn delete_snapshot(&self, name: &str) -> io::Result<()> {
+        check_name(name)?;
+        let _lock = self.lock()?;
+        tree::remove_tree(&self.snapshots_dir().join(name))
+    }
+}
@@ -0,0 +1,121 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{Prefix, PrefixBuilder};
+
+pub(crate) fn home_dir() -> io::Result<PathBuf> {
+    match env::var_os("HOME") {
+        Some(home) if !home.is_empty() => Ok(PathBuf::from(home)),
+        _ => Err(io::Error::new(io::ErrorKind::NotFound, "HOME is not set")),
+    }
+}
+
+/// A variable holding a directory, ignoring it when empty as the XDG Base
+/// Directory specification asks.
+pub(crate) fn dir_var(name: &str) -> Option<PathBuf> {
+    env::var_os(name)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// A directory of prefixes kept under their names, e.g., one per
+/// application.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PrefixStore {
+    root: PathBuf,
+}
+
+impl PrefixStore {
+    pub fn at(root: impl AsRef<Path>) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+
+    /// A store at [`PrefixStore::default_root`].
+    pub fn user() -> io::Result<Self> {
+        Self::default_root().map(Self::at)
+    }
+
+    /// `wine-rs/prefixes` in the data directory of the user: under
+    /// `~/Library/Application Support` on macOS and `$XDG_DATA_HOME`, falling
+    /// back to `~/.local/share`, elsewhere.
+    pub fn default_root() -> io::Result<PathBuf> {
+        let data_dir = if cfg!(target_os = "macos") {
+            home_dir()?.join("Library/Application Support")
+        } else {
+            match dir_var("XDG_DATA_HOME") {
+                Some(data_home) => data_home,
+                None => home_dir()?.join(".local/share"),
+            }
+        };
+        Ok(data_dir.join("wine-rs/prefixes"))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Where the prefix called `name` lives, whether or not it exists.
+    pub fn path_of(&self, name: &str) -> io::Result<PathBuf> {
+        if name.is_empty()
+            || name == "."
+            || name == ".."
+            || name.contains(['/', '\0'])
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("`{name}` cannot name a prefix"),
+            ));
+        }
+        Ok(self.root.join(name))
+    }
+
+    /// Begins constructing the prefix called `name`, whose Wine binaries must
+    /// still be given unless it has its own `bin/`.
+    pub fn builder(&self, name: &str) -> io::Result<PrefixBuilder> {
+        self.path_of(name).map(PrefixBuilder::new)
+    }
+
+    /// The names of the prefixes in the store, sorted, which is empty if the
+    /// store was never created.
+    pub fn names(&self) -> io::Result<Vec<String>> {
+        let entries = match fs::read_dir(&self.root) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok(Vec::new());
+            }
+            Err(error) => return Err(error),
+        };
+        let mut names = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            if entry.path().is_dir()
+                && let Ok(name) = entry.file_name().into_string()
+            {
+                names.push(name);
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+}
+
+impl Prefix {
+    /// Where Wine itself puts the prefix when not told: `$WINEPREFIX`,
+    /// falling back to `~/.wine`.
+    pub fn default_path() -> io::Result<PathBuf> {
+        match dir_var("WINEPREFIX") {
+            Some(prefix) => Ok(prefix),
+            None => Ok(home_dir()?.join(".wine")),
+        }
+    }
+}
@@ -0,0 +1,222 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    io::{self, BufRead, BufReader, Read},
+    process::{self, Command, ExitStatus},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+use crate::{
+    Codepage, Error, KillOnDrop, WineCommand, diagnostics::Diagnostic,
+};
+
+/// A line of output from a program launched with
+/// [`WineCommand::spawn_with_channel`] or [`WineCommand::spawn_with_lines`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutputLine {
+    Stdout(String),
+    /// A line on stderr that did not come from Wine's debug machinery.
+    Stderr(String),
+    Diagnostic(Diagnostic),
+}
+
+/// A running program, which is killed when dropped according to
+/// [`WineCommand::kill_on_drop`].
+#[derive(Debug)]
+pub struct WineChild {
+    pub(crate) child: process::Child,
+    pub(crate) readers: Vec<thread::JoinHandle<()>>,
+    kill_on_drop: KillOnDrop,
+    pub(crate) hung: Arc<AtomicBool>,
+}
+
+/// Forwards every line of `stream` through `sender` until either side closes.
+fn forward_lines(
+    stream: impl Read + Send + 'static,
+    codepage: Codepage,
+    sender: mpsc::Sender<OutputLine>,
+    to_line: fn(String) -> OutputLine,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(stream);
+        let mut buffer = Vec::new();
+        loop {
+            buffer.clear();
+            match reader.read_until(b'\n', &mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            let line = codepage
+                .decode(&buffer)
+                .trim_end_matches(['\r', '\n'])
+                .to_owned();
+            if sender.send(to_line(line)).is_err() {
+                break;
+            }
+        }
+    })
+}
+
+/// Sends `SIGKILL` to the process `pid`, or to its process group if `group`.
+pub(crate) fn send_kill(pid: u32, group: bool) -> io::Result<ExitStatus> {
+    let target = if group {
+        format!("-{pid}")
+    } else {
+        pid.to_string()
+    };
+    Command::new("kill")
+        .args(["-s", "KILL", "--", &target])
+        .stderr(process::Stdio::null())
+        .status()
+}
+
+fn stderr_line(line: String) -> OutputLine {
+    match Diagnostic::parse(&line) {
+        Some(diagnostic) => OutputLine::Diagnostic(diagnostic),
+        None => OutputLine::Stderr(line),
+    }
+}
+
+impl WineCommand<'_> {
+    /// Like [`WineCommand::spawn`], but as a [`WineChild`].
+    pub fn spawn_child(&self) -> io::Result<WineChild> {
+        Ok(WineChild {
+            child: self.spawn()?,
+            readers: Vec::new(),
+            kill_on_drop: self.kill_on_drop,
+            hung: Arc::default(),
+        })
+    }
+
+    /// Spawns the command, sending its output through the returned channel as
+    /// it is written. The channel closes once the program and every process
+    /// it shares its stdio with have exited.
+    pub fn spawn_with_channel(
+        &self,
+    ) -> io::Result<(WineChild, mpsc::Receiver<OutputLine>)> {
+        self.spawn_streaming(false)
+    }
+
+    /// [`WineCommand::spawn_with_channel`], with stdin piped too if
+    /// `pipe_stdin`.
+    pub(crate) fn spawn_streaming(
+        &self,
+        pipe_stdin: bool,
+    ) -> io::Result<(WineChild, mpsc::Receiver<OutputLine>)> {
+        let mut child = self.spawn_captured(pipe_stdin)?;
+        let (sender, receiver) = mpsc::channel();
+        let codepage = self.codepage.unwrap_or(Codepage::UTF8);
+        let mut readers = Vec::new();
+        if let Some(stdout) = child.stdout.take() {
+            readers.push(forward_lines(
+                stdout,
+                codepage,
+                sender.clone(),
+                OutputLine::Stdout,
+            ));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            readers.push(forward_lines(stderr, codepage, sender, stderr_line));
+        }
+        let child = WineChild {
+            child,
+            readers,
+            kill_on_drop: self.kill_on_drop,
+            hung: Arc::default(),
+        };
+        Ok((child, receiver))
+    }
+
+    /// Spawns the command, calling `on_line` from a background thread with
+    /// each line of its output as it is written.
+    pub fn spawn_with_lines(
+        &self,
+        mut on_line: impl FnMut(OutputLine) + Send + 'static,
+    ) -> io::Result<WineChild> {
+        let (mut child, receiver) = self.spawn_with_channel()?;
+        child.readers.push(thread::spawn(move || {
+            for line in receiver {
+                on_line(line);
+            }
+        }));
+        Ok(child)
+    }
+}
+
+impl WineChild {
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    pub fn kill(&mut self) -> io::Result<()> {
+        self.child.kill()
+    }
+
+    /// Kills the program and the processes it started, which requires it to
+    /// have been spawned with [`KillOnDrop::ProcessTree`] so that they share
+    /// its process group.
+    pub fn kill_tree(&mut self) -> io::Result<()> {
+        if self.kill_on_drop != KillOnDrop::ProcessTree {
+            return Err(Error::Unsupported(
+                "the process was not spawned in a process group of its own"
+                    .into(),
+            )
+            .into());
+        }
+        // The group outlives its leader, so this works even after it exited.
+        let status = send_kill(self.child.id(), true)?;
+        if !status.success() && self.child.try_wait()?.is_none() {
+            return Err(Error::ProcessCrashed {
+                program: "kill".into(),
+                status,
+                stderr: String::new(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Whether the watchdog of [`WineCommand::spawn_with_watchdog`] found the
+    /// program hung and it has not shown signs of life since.
+    pub fn is_hung(&self) -> bool {
+        self.hung.load(Ordering::SeqCst)
+    }
+
+    pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        self.child.try_wait()
+    }
+
+    /// Waits for the program to exit and for its output to be delivered.
+    pub fn wait(&mut self) -> io::Result<ExitStatus> {
+        let status = self.child.wait()?;
+        for reader in self.readers.drain(..) {
+            let _ = reader.join();
+        }
+        Ok(status)
+    }
+}
+
+impl Drop for WineChild {
+    fn drop(&mut self) {
+        let result = match self.kill_on_drop {
+            KillOnDrop::Never => return,
+            KillOnDrop::Process => match self.child.try_wait() {
+                Ok(None) => self.child.kill(),
+                _ => Ok(()),
+            },
+            KillOnDrop::ProcessTree => self.kill_tree(),
+        };
+        if result.is_ok() {
+            let _ = self.child.wait();
+        }
+    }
+}
@@ -0,0 +1,108 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    io,
+    process::ExitStatus,
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::WineCommand;
+
+/// How often a timed launch polls for the wineserver, which bounds the
+/// resolution of [`LaunchTimings::server_start`].
+const SERVER_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The phases of a launch, each measured from the moment it began.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LaunchTimings {
+    /// Until the loader process was created.
+    pub loader_start: Duration,
+    /// Until the wineserver accepted connections, or `None` if it was already
+    /// running or was never seen.
+    pub server_start: Option<Duration>,
+    /// Until the process exited.
+    pub exit: Duration,
+}
+
+impl WineCommand<'_> {
+    /// Like [`WineCommand::status`], but also measures the phases of the
+    /// launch. The start of the wineserver is only seen to within 50 ms.
+    pub fn status_timed(&self) -> io::Result<(ExitStatus, LaunchTimings)> {
+        let was_server_running = self.prefix.is_server_running();
+        let start = Instant::now();
+        let mut command = self.resolve();
+        let mut child = self.spawn_resolved("status_timed", &mut command)?;
+        let loader_start = start.elapsed();
+
+        let (exited, exit) = mpsc::channel::<()>();
+        let (result, server_start) = thread::scope(|scope| {
+            let watcher = scope.spawn(move || {
+                if was_server_running {
+                    return None;
+                }
+                loop {
+                    if self.prefix.is_server_running() {
+                        return Some(start.elapsed());
+                    }
+                    if exit.recv_timeout(SERVER_POLL_INTERVAL)
+                        != Err(mpsc::RecvTimeoutError::Timeout)
+                    {
+                        return None;
+                    }
+                }
+            });
+            let result = child.wait();
+            drop(exited);
+            (result, watcher.join().unwrap())
+        });
+        let exit = start.elapsed();
+        let status = self.finish(&self.all_hooks(), &command, result)?;
+
+        Ok((
+            status,
+            LaunchTimings {
+                loader_start,
+                server_start,
+                exit,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    };
+
+    use crate::{PrefixBuilder, runner::RecordingRunner};
+
+    #[test]
+    fn status_timed_runs_the_launch_hooks() {
+        let (prefix, runner) =
+            RecordingRunner::attach(PrefixBuilder::new("/prefix").build());
+        let exited = Arc::new(AtomicBool::new(false));
+        let mut command = prefix.wine_command("game.exe");
+        command
+            .pre_launch_hook(|command| command.args.push("-windowed".into()))
+            .post_exit_hook({
+                let exited = exited.clone();
+                move |_, status| {
+                    exited.store(status.unwrap().success(), Ordering::Relaxed);
+                }
+            });
+
+        let (status, timings) = command.status_timed().unwrap();
+        assert!(status.success());
+        assert!(exited.load(Ordering::Relaxed));
+        assert!(timings.loader_start <= timings.exit);
+        assert_eq!(runner.take()[0].args.last().unwrap(), "-windowed");
+    }
+}
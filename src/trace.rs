@@ -0,0 +1,79 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Instrumentation of prefix operations, compiled out unless the `tracing`
+//! feature is enabled.
+
+use std::{
+    ffi::OsStr,
+    io,
+    path::Path,
+    process::{self, ExitStatus},
+};
+
+/// Results from which the exit status of the underlying process can be read.
+pub(crate) trait Outcome {
+    #[cfg_attr(not(feature = "tracing"), expect(dead_code))]
+    fn exit_status(&self) -> Option<ExitStatus> {
+        None
+    }
+}
+
+impl Outcome for () {}
+
+impl Outcome for process::Child {}
+
+impl Outcome for ExitStatus {
+    fn exit_status(&self) -> Option<ExitStatus> {
+        Some(*self)
+    }
+}
+
+impl Outcome for process::Output {
+    fn exit_status(&self) -> Option<ExitStatus> {
+        Some(self.status)
+    }
+}
+
+/// Runs `body` inside a span describing `operation` on `prefix`.
+#[cfg(feature = "tracing")]
+pub(crate) fn operation<T: Outcome>(
+    operation: &'static str,
+    prefix: &Path,
+    program: Option<&OsStr>,
+    body: impl FnOnce() -> io::Result<T>,
+) -> io::Result<T> {
+    let span = tracing::info_span!(
+        "wine",
+        operation,
+        prefix = %prefix.display(),
+        program = program.map(|program| program.to_string_lossy().into_owned()),
+    );
+    let _entered = span.enter();
+    let start = std::time::Instant::now();
+    let result = body();
+    let duration = start.elapsed();
+    match &result {
+        Ok(outcome) => match outcome.exit_status() {
+            Some(status) => {
+                tracing::info!(?duration, %status, "finished");
+            }
+            None => tracing::info!(?duration, "finished"),
+        },
+        Err(error) => tracing::warn!(?duration, %error, "failed"),
+    }
+    result
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn operation<T: Outcome>(
+    _operation: &'static str,
+    _prefix: &Path,
+    _program: Option<&OsStr>,
+    body: impl FnOnce() -> io::Result<T>,
+) -> io::Result<T> {
+    body()
+}
@@ -0,0 +1,81 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Copying prefixes, which are full of symlinks that must stay symlinks.
+
+use std::{
+    fs, io,
+    os::unix::fs::symlink,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// Copies the directory `source` to `destination`, which must not exist,
+/// recreating symlinks rather than following them. Sockets and other special
+/// files are skipped.
+pub(crate) fn copy_tree(source: &Path, destination: &Path) -> io::Result<()> {
+    fs::create_dir(destination)?;
+    fs::set_permissions(destination, fs::metadata(source)?.permissions())?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let from = entry.path();
+        let to = destination.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        if file_type.is_symlink() {
+            symlink(fs::read_link(&from)?, &to)?;
+        } else if file_type.is_dir() {
+            copy_tree(&from, &to)?;
+        } else if file_type.is_file() {
+            fs::copy(&from, &to)?;
+        }
+    }
+    Ok(())
+}
+
+/// Copies the file or directory `source` to `destination`, which must not
+/// exist, sharing the data of files copy-on-write on filesystems that
+/// support it, such as Btrfs, XFS, and APFS.
+///
+/// Hard links would be cheaper still, but programs that write to a file in
+/// place would then change both copies.
+pub(crate) fn clone_tree(source: &Path, destination: &Path) -> io::Result<()> {
+    let mut cp = Command::new("cp");
+    if cfg!(target_os = "macos") {
+        cp.arg("-c");
+    } else {
+        cp.arg("--reflink=auto");
+    }
+    let cloned = cp
+        .arg("-a")
+        .arg(source)
+        .arg(destination)
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+    if cloned {
+        return Ok(());
+    }
+    remove_tree(destination)?;
+    if fs::symlink_metadata(source)?.is_dir() {
+        copy_tree(source, destination)
+    } else {
+        fs::copy(source, destination).map(drop)
+    }
+}
+
+/// Removes the file or directory `path` with everything below it, doing
+/// nothing if it does not exist.
+pub(crate) fn remove_tree(path: &Path) -> io::Result<()> {
+    let result = match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(error) => Err(error),
+    };
+    match result {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{fs, io, path::Path};
+
+use crate::{
+    Error, Prefix, serialize::serialize_fields, verify::REGISTRY_FILES,
+};
+
+/// Sizes in bytes. Symlinks are not followed, so folders linked into the host
+/// home directory count as nothing.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DiskUsage {
+    pub total: u64,
+    pub drive_c: u64,
+    /// `C:\windows`.
+    pub windows: u64,
+    /// `C:\Program Files` and `C:\Program Files (x86)`.
+    pub program_files: u64,
+    /// `C:\users`.
+    pub users: u64,
+    /// The `.reg` files at the root of the prefix.
+    pub registry: u64,
+}
+
+serialize_fields!(DiskUsage {
+    total,
+    drive_c,
+    windows,
+    program_files,
+    users,
+    registry
+});
+
+fn tree_size(path: &Path) -> io::Result<u64> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(error) => return Err(error),
+    };
+    if !metadata.is_dir() {
+        return Ok(if metadata.is_file() {
+            metadata.len()
+        } else {
+            0
+        });
+    }
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        size += tree_size(&entry?.path())?;
+    }
+    Ok(size)
+}
+
+impl Prefix {
+    /// Walks the prefix to report what is taking up space.
+    pub fn disk_usage(&self) -> io::Result<DiskUsage> {
+        self.check_exists()?;
+        let drive_c = self.drive_c();
+        let mut registry = 0;
+        for registry_file in REGISTRY_FILES {
+            registry += tree_size(&self.path.join(registry_file))?;
+        }
+        Ok(DiskUsage {
+            total: tree_size(&self.path)?,
+            drive_c: tree_size(&drive_c)?,
+            windows: tree_size(&drive_c.join("windows"))?,
+            program_files: tree_size(&drive_c.join("Program Files"))?
+                + tree_size(&drive_c.join("Program Files (x86)"))?,
+            users: tree_size(&drive_c.join("users"))?,
+            registry,
+        })
+    }
+}
+
+/// Removes everything inside `dir` without following symlinks, returning the
+/// number of bytes freed.
+fn clear_dir(dir: &Path) -> io::Result<u64> {
+    if dir.is_symlink() {
+        return Ok(0);
+    }
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(error) => return Err(error),
+    };
+    let mut freed = 0;
+    for entry in entries {
+        let path = entry?.path();
+        freed += tree_size(&path)?;
+        if fs::symlink_metadata(&path)?.is_dir() {
+            fs::remove_dir_all(&path)?;
+        } else {
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(freed)
+}
+
+impl Prefix {
+    /// Empties `C:\windows\temp` and the temporary directories of each user,
+    /// returning the number of bytes freed. Fails while the wineserver is
+    /// running, since programs may still be using those files.
+    pub fn clean_temp(&self) -> io::Result<u64> {
+        let _lock = self.lock()?;
+        if self.is_server_running() {
+            return Err(
+                Error::PrefixBusy("clean temporary files".to_owned()).into()
+            );
+        }
+
+        let drive_c = self.drive_c();
+        let mut temp_dirs = vec![self.windows_dir().join("temp")];
+        if let Ok(users) = fs::read_dir(drive_c.join("users")) {
+            for user in users {
+                let user = user?.path();
+                temp_dirs.push(user.join("AppData").join("Local").join("Temp"));
+                temp_dirs.push(user.join("Temp"));
+            }
+        }
+
+        let mut freed = 0;
+        for temp_dir in temp_dirs {
+            freed += clear_dir(&temp_dir)?;
+        }
+        Ok(freed)
+    }
+}
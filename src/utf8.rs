@@ -0,0 +1,73 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! [`camino`] counterparts of the path helpers, with the `camino` feature,
+//! which fail with [`io::ErrorKind::InvalidData`] for paths that are not
+//! UTF-8.
+
+use std::{io, path::PathBuf};
+
+use camino::{FromPathBufError, FromPathError, Utf8Path, Utf8PathBuf};
+
+use crate::{Prefix, PrefixStore};
+
+fn to_utf8(path: PathBuf) -> io::Result<Utf8PathBuf> {
+    Utf8PathBuf::try_from(path).map_err(FromPathBufError::into_io_error)
+}
+
+impl Prefix {
+    /// [`Prefix::path`] as UTF-8.
+    pub fn utf8_path(&self) -> io::Result<&Utf8Path> {
+        <&Utf8Path>::try_from(self.path.as_path())
+            .map_err(FromPathError::into_io_error)
+    }
+
+    /// [`Prefix::drive_c`] as UTF-8.
+    pub fn utf8_drive_c(&self) -> io::Result<Utf8PathBuf> {
+        to_utf8(self.drive_c())
+    }
+
+    /// [`Prefix::resolve_guest_path`] as UTF-8.
+    pub fn resolve_guest_utf8_path(
+        &self,
+        windows_path: &str,
+    ) -> io::Result<Utf8PathBuf> {
+        to_utf8(self.resolve_guest_path(windows_path)?)
+    }
+
+    /// [`Prefix::windows_path`] as UTF-8, which a relative `path` may not be
+    /// if the current directory is not.
+    pub fn utf8_windows_path(
+        &self,
+        path: impl AsRef<Utf8Path>,
+    ) -> io::Result<String> {
+        self.windows_path(path.as_ref())
+            .into_string()
+            .map_err(|path| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("`{}` is not UTF-8", path.to_string_lossy()),
+                )
+            })
+    }
+
+    /// [`Prefix::default_path`] as UTF-8.
+    pub fn utf8_default_path() -> io::Result<Utf8PathBuf> {
+        to_utf8(Self::default_path()?)
+    }
+}
+
+impl PrefixStore {
+    /// [`PrefixStore::root`] as UTF-8.
+    pub fn utf8_root(&self) -> io::Result<&Utf8Path> {
+        <&Utf8Path>::try_from(self.root()).map_err(FromPathError::into_io_error)
+    }
+
+    /// [`PrefixStore::path_of`] as UTF-8.
+    pub fn utf8_path_of(&self, name: &str) -> io::Result<Utf8PathBuf> {
+        to_utf8(self.path_of(name)?)
+    }
+}
@@ -0,0 +1,295 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    ffi::OsStr,
+    fs,
+    io::{self, BufRead, BufReader},
+    os::unix,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    Prefix, ResolvedCommand, VersionMismatch, installation,
+    serialize::serialize_fields,
+};
+
+pub const REGISTRY_FILES: &[&str] = &["system.reg", "user.reg", "userdef.reg"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PrefixIssue {
+    MissingDriveC,
+    MissingRegistryFile(&'static str),
+    MissingDosDevices,
+    /// `dosdevices/c:` does not lead to `drive_c`.
+    BrokenSystemDrive,
+    DanglingSymlink(PathBuf),
+    /// The architecture recorded in `system.reg` disagrees with the directory
+    /// layout of `drive_c`.
+    ArchMismatch {
+        registry: String,
+        layout: String,
+    },
+    VersionMismatch(VersionMismatch),
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct VerifyReport {
+    pub issues: Vec<PrefixIssue>,
+}
+
+impl VerifyReport {
+    pub fn is_healthy(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+serialize_fields!(VerifyReport { issues });
+
+/// As a map whose `type` field is the variant name in snake case.
+#[cfg(feature = "serde")]
+impl serde::Serialize for PrefixIssue {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        macro_rules! issue {
+            ($type:literal $(, $field:literal: $value:expr)*) => {{
+                let mut state = serializer.serialize_struct(
+                    "PrefixIssue",
+                    1 + <[&str]>::len(&[$($field),*]),
+                )?;
+                state.serialize_field("type", $type)?;
+                $(state.serialize_field($field, $value)?;)*
+                state.end()
+            }};
+        }
+
+        match self {
+            Self::MissingDriveC => issue!("missing_drive_c"),
+            Self::MissingRegistryFile(file) => {
+                issue!("missing_registry_file", "file": file)
+            }
+            Self::MissingDosDevices => issue!("missing_dos_devices"),
+            Self::BrokenSystemDrive => issue!("broken_system_drive"),
+            Self::DanglingSymlink(path) => {
+                issue!("dangling_symlink", "path": path)
+            }
+            Self::ArchMismatch { registry, layout } => issue!(
+                "arch_mismatch",
+                "registry": registry,
+                "layout": layout
+            ),
+            Self::VersionMismatch(mismatch) => issue!(
+                "version_mismatch",
+                "wine": &mismatch.wine,
+                "wineserver": &mismatch.wineserver
+            ),
+        }
+    }
+}
+
+fn dangling_symlinks(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut dangling = Vec::new();
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            return Ok(dangling);
+        }
+        Err(error) => return Err(error),
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_symlink() && !path.exists() {
+            dangling.push(path);
+        }
+    }
+    Ok(dangling)
+}
+
+/// Whether `path` is named like the link of a drive, e.g. `c:`.
+fn is_drive_link(path: &Path) -> bool {
+    path.file_name()
+        .and_then(OsStr::to_str)
+        .and_then(|name| name.strip_suffix(':'))
+        .is_some_and(|letter| {
+            letter.len() == 1 && letter.chars().all(|c| c.is_ascii_alphabetic())
+        })
+}
+
+/// Reads the `#arch=` header that `wineboot` writes into `system.reg`.
+fn registry_arch(system_reg: &Path) -> io::Result<Option<String>> {
+    let reader = BufReader::new(fs::File::open(system_reg)?);
+    for line in reader.lines().take(8) {
+        if let Some(arch) = line?.strip_prefix("#arch=") {
+            return Ok(Some(arch.trim().to_owned()));
+        }
+    }
+    Ok(None)
+}
+
+impl Prefix {
+    /// The `--version` output of `binary`, if it runs, in the environment of
+    /// the prefix.
+    pub(crate) fn version_output(
+        &self,
+        binary: impl AsRef<OsStr>,
+    ) -> Option<String> {
+        let mut command = ResolvedCommand::new(binary, &self.path);
+        self.environment_policy().apply(&mut command);
+        command.args.push("--version".into());
+        installation::parse_version_output(self.runner.0.output(&command).ok()?)
+    }
+
+    /// Checks that the prefix has the structure `wineboot` creates, and that
+    /// its `wine` and `wineserver` binaries belong together.
+    pub fn verify(&self) -> io::Result<VerifyReport> {
+        let mut report = VerifyReport::default();
+
+        let drive_c = self.drive_c();
+        if !drive_c.is_dir() {
+            report.issues.push(PrefixIssue::MissingDriveC);
+        }
+        for registry_file in REGISTRY_FILES {
+            if !self.path.join(registry_file).is_file() {
+                report
+                    .issues
+                    .push(PrefixIssue::MissingRegistryFile(registry_file));
+            }
+        }
+
+        let dosdevices = self.path.join("dosdevices");
+        if !dosdevices.is_dir() {
+            report.issues.push(PrefixIssue::MissingDosDevices);
+        } else if fs::canonicalize(dosdevices.join("c:")).ok()
+            != fs::canonicalize(&drive_c).ok()
+        {
+            report.issues.push(PrefixIssue::BrokenSystemDrive);
+        }
+
+        let mut symlink_dirs = vec![dosdevices];
+        if let Ok(users) = fs::read_dir(drive_c.join("users")) {
+            for user in users {
+                symlink_dirs.push(user?.path());
+            }
+        }
+        for dir in symlink_dirs {
+            report.issues.extend(
+                dangling_symlinks(&dir)?
+                    .into_iter()
+                    .map(PrefixIssue::DanglingSymlink),
+            );
+        }
+
+        let system_reg = self.path.join("system.reg");
+        if system_reg.is_file()
+            && drive_c.is_dir()
+            && let Some(registry) = registry_arch(&system_reg)?
+        {
+            let layout = if self.is_64_bit() { "win64" } else { "win32" };
+            if registry != layout {
+                report.issues.push(PrefixIssue::ArchMismatch {
+                    registry,
+                    layout: layout.to_owned(),
+                });
+            }
+        }
+
+        if let Err(mismatch) = installation::check_versions(
+            self.version_output(&self.wine).as_deref(),
+            self.version_output(&self.wineserver).as_deref(),
+        ) {
+            report.issues.push(PrefixIssue::VersionMismatch(mismatch));
+        }
+
+        Ok(report)
+    }
+
+    /// Re-runs `wineboot` to restore missing files and relinks the drives in
+    /// `dosdevices`, returning what remains broken afterwards. The `Z:` drive
+    /// is left alone in a sandboxed prefix, and a `c:` that is a directory
+    /// rather than a link remains a [`PrefixIssue::BrokenSystemDrive`].
+    pub fn repair(&self) -> io::Result<VerifyReport> {
+        let _lock = self.lock()?;
+        self.update_locked()?;
+
+        let dosdevices = self.path.join("dosdevices");
+        fs::create_dir_all(&dosdevices)?;
+        // Device links like `d::` are left to Wine.
+        for path in dangling_symlinks(&dosdevices)? {
+            if is_drive_link(&path) {
+                fs::remove_file(path)?;
+            }
+        }
+        // A real `c:` may hold data, so it is only reported as broken.
+        let system_drive = dosdevices.join("c:");
+        if fs::canonicalize(&system_drive).ok()
+            != fs::canonicalize(self.drive_c()).ok()
+            && (system_drive.is_symlink() || !system_drive.exists())
+        {
+            if system_drive.is_symlink() {
+                fs::remove_file(&system_drive)?;
+            }
+            unix::fs::symlink("../drive_c", &system_drive)?;
+        }
+        let root_drive = dosdevices.join("z:");
+        if !self.is_sandboxed() && !root_drive.is_symlink() {
+            unix::fs::symlink("/", root_drive)?;
+        }
+
+        self.verify()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, os::unix, process};
+
+    use super::PrefixIssue;
+    use crate::{
+        EnvironmentPolicy, PrefixBuilder, PrefixConfig, runner::RecordingRunner,
+    };
+
+    #[test]
+    fn version_probes_use_the_prefix_environment() {
+        let mut builder = PrefixBuilder::new("/prefix");
+        builder.config(PrefixConfig {
+            environment: Some(EnvironmentPolicy::Deny(vec!["DISPLAY".into()])),
+            ..PrefixConfig::default()
+        });
+        let (prefix, runner) = RecordingRunner::attach(builder.build());
+
+        prefix.version_output(&prefix.wine);
+        let recorded = runner.take();
+        assert_eq!(recorded[0].args, ["--version"]);
+        assert_eq!(recorded[0].unset_envs, ["DISPLAY"]);
+    }
+
+    #[test]
+    fn repair_leaves_device_links_and_real_system_drives() {
+        let path = env::temp_dir()
+            .join(format!("wine-rs-verify-test-{}", process::id()));
+        let dosdevices = path.join("dosdevices");
+        fs::create_dir_all(dosdevices.join("c:")).unwrap();
+        fs::create_dir_all(path.join("drive_c")).unwrap();
+        unix::fs::symlink("/nonexistent/cdrom", dosdevices.join("d::"))
+            .unwrap();
+        unix::fs::symlink("/nonexistent/mount", dosdevices.join("e:")).unwrap();
+        let (prefix, _) =
+            RecordingRunner::attach(PrefixBuilder::new(&path).build());
+
+        let report = prefix.repair().unwrap();
+        assert!(dosdevices.join("c:").is_dir());
+        assert!(!dosdevices.join("c:").is_symlink());
+        assert!(report.issues.contains(&PrefixIssue::BrokenSystemDrive));
+        assert!(dosdevices.join("d::").is_symlink());
+        assert!(!dosdevices.join("e:").is_symlink());
+
+        fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -0,0 +1,196 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use crate::Prefix;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InstalledFileKind {
+    /// An `.exe`.
+    Executable,
+    /// A `.lnk`, e.g., in the Start Menu.
+    Shortcut,
+}
+
+/// A file that appeared in `drive_c` while an [`InstallWatcher`] ran.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InstalledFile {
+    /// On the host.
+    pub path: PathBuf,
+    pub kind: InstalledFileKind,
+}
+
+fn kind_of(path: &Path) -> Option<InstalledFileKind> {
+    let extension = path.extension()?;
+    if extension.eq_ignore_ascii_case("exe") {
+        Some(InstalledFileKind::Executable)
+    } else if extension.eq_ignore_ascii_case("lnk") {
+        Some(InstalledFileKind::Shortcut)
+    } else {
+        None
+    }
+}
+
+/// What is known of `drive_c`, so that a poll only lists the directories
+/// whose modification time moved, i.e., that gained or lost entries.
+#[derive(Default)]
+struct Scan {
+    dirs: HashMap<PathBuf, Option<SystemTime>>,
+    seen: HashSet<PathBuf>,
+}
+
+impl Scan {
+    /// Lists `dir` and the new directories under it. Symlinks are not
+    /// followed, since Wine links the shell folders of users to their home
+    /// directory on the host.
+    fn list(&mut self, dir: &Path, found: &mut Vec<InstalledFile>) {
+        let modified =
+            fs::metadata(dir).and_then(|metadata| metadata.modified());
+        self.dirs.insert(dir.to_path_buf(), modified.ok());
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            if file_type.is_dir() {
+                if !self.dirs.contains_key(&path) {
+                    self.list(&path, found);
+                }
+            } else if file_type.is_file()
+                && let Some(kind) = kind_of(&path)
+                && self.seen.insert(path.clone())
+            {
+                found.push(InstalledFile { path, kind });
+            }
+        }
+    }
+
+    fn poll(&mut self) -> Vec<InstalledFile> {
+        let mut found = Vec::new();
+        let changed: Vec<_> = self
+            .dirs
+            .iter()
+            .filter(|(dir, modified)| {
+                fs::metadata(dir)
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+                    != **modified
+            })
+            .map(|(dir, _)| dir.clone())
+            .collect();
+        for dir in changed {
+            if dir.is_dir() {
+                self.list(&dir, &mut found);
+            } else {
+                self.dirs.remove(&dir);
+            }
+        }
+        found
+    }
+}
+
+/// Reports the executables and shortcuts created in `drive_c` while an
+/// installer runs, e.g., to offer launching the installed programs without
+/// rescanning the prefix afterwards.
+///
+/// It polls from a thread of its own, which stops when this is dropped.
+#[derive(Debug)]
+pub struct InstallWatcher {
+    stop: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+    receiver: mpsc::Receiver<InstalledFile>,
+}
+
+impl InstallWatcher {
+    /// The files found since this was last asked, without blocking.
+    pub fn try_iter(&self) -> impl Iterator<Item = InstalledFile> + '_ {
+        self.receiver.try_iter()
+    }
+
+    /// Waits up to `timeout` for the next file to be found.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<InstalledFile> {
+        self.receiver.recv_timeout(timeout).ok()
+    }
+
+    /// Stops watching after one last poll, e.g., once the installer has
+    /// exited, and returns the files not yet received.
+    pub fn finish(mut self) -> Vec<InstalledFile> {
+        self.stop();
+        self.receiver.try_iter().collect()
+    }
+
+    fn stop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for InstallWatcher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl Prefix {
+    /// Starts watching `drive_c`, checking for new files every `interval`.
+    /// The files already there are listed before this returns, so only ones
+    /// created afterwards are reported.
+    pub fn watch_installs(
+        &self,
+        interval: Duration,
+    ) -> io::Result<InstallWatcher> {
+        let drive_c = self.drive_c();
+        if !drive_c.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} does not exist", drive_c.display()),
+            ));
+        }
+        let mut scan = Scan::default();
+        scan.list(&drive_c, &mut Vec::new());
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = mpsc::channel();
+        let thread = thread::spawn({
+            let stop = stop.clone();
+            move || {
+                loop {
+                    let stopping = stop.load(Ordering::Relaxed);
+                    for file in scan.poll() {
+                        let _ = sender.send(file);
+                    }
+                    if stopping {
+                        return;
+                    }
+                    thread::park_timeout(interval);
+                }
+            }
+        });
+        Ok(InstallWatcher {
+            stop,
+            thread: Some(thread),
+            receiver,
+        })
+    }
+}
@@ -0,0 +1,169 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    io,
+    sync::{atomic::Ordering, mpsc},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    KillOnDrop, OutputLine, WineChild, WineCommand, stream::send_kill,
+};
+
+/// How [`WineCommand::spawn_with_watchdog`] decides that a program is hung:
+/// once it has printed nothing and used next to no CPU for `quiet_period`.
+///
+/// A program spinning in a busy loop keeps the CPU busy and so is never
+/// considered hung.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Watchdog {
+    pub quiet_period: Duration,
+    /// How often the CPU time of the program is sampled.
+    pub sample_interval: Duration,
+    /// The CPU time a sample may add and still count as quiet, since threads
+    /// of Wine itself wake up now and then.
+    pub idle_cpu: Duration,
+    /// Whether to kill the program once the hang was reported, which kills
+    /// the processes it started too under [`KillOnDrop::ProcessTree`].
+    pub kill_on_hang: bool,
+}
+
+impl Default for Watchdog {
+    fn default() -> Self {
+        Self {
+            quiet_period: Duration::from_secs(300),
+            sample_interval: Duration::from_secs(1),
+            idle_cpu: Duration::from_millis(10),
+            kill_on_hang: false,
+        }
+    }
+}
+
+/// A program found hung by its [`Watchdog`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Hang {
+    /// The host process ID of the program.
+    pub pid: u32,
+    /// How long the program has shown no signs of life.
+    pub quiet_for: Duration,
+}
+
+/// The CPU time used so far by the process `pid`, or by every process in its
+/// process group if `group`.
+///
+/// Only Linux exposes it without extra dependencies, so elsewhere this is
+/// always zero and only output counts as a sign of life.
+#[cfg(target_os = "linux")]
+fn cpu_time(pid: u32, group: bool) -> Duration {
+    /// The unit of `/proc/<pid>/stat`, which Linux fixes at 100 Hz.
+    const TICKS_PER_SECOND: u64 = 100;
+
+    let ticks = |stat: &str, group_id: Option<u32>| -> Option<u64> {
+        // The command name may contain anything, so fields are counted from
+        // its closing parenthesis, after which the state is the first.
+        let (_, fields) = stat.rsplit_once(')')?;
+        let fields = fields.split_whitespace().collect::<Vec<_>>();
+        if let Some(group_id) = group_id
+            && fields.get(2)?.parse::<u32>().ok()? != group_id
+        {
+            return None;
+        }
+        Some(
+            fields.get(11)?.parse::<u64>().ok()?
+                + fields.get(12)?.parse::<u64>().ok()?,
+        )
+    };
+    let total = if group {
+        std::fs::read_dir("/proc")
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| {
+                entry.file_name().to_str().is_some_and(|name| {
+                    name.bytes().all(|byte| byte.is_ascii_digit())
+                })
+            })
+            .filter_map(|entry| {
+                std::fs::read_to_string(entry.path().join("stat")).ok()
+            })
+            .filter_map(|stat| ticks(&stat, Some(pid)))
+            .sum()
+    } else {
+        std::fs::read_to_string(format!("/proc/{pid}/stat"))
+            .ok()
+            .and_then(|stat| ticks(&stat, None))
+            .unwrap_or(0)
+    };
+    Duration::from_millis(total * 1000 / TICKS_PER_SECOND)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn cpu_time(_pid: u32, _group: bool) -> Duration {
+    Duration::ZERO
+}
+
+impl WineCommand<'_> {
+    /// Like [`WineCommand::spawn_with_lines`], but calls `on_hang` from the
+    /// background thread whenever the program goes quiet according to
+    /// `watchdog`, e.g., to capture a backtrace of it with winedbg before it
+    /// is killed. Unlike a timeout, this lets long installers run for as long
+    /// as they make progress.
+    ///
+    /// The hang is reported once per quiet stretch, and
+    /// [`WineChild::is_hung`] tells whether the program is still hung.
+    pub fn spawn_with_watchdog(
+        &self,
+        watchdog: Watchdog,
+        mut on_line: impl FnMut(OutputLine) + Send + 'static,
+        mut on_hang: impl FnMut(&Hang) + Send + 'static,
+    ) -> io::Result<WineChild> {
+        let (mut child, receiver) = self.spawn_with_channel()?;
+        let pid = child.id();
+        let group = self.kill_on_drop == KillOnDrop::ProcessTree;
+        let hung = child.hung.clone();
+        child.readers.push(thread::spawn(move || {
+            let mut cpu = cpu_time(pid, group);
+            let mut last_sample = Instant::now();
+            let mut last_activity = Instant::now();
+            loop {
+                let wait = watchdog
+                    .sample_interval
+                    .saturating_sub(last_sample.elapsed());
+                match receiver.recv_timeout(wait) {
+                    Ok(line) => {
+                        on_line(line);
+                        last_activity = Instant::now();
+                        hung.store(false, Ordering::SeqCst);
+                    }
+                    Err(mpsc::RecvTimeoutError::Timeout) => {}
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+                if last_sample.elapsed() < watchdog.sample_interval {
+                    continue;
+                }
+                let sample = cpu_time(pid, group);
+                if sample.saturating_sub(cpu) > watchdog.idle_cpu {
+                    last_activity = Instant::now();
+                }
+                cpu = sample;
+                last_sample = Instant::now();
+
+                let quiet_for = last_activity.elapsed();
+                if quiet_for < watchdog.quiet_period {
+                    hung.store(false, Ordering::SeqCst);
+                } else if !hung.swap(true, Ordering::SeqCst) {
+                    on_hang(&Hang { pid, quiet_for });
+                    if watchdog.kill_on_hang {
+                        let _ = send_kill(pid, group);
+                    }
+                }
+            }
+        }));
+        Ok(child)
+    }
+}
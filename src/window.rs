@@ -0,0 +1,152 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    ffi::OsStr,
+    fs, io,
+    os::unix::ffi::OsStrExt,
+    process::{self, Command},
+};
+
+use crate::{Error, Prefix, serialize::serialize_fields};
+
+/// A top-level window that Wine shows on the X11 display of the host.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WineWindow {
+    /// The X11 window ID.
+    pub id: u64,
+    pub title: String,
+    /// The executable file name of the program showing the window, e.g.,
+    /// `notepad.exe`, which Wine sets as its class.
+    pub program: String,
+}
+
+serialize_fields!(WineWindow { id, title, program });
+
+impl WineWindow {
+    /// Whether this is the window of a virtual desktop, see
+    /// [`Prefix::set_virtual_desktop`].
+    pub fn is_virtual_desktop(&self) -> bool {
+        self.program.eq_ignore_ascii_case("explorer.exe")
+            && self.title.ends_with("Wine desktop")
+    }
+}
+
+/// Runs a host tool to completion, failing with its stderr if it does. The
+/// error for a missing tool names `package`, which provides it.
+pub(crate) fn host_tool(
+    program: &str,
+    package: &str,
+    args: impl IntoIterator<Item = impl AsRef<OsStr>>,
+) -> io::Result<process::Output> {
+    let output =
+        Command::new(program).args(args).output().map_err(|error| {
+            if error.kind() == io::ErrorKind::NotFound {
+                Error::NotInstalled(format!("`{program}` from {package}"))
+                    .into()
+            } else {
+                error
+            }
+        })?;
+    if !output.status.success() {
+        return Err(Error::ProcessCrashed {
+            program: program.into(),
+            status: output.status,
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        }
+        .into());
+    }
+    Ok(output)
+}
+
+/// Parses the output of `xwininfo -root -tree`, whose lines for windows
+/// look like `0x1e00004 "Notepad": ("notepad.exe" "Notepad.exe")  ...`,
+/// keeping those whose class names an executable.
+fn parse_xwininfo(output: &str) -> Vec<WineWindow> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (id, rest) = line.trim_start().split_once(' ')?;
+            let id = u64::from_str_radix(id.strip_prefix("0x")?, 16).ok()?;
+            let (title, class) =
+                rest.strip_prefix('"')?.rsplit_once("\": (")?;
+            let (class, _) = class.split_once(')')?;
+            let program = class.split('"').nth(1)?;
+            if !program.to_ascii_lowercase().ends_with(".exe") {
+                return None;
+            }
+            Some(WineWindow {
+                id,
+                title: title.to_owned(),
+                program: program.to_owned(),
+            })
+        })
+        .collect()
+}
+
+/// The `WINEPREFIX` of the process showing the window `id`, if it can be
+/// told, which Linux allows through `/proc`.
+fn window_prefix(id: u64) -> Option<Vec<u8>> {
+    let output = host_tool(
+        "xprop",
+        "x11-utils",
+        ["-id", &id.to_string(), "_NET_WM_PID"],
+    )
+    .ok()?;
+    let output = String::from_utf8_lossy(&output.stdout);
+    let (_, pid) = output.split_once(" = ")?;
+    let pid: u32 = pid.trim().parse().ok()?;
+    let environ = fs::read(format!("/proc/{pid}/environ")).ok()?;
+    environ
+        .split(|&byte| byte == 0)
+        .find_map(|var| var.strip_prefix(b"WINEPREFIX="))
+        .map(<[u8]>::to_vec)
+}
+
+/// The first of `windows` whose title is `title`, or else contains it.
+pub(crate) fn find_titled<'a>(
+    windows: &'a [WineWindow],
+    title: &str,
+) -> Option<&'a WineWindow> {
+    windows
+        .iter()
+        .find(|window| window.title == title)
+        .or_else(|| windows.iter().find(|window| window.title.contains(title)))
+}
+
+impl Prefix {
+    /// The windows that programs in this prefix show on the display in
+    /// `DISPLAY`, from the top of the stacking order down, found with
+    /// `xwininfo` and `xprop`. Windows whose process cannot be inspected,
+    /// e.g., on hosts other than Linux, are listed whatever their prefix.
+    pub fn windows(&self) -> io::Result<Vec<WineWindow>> {
+        let output = host_tool("xwininfo", "x11-utils", ["-root", "-tree"])?;
+        let mut windows =
+            parse_xwininfo(&String::from_utf8_lossy(&output.stdout));
+        // `xwininfo` lists the windows from the bottom up.
+        windows.reverse();
+        windows.retain(|window| {
+            window_prefix(window.id).is_none_or(|prefix| {
+                fs::canonicalize(OsStr::from_bytes(&prefix)).ok()
+                    == fs::canonicalize(&self.path).ok()
+            })
+        });
+        Ok(windows)
+    }
+
+    /// The topmost window of the prefix whose title is `title`, or else
+    /// contains it. Fails with [`io::ErrorKind::NotFound`] if there is none.
+    pub fn find_window(&self, title: &str) -> io::Result<WineWindow> {
+        find_titled(&self.windows()?, title)
+            .cloned()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no window titled `{title}`"),
+                )
+            })
+    }
+}
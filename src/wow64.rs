@@ -0,0 +1,151 @@
+// Copyright (C) 2025 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{env, ffi::OsStr, fs, io, path::Path};
+
+use crate::{Error, Prefix, WineCommand, WineInstallation};
+
+/// How a Wine build runs 32-bit Windows programs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Wow64Mode {
+    /// Through a separate 32-bit loader and host libraries, as every build
+    /// did before the new WoW64 mode.
+    Legacy,
+    /// Through the 64-bit loader alone, which translates 32-bit calls itself.
+    /// Such builds cannot create `win32` prefixes.
+    New,
+    /// Not at all.
+    Win64Only,
+}
+
+/// The `lib/wine/<arch>` directories of the build installed at `root`, also
+/// looking under `lib64/` and the Debian multiarch directories.
+fn arch_dirs(root: &Path) -> Vec<String> {
+    let mut wine_dirs = Vec::new();
+    for lib in ["lib", "lib64", "lib32"] {
+        let lib = root.join(lib);
+        wine_dirs.push(lib.join("wine"));
+        if let Ok(entries) = fs::read_dir(&lib) {
+            wine_dirs.extend(
+                entries.flatten().map(|entry| entry.path().join("wine")),
+            );
+        }
+    }
+    wine_dirs
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect()
+}
+
+/// Detects the mode from the layout of the build at `root`, or returns `None`
+/// for builds older than the per-architecture layout of Wine 6.
+fn detect(root: &Path) -> Option<Wow64Mode> {
+    let arch_dirs = arch_dirs(root);
+    let has = |name: &str| arch_dirs.iter().any(|dir| dir == name);
+    if has("i386-unix") {
+        Some(Wow64Mode::Legacy)
+    } else if has("i386-windows") {
+        Some(Wow64Mode::New)
+    } else if has("x86_64-unix") {
+        Some(Wow64Mode::Win64Only)
+    } else if root.join("bin/wine64").is_file() {
+        Some(Wow64Mode::Legacy)
+    } else {
+        None
+    }
+}
+
+impl WineInstallation {
+    /// See [`Wow64Mode`].
+    pub fn wow64_mode(&self) -> Option<Wow64Mode> {
+        detect(&self.root)
+    }
+}
+
+impl Prefix {
+    /// How the configured Wine build runs 32-bit programs.
+    pub fn wow64_mode(&self) -> Option<Wow64Mode> {
+        detect(self.install_root())
+    }
+
+    /// Fails early, with a clearer message than `wineboot` gives, if
+    /// `command` would create a prefix of an architecture the build cannot
+    /// create, and otherwise asks builds that cannot create `win32` prefixes
+    /// for a `win64` one explicitly.
+    pub(crate) fn prepare_creation_arch(
+        &self,
+        command: &mut WineCommand<'_>,
+    ) -> io::Result<()> {
+        let resolved = command.resolve();
+        let arch = match resolved.env("WINEARCH") {
+            Some(arch) => Some(arch.to_os_string()),
+            None if !resolved.env_clear
+                && !resolved.unset_envs.iter().any(|var| var == "WINEARCH") =>
+            {
+                env::var_os("WINEARCH")
+            }
+            None => None,
+        };
+        let reason = match self.wow64_mode() {
+            Some(Wow64Mode::New) => {
+                "uses the new WoW64 mode, whose win64 prefixes run 32-bit \
+                 programs too"
+            }
+            Some(Wow64Mode::Win64Only) => "cannot run 32-bit programs",
+            Some(Wow64Mode::Legacy) | None => return Ok(()),
+        };
+        match arch.as_deref() {
+            Some(arch) if arch == OsStr::new("win32") => {
+                Err(Error::Unsupported(format!(
+                    "cannot create a win32 prefix: the Wine build at {} \
+                     {reason}",
+                    self.install_root().display()
+                ))
+                .into())
+            }
+            Some(_) => Ok(()),
+            None => {
+                command.env("WINEARCH", "win64");
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, ffi::OsStr, fs, process};
+
+    use crate::{EnvironmentPolicy, Error, PrefixBuilder};
+
+    #[test]
+    fn new_wow64_builds_create_win64_prefixes() {
+        let root = env::temp_dir()
+            .join(format!("wine-rs-wow64-test-{}", process::id()));
+        fs::create_dir_all(root.join("lib/wine/i386-windows")).unwrap();
+        let prefix = PrefixBuilder::new(root.join("prefix"))
+            .wine(root.join("bin/wine"))
+            .build();
+
+        let mut command = prefix.wine_command("wineboot");
+        command.environment(EnvironmentPolicy::Deny(vec!["WINEARCH".into()]));
+        prefix.prepare_creation_arch(&mut command).unwrap();
+        assert_eq!(
+            command.resolve().env("WINEARCH"),
+            Some(OsStr::new("win64"))
+        );
+
+        let mut command = prefix.wine_command("wineboot");
+        command.env("WINEARCH", "win32");
+        let error = prefix.prepare_creation_arch(&mut command).unwrap_err();
+        assert!(matches!(Error::from(error), Error::Unsupported(_)));
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}